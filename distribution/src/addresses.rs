@@ -0,0 +1,329 @@
+use std::collections::HashMap;
+use std::fs::File;
+
+use alloy::primitives::utils::parse_ether;
+
+use crate::prelude::*;
+
+/// A recipient read from an addresses file
+#[derive(Debug, Clone, PartialEq)]
+pub struct Recipient {
+    pub address: Address,
+    /// Amount in wei, from either the `amount` (ether) or `amount_wei` column
+    pub amount: Option<U256>,
+    pub uri: Option<String>,
+    pub token_id: Option<U256>,
+    pub label: Option<String>,
+    /// Relative share for weighted allocation strategies
+    pub weight: Option<f64>,
+}
+
+/// Columns recognised in CSV headers and JSON objects
+const COLUMNS: [&str; 7] = [
+    "address",
+    "amount",
+    "amount_wei",
+    "uri",
+    "token_id",
+    "label",
+    "weight",
+];
+
+/// Unvalidated columns of a single row
+#[derive(Debug, Default)]
+struct Row {
+    address: Option<String>,
+    amount: Option<String>,
+    amount_wei: Option<String>,
+    uri: Option<String>,
+    token_id: Option<String>,
+    label: Option<String>,
+    weight: Option<String>,
+}
+
+impl Row {
+    fn set(&mut self, column: &str, value: String) {
+        let value = Some(value).filter(|value| !value.is_empty());
+        match column {
+            "address" => self.address = value,
+            "amount" => self.amount = value,
+            "amount_wei" => self.amount_wei = value,
+            "uri" => self.uri = value,
+            "token_id" => self.token_id = value,
+            "label" => self.label = value,
+            "weight" => self.weight = value,
+            _ => {}
+        }
+    }
+
+    fn parse(self) -> std::result::Result<Recipient, String> {
+        let address = self.address.ok_or("missing address")?;
+        let address = address
+            .parse::<Address>()
+            .map_err(|_| format!("invalid address {}", address))?;
+        let amount = match (self.amount, self.amount_wei) {
+            (Some(_), Some(_)) => return Err("both amount and amount_wei are set".to_string()),
+            (Some(amount), None) => {
+                Some(parse_ether(&amount).map_err(|_| format!("invalid amount {}", amount))?)
+            }
+            (None, Some(amount_wei)) => Some(
+                amount_wei
+                    .parse::<U256>()
+                    .map_err(|_| format!("invalid amount_wei {}", amount_wei))?,
+            ),
+            (None, None) => None,
+        };
+        let token_id = self
+            .token_id
+            .map(|token_id| {
+                token_id
+                    .parse::<U256>()
+                    .map_err(|_| format!("invalid token_id {}", token_id))
+            })
+            .transpose()?;
+        let weight = self
+            .weight
+            .map(|weight| match weight.parse::<f64>() {
+                Ok(weight) if weight.is_finite() && weight >= 0.0 => Ok(weight),
+                _ => Err(format!("invalid weight {}", weight)),
+            })
+            .transpose()?;
+        Ok(Recipient {
+            address,
+            amount,
+            uri: self.uri,
+            token_id,
+            label: self.label,
+            weight,
+        })
+    }
+}
+
+/// Columns of a single row, in file order
+type Fields = Vec<(String, String)>;
+
+/// Read the rows of a CSV file, with an optional header row naming the columns
+///
+/// Without a header the columns are `address` and `amount`, as in the simple format, followed by
+/// unnamed ones.
+fn read_csv(path: &str) -> Result<Vec<(String, Fields)>> {
+    let mut reader = csv::ReaderBuilder::new()
+        .has_headers(false)
+        .flexible(true)
+        .trim(csv::Trim::All)
+        .from_path(path)?;
+    let mut columns = vec!["address".to_string(), "amount".to_string()];
+    let mut rows = Vec::new();
+    for (i, record) in reader.records().enumerate() {
+        let record = record?;
+        let line = record
+            .position()
+            .map_or(i as u64 + 1, |position| position.line());
+        if i == 0
+            && record
+                .iter()
+                .any(|field| field.eq_ignore_ascii_case("address"))
+        {
+            columns = record.iter().map(|field| field.to_lowercase()).collect();
+            continue;
+        }
+        let fields = record
+            .iter()
+            .enumerate()
+            .map(|(i, field)| {
+                let column = columns.get(i).cloned().unwrap_or_default();
+                (column, field.to_string())
+            })
+            .collect();
+        rows.push((format!("line {}", line), fields));
+    }
+    Ok(rows)
+}
+
+fn json_fields(value: Value) -> std::result::Result<Fields, String> {
+    let Value::Object(fields) = value else {
+        return Err("expected an object".to_string());
+    };
+    Ok(fields
+        .into_iter()
+        .filter_map(|(column, value)| match value {
+            Value::String(value) => Some((column, value)),
+            Value::Null => None,
+            value => Some((column, value.to_string())),
+        })
+        .collect())
+}
+
+/// Read the rows of a JSON array, or of JSONL when `lines` is set
+fn read_json(
+    path: &str,
+    lines: bool,
+) -> Result<Vec<(String, std::result::Result<Fields, String>)>> {
+    if lines {
+        let mut rows = Vec::new();
+        for (i, line) in io::BufReader::new(File::open(path)?).lines().enumerate() {
+            let line = line?;
+            if line.trim().is_empty() {
+                continue;
+            }
+            let row = serde_json::from_str(&line)
+                .map_err(|e| e.to_string())
+                .and_then(json_fields);
+            rows.push((format!("line {}", i + 1), row));
+        }
+        Ok(rows)
+    } else {
+        let values: Vec<Value> = serde_json::from_reader(io::BufReader::new(File::open(path)?))?;
+        Ok(values
+            .into_iter()
+            .enumerate()
+            .map(|(i, value)| (format!("entry {}", i + 1), json_fields(value)))
+            .collect())
+    }
+}
+
+/// Read the columns of every row of an addresses file, in whichever format it is in
+fn read_fields(
+    path: &str,
+    config: &AddressesConfig,
+) -> Result<Vec<(String, std::result::Result<Fields, String>)>> {
+    let format = config.format.unwrap_or_else(|| {
+        match Path::new(path).extension().and_then(|ext| ext.to_str()) {
+            Some("json") => AddressesFormat::Json,
+            Some("jsonl") => AddressesFormat::Jsonl,
+            _ => AddressesFormat::Csv,
+        }
+    });
+    Ok(match format {
+        AddressesFormat::Csv => read_csv(path)?
+            .into_iter()
+            .map(|(location, fields)| (location, Ok(fields)))
+            .collect(),
+        AddressesFormat::Json => read_json(path, false)?,
+        AddressesFormat::Jsonl => read_json(path, true)?,
+    })
+}
+
+/// Fail with every error collected in strict mode
+fn check_errors(path: &str, errors: Vec<String>) -> Result<()> {
+    if errors.is_empty() {
+        return Ok(());
+    }
+    Err(anyhow::anyhow!(
+        "Invalid addresses file {}:\n  {}",
+        path,
+        errors.join("\n  ")
+    ))
+}
+
+/// Read every row of an addresses file as raw columns, for contract call argument templates
+///
+/// Column 0 is always the address, followed by the row's other columns in file order (key order
+/// for JSON objects). Rows without a valid address are skipped with a warning, or all reported
+/// together in strict mode.
+pub fn read_rows(path: &str, config: &AddressesConfig) -> Result<Vec<Vec<String>>> {
+    let mut rows = Vec::new();
+    let mut errors = Vec::new();
+    for (location, fields) in read_fields(path, config)? {
+        let result = fields.and_then(|fields| {
+            let (address, rest): (Vec<_>, Vec<_>) = fields
+                .into_iter()
+                .partition(|(column, _)| column == "address");
+            let address = address
+                .into_iter()
+                .next()
+                .map(|(_, address)| address)
+                .ok_or("missing address")?;
+            address
+                .parse::<Address>()
+                .map_err(|_| format!("invalid address {}", address))?;
+            Ok(std::iter::once(address)
+                .chain(rest.into_iter().map(|(_, value)| value))
+                .collect())
+        });
+        match result {
+            Ok(row) => rows.push(row),
+            Err(e) if config.strict => errors.push(format!("{}: {}", location, e)),
+            Err(e) => warn!("{} {}: {}, skipping", path, location, e),
+        }
+    }
+    check_errors(path, errors)?;
+    Ok(rows)
+}
+
+/// Read every recipient from an addresses file
+///
+/// Invalid rows are skipped with a warning, or all reported together in strict mode.
+pub fn read_recipients(path: &str, config: &AddressesConfig) -> Result<Vec<Recipient>> {
+    let mut warned = Vec::new();
+    let rows = read_fields(path, config)?
+        .into_iter()
+        .map(|(location, fields)| {
+            let row = fields.map(|fields| {
+                let mut row = Row::default();
+                for (column, value) in fields {
+                    if !column.is_empty()
+                        && !COLUMNS.contains(&column.as_str())
+                        && !warned.contains(&column)
+                    {
+                        warn!("{}: ignoring unknown column {}", path, column);
+                        warned.push(column.clone());
+                    }
+                    row.set(&column, value);
+                }
+                row
+            });
+            (location, row)
+        });
+
+    let mut recipients: Vec<Recipient> = Vec::new();
+    // Index into `recipients` and location of each address's first occurrence
+    let mut seen: HashMap<Address, (usize, String)> = HashMap::new();
+    let mut errors = Vec::new();
+    for (location, row) in rows {
+        let result = row.and_then(Row::parse).and_then(|recipient| {
+            let Some((index, first)) = seen.get(&recipient.address) else {
+                seen.insert(recipient.address, (recipients.len(), location.clone()));
+                recipients.push(recipient);
+                return Ok(());
+            };
+            match config.duplicates {
+                DuplicatePolicy::Skip => {
+                    warn!(
+                        "{} {}: skipping duplicate address {} (first on {})",
+                        path, location, recipient.address, first
+                    );
+                    Ok(())
+                }
+                DuplicatePolicy::Sum => {
+                    let existing = &mut recipients[*index];
+                    existing.amount = match (existing.amount, recipient.amount) {
+                        (Some(a), Some(b)) => Some(a + b),
+                        (None, None) => None,
+                        _ => {
+                            return Err(format!(
+                                "cannot sum amounts for duplicate address {} (first on {})",
+                                recipient.address, first
+                            ))
+                        }
+                    };
+                    Ok(())
+                }
+                DuplicatePolicy::Error => Err(format!(
+                    "duplicate address {} (first on {})",
+                    recipient.address, first
+                )),
+            }
+        });
+        if let Err(e) = result {
+            if config.strict {
+                errors.push(format!("{}: {}", location, e));
+            } else {
+                warn!("{} {}: {}, skipping", path, location, e);
+            }
+        }
+    }
+
+    check_errors(path, errors)?;
+    Ok(recipients)
+}
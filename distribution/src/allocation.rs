@@ -0,0 +1,148 @@
+use rand::rngs::StdRng;
+use rand::SeedableRng;
+
+use crate::addresses::Recipient;
+use crate::builders::native::float_to_u256;
+use crate::prelude::*;
+
+/// Split `total` in proportion to `weights`
+///
+/// Rounding leftovers go one wei at a time to the largest remainders, so the shares always add
+/// up to exactly `total`.
+fn split(total: U256, weights: &[U256]) -> Result<Vec<U256>> {
+    let weight_sum = weights.iter().sum::<U256>();
+    if weight_sum.is_zero() {
+        return Err(anyhow::anyhow!(
+            "Cannot split a budget between recipients with zero total weight"
+        ));
+    }
+    let mut shares = Vec::with_capacity(weights.len());
+    let mut remainders = Vec::with_capacity(weights.len());
+    for (i, weight) in weights.iter().enumerate() {
+        let product = total
+            .checked_mul(*weight)
+            .ok_or_else(|| anyhow::anyhow!("Budget and weights are too large to split"))?;
+        shares.push(product / weight_sum);
+        remainders.push((product % weight_sum, i));
+    }
+
+    let mut left = total - shares.iter().sum::<U256>();
+    remainders.sort_by(|a, b| b.0.cmp(&a.0).then(a.1.cmp(&b.1)));
+    for (_, i) in remainders {
+        if left.is_zero() {
+            break;
+        }
+        shares[i] += U256::from(1);
+        left -= U256::from(1);
+    }
+    Ok(shares)
+}
+
+fn weights(recipients: &[Recipient]) -> Result<Vec<U256>> {
+    recipients
+        .iter()
+        .map(|recipient| {
+            recipient
+                .weight
+                .map(float_to_u256)
+                .ok_or_else(|| anyhow::anyhow!("Missing weight for {}", recipient.address))
+        })
+        .collect()
+}
+
+/// Whether `allocate` gives every recipient nothing, whoever they are
+pub fn pays_nothing(strategy: &AllocationConfig) -> bool {
+    match strategy {
+        AllocationConfig::Fixed { amount } => amount.is_zero(),
+        AllocationConfig::Random { high, .. } => high.is_zero(),
+        AllocationConfig::Even { total }
+        | AllocationConfig::ProRata { total }
+        | AllocationConfig::CappedProRata { total, .. } => total.is_zero(),
+        AllocationConfig::Tiered { tiers } => tiers.iter().all(|tier| tier.amount.is_zero()),
+    }
+}
+
+/// Whether `allocate` always returns the same amounts for the same recipients
+pub fn is_deterministic(strategy: &AllocationConfig) -> bool {
+    !matches!(strategy, AllocationConfig::Random { seed: None, .. })
+}
+
+/// Amounts in wei for each recipient, in order
+pub fn allocate(strategy: &AllocationConfig, recipients: &[Recipient]) -> Result<Vec<U256>> {
+    let count = recipients.len();
+    match strategy {
+        AllocationConfig::Fixed { amount } => Ok(vec![*amount; count]),
+        AllocationConfig::Random { low, high, seed } => {
+            if low > high {
+                return Err(anyhow::anyhow!("Random allocation low is above high"));
+            }
+            let low = u128::try_from(*low)?;
+            let high = u128::try_from(*high)?;
+            let mut rng = match seed {
+                Some(seed) => StdRng::seed_from_u64(*seed),
+                None => StdRng::from_os_rng(),
+            };
+            Ok((0..count)
+                .map(|_| U256::from(rng.random_range(low..=high)))
+                .collect())
+        }
+        AllocationConfig::Even { total } => split(*total, &vec![U256::from(1); count]),
+        AllocationConfig::ProRata { total } => split(*total, &weights(recipients)?),
+        AllocationConfig::CappedProRata { total, cap } => {
+            if *cap * U256::from(count) < *total {
+                return Err(anyhow::anyhow!(
+                    "Budget exceeds the cap for every one of {} recipients",
+                    count
+                ));
+            }
+            let weights = weights(recipients)?;
+            let mut capped = vec![false; count];
+            // Cap everyone whose share is too large, then re-split what is left among the rest
+            loop {
+                let capped_count = capped.iter().filter(|capped| **capped).count();
+                let remaining = *total - *cap * U256::from(capped_count);
+                let uncapped_weights = weights
+                    .iter()
+                    .zip(&capped)
+                    .map(|(weight, capped)| if *capped { U256::ZERO } else { *weight })
+                    .collect::<Vec<_>>();
+                let shares = if remaining.is_zero() {
+                    vec![U256::ZERO; count]
+                } else {
+                    split(remaining, &uncapped_weights)?
+                };
+
+                let mut newly_capped = false;
+                for (i, share) in shares.iter().enumerate() {
+                    if !capped[i] && share > cap {
+                        capped[i] = true;
+                        newly_capped = true;
+                    }
+                }
+                if !newly_capped {
+                    return Ok(shares
+                        .into_iter()
+                        .zip(&capped)
+                        .map(|(share, capped)| if *capped { *cap } else { share })
+                        .collect());
+                }
+            }
+        }
+        AllocationConfig::Tiered { tiers } => {
+            let mut tiers = tiers.iter().collect::<Vec<_>>();
+            tiers.sort_by(|a, b| b.min_weight.total_cmp(&a.min_weight));
+            recipients
+                .iter()
+                .map(|recipient| {
+                    let weight = recipient.weight.ok_or_else(|| {
+                        anyhow::anyhow!("Missing weight for {}", recipient.address)
+                    })?;
+                    Ok(tiers
+                        .iter()
+                        .find(|tier| weight >= tier.min_weight)
+                        .map_or(U256::ZERO, |tier| tier.amount))
+                })
+                .collect()
+        }
+    }
+}
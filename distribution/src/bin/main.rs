@@ -1,204 +1,282 @@
-use std::collections::HashSet;
-use std::fs::File;
-use std::io::BufRead;
-use std::path::Path;
+use std::collections::{HashMap, HashSet};
+use std::str::FromStr;
 
-use clap::{App, Arg};
+use alloy::primitives::utils::format_ether;
+use clap::{App, Arg, ArgMatches, SubCommand};
 use tracing::{error, info, warn};
 use tracing_subscriber::{self, EnvFilter};
 
+use distribution::addresses::{self, Recipient};
+use distribution::allocation;
 use distribution::builders;
 use distribution::distributors;
+use distribution::filters;
+use distribution::idempotency;
+use distribution::merkle;
+use distribution::overrides::{self, Override};
+use distribution::plan::{Expectations, PlanSummary, TransactionPlan};
 use distribution::prelude::*;
+use distribution::validate;
+use distribution::verify::{self, ExpectedAmount};
 
-// Helper to read the addresses file
-pub fn read_addresses_file(filename: &str) -> io::Result<Vec<(Address, Option<f64>)>> {
-    let path = Path::new(filename);
-    let file = File::open(path)?;
-    let lines = io::BufReader::new(file).lines();
-
-    let mut address_amounts = Vec::new();
-    let mut unique_addresses = HashSet::new();
-    let mut has_amounts = false;
-
-    for line in lines {
-        let line = line?;
-        let parts: Vec<&str> = line.split(',').collect();
-
-        if let Ok(address) = parts[0].trim().parse::<Address>() {
-            if unique_addresses.insert(address) {
-                address_amounts.push((
-                    address,
-                    if parts.len() > 1 {
-                        has_amounts = true;
-                        Some(parts[1].parse::<f64>().unwrap())
-                    } else {
-                        if has_amounts {
-                            return Err(io::Error::new(
-                                io::ErrorKind::InvalidData,
-                                "Missing amounts for some addresses",
-                            ));
-                        }
-                        None
-                    },
-                ));
-            } else {
-                warn!("Skipping duplicate address: {}", address);
-            }
-        } else {
-            warn!("Skipping invalid address: {}", parts[0]);
-        }
-    }
-
-    Ok(address_amounts)
+// Helper to pick the RPC URL for chain reads, which go through an HTTP provider
+fn read_rpc_url(config: &UnifiedConfig) -> &str {
+    config
+        .core
+        .rpc_urls
+        .iter()
+        .find(|url| !is_ws_url(url))
+        .expect("At least one HTTP RPC URL must be specified in config")
 }
 
-#[tokio::main]
-pub async fn main() -> anyhow::Result<()> {
-    // Initialize logging
-    tracing_subscriber::fmt()
-        .with_env_filter(
-            EnvFilter::try_from_default_env().unwrap_or_else(|_| EnvFilter::new("info")),
-        )
-        .init();
-
-    dotenv().ok();
-
-    // Parse command-line arguments
-    let matches = App::new("Duster")
-        .arg(
-            Arg::with_name("config")
-                .short("c")
-                .long("config")
-                .value_name("FILE")
-                .help("Sets a custom config file")
-                .default_value("config.yml")
-                .takes_value(true),
-        )
-        .get_matches();
+// Helper to connect to every HTTP RPC URL, for spreading gas estimates over them
+fn estimate_providers(rpc_urls: &[String]) -> Vec<Box<dyn Provider>> {
+    rpc_urls
+        .iter()
+        .filter(|url| !is_ws_url(url))
+        .map(|url| {
+            Box::new(ProviderBuilder::new().on_http(url.parse().unwrap())) as Box<dyn Provider>
+        })
+        .collect()
+}
 
-    // Read config file
-    let config_file = matches
-        .value_of("config")
-        .expect("Config file must be specified");
-    let config = read_config_file::<UnifiedConfig>(config_file);
+// Helper to read a signer's private key from the given environment variable
+fn read_signer(env_var: &str) -> anyhow::Result<PrivateKeySigner> {
+    let private_key =
+        std::env::var(env_var).map_err(|_| anyhow::anyhow!("{} must be set", env_var))?;
+    let private_bytes = hex::decode(private_key.trim_start_matches("0x"))
+        .map_err(|e| anyhow::anyhow!("{} is not a hex private key: {}", env_var, e))?;
+    Ok(PrivateKeySigner::from_slice(&private_bytes)?)
+}
 
-    // Validate that we have at least one RPC URL
-    if config.core.rpc_urls.is_empty() {
-        error!("At least one RPC URL must be specified in config");
-        std::process::exit(1);
+// Helper to assign amounts (in wei) to recipients without one in the addresses file
+fn allocate_amounts(
+    distribution_type: DistributionType,
+    recipients: &[Recipient],
+    sections: &BuilderSections,
+) -> anyhow::Result<Vec<U256>> {
+    let unallocated = recipients
+        .iter()
+        .filter(|recipient| recipient.amount.is_none())
+        .cloned()
+        .collect::<Vec<_>>();
+    let mut allocated = if unallocated.is_empty() {
+        vec![]
+    } else {
+        let token_config = sections.token.as_ref().ok_or_else(|| {
+            anyhow::anyhow!(
+                "Token config must be set for distribution type {:?} \
+                 when the addresses file has no amount for some recipients",
+                distribution_type
+            )
+        })?;
+        allocation::allocate(&token_config.allocation(), &unallocated)?
     }
+    .into_iter();
+    // File amounts override config amounts
+    Ok(recipients
+        .iter()
+        .map(|recipient| {
+            recipient
+                .amount
+                .unwrap_or_else(|| allocated.next().unwrap())
+        })
+        .collect())
+}
 
-    // Create distribution options
-    let options = DistributionOptions {
-        rpc_urls: config.core.rpc_urls.clone(),
-        rpc_batch_size: config.core.rpc_batch_size,
-        target_tps: config.core.target_tps,
-        wait_for_confirmation: true,
-        confirmation_timeout: 120,
-    };
-
-    // Get private key from environment
-    let signer = {
-        let private_key = std::env::var("PRIVATE_KEY").expect("PRIVATE_KEY must be set");
-        let private_bytes = hex::decode(&private_key[2..]).unwrap();
-        PrivateKeySigner::from_bytes(&FixedBytes::from_slice(&private_bytes)).unwrap()
-    };
+// Helper to drop recipients with a zero amount
+fn retain_amounts(addresses: Vec<Address>, amounts: Vec<U256>) -> (Vec<Address>, Vec<U256>) {
+    addresses
+        .into_iter()
+        .zip(amounts)
+        .filter(|(_, amount)| !amount.is_zero())
+        .unzip()
+}
 
-    // Create provider for the chain (using first RPC URL for builder operations)
-    let provider = ProviderBuilder::new().on_http(config.core.rpc_urls[0].parse().unwrap());
+// Helper to read the recipients a distribution type pays
+fn load_recipients(
+    distribution_type: DistributionType,
+    addresses_file: Option<&String>,
+    addresses_config: &AddressesConfig,
+    sections: &BuilderSections,
+) -> anyhow::Result<Vec<Recipient>> {
+    Ok(match distribution_type {
+        DistributionType::NativeDirect
+        | DistributionType::NativeBatch
+        | DistributionType::Merkle
+        | DistributionType::NFTMint => {
+            let addresses_file = addresses_file.ok_or_else(|| {
+                anyhow::anyhow!(
+                    "Addresses file must be specified for distribution type {:?}",
+                    distribution_type
+                )
+            })?;
+            addresses::read_recipients(addresses_file, addresses_config)?
+        }
+        DistributionType::MerkleClaim => {
+            let merkle_config = sections.merkle.as_ref().ok_or_else(|| {
+                anyhow::anyhow!(
+                    "Merkle config must be set for distribution type {:?}",
+                    distribution_type
+                )
+            })?;
+            merkle::ProofsFile::read(&merkle_config.proofs_file)?
+                .claims
+                .iter()
+                .map(|claim| Recipient {
+                    address: claim.account,
+                    amount: Some(claim.amount),
+                    uri: None,
+                    token_id: None,
+                    label: None,
+                    weight: None,
+                })
+                .collect()
+        }
+        _ => vec![],
+    })
+}
 
-    // Extract and prepare addresses and amounts
-    let (addresses, amounts) = match config.core.distribution_type {
-        DistributionType::NativeDirect | DistributionType::NativeBatch => {
-            let addresses_file = config.core.addresses_file.as_ref().expect(&format!(
-                "Addresses file must be specified for distribution type {:?}",
-                config.core.distribution_type
-            ));
-            let address_data = read_addresses_file(&addresses_file)?;
-            // Check if we have amounts from file
-            let have_amounts_from_file = address_data[0].1.is_some();
-
-            if have_amounts_from_file {
-                // Use file amounts
-                let addresses = address_data
-                    .iter()
-                    .map(|(addr, _)| *addr)
-                    .collect::<Vec<_>>();
-                let amounts = address_data
-                    .iter()
-                    .map(|(_, amt)| amt.expect("Amount must be set in file"))
-                    .collect::<Vec<_>>();
-                (addresses, amounts)
-            } else {
-                // Use config amounts
-                let token_config = config.token.as_ref().expect(&format!(
-                    "Token config must be set for distribution type {:?}",
-                    config.core.distribution_type
-                ));
-                let addresses = address_data
-                    .iter()
-                    .map(|(addr, _)| *addr)
-                    .collect::<Vec<_>>();
-                let amounts = if token_config.amount_per_address_low
-                    == token_config.amount_per_address_high
-                {
-                    // Fixed amount
-                    vec![token_config.amount_per_address_low; addresses.len()]
-                } else {
-                    // Random amount in range
-                    let mut rng = rand::rng();
-                    addresses
-                        .iter()
-                        .map(|_| {
-                            rng.random_range(
-                                token_config.amount_per_address_low
-                                    ..=token_config.amount_per_address_high,
-                            )
-                        })
-                        .collect()
-                };
+// Helper to assign amounts (in wei) to the recipients a distribution type pays
+fn recipient_amounts(
+    distribution_type: DistributionType,
+    recipients: &[Recipient],
+    sections: &BuilderSections,
+) -> anyhow::Result<(Vec<Address>, Vec<U256>)> {
+    let addresses = recipients
+        .iter()
+        .map(|recipient| recipient.address)
+        .collect::<Vec<_>>();
+    Ok(match distribution_type {
+        DistributionType::NativeDirect
+        | DistributionType::NativeBatch
+        | DistributionType::Merkle => {
+            let amounts = allocate_amounts(distribution_type, recipients, sections)?;
 
-                (addresses, amounts)
+            // Tiered allocations leave recipients below the lowest tier with nothing
+            let total = addresses.len();
+            let (addresses, amounts) = retain_amounts(addresses, amounts);
+            if addresses.len() < total {
+                info!(
+                    "Skipping {} recipients allocated nothing",
+                    total - addresses.len()
+                );
             }
+
+            (addresses, amounts)
         }
-        DistributionType::NFTMint => {
-            let addresses_file = config.core.addresses_file.as_ref().expect(&format!(
-                "Addresses file must be specified for distribution type {:?}",
-                config.core.distribution_type
-            ));
-            let address_data = read_addresses_file(&addresses_file)?;
-            let addresses = address_data
+        DistributionType::MerkleClaim => {
+            let amounts = recipients
                 .iter()
-                .map(|(addr, _)| *addr)
-                .collect::<Vec<_>>();
-            (addresses, vec![])
+                .map(|recipient| recipient.amount.unwrap_or_default())
+                .collect();
+            (addresses, amounts)
         }
-        _ => (vec![], vec![]),
-    };
+        _ => (addresses, vec![]),
+    })
+}
 
-    {
-        info!("Distribution type: {:?}", config.core.distribution_type);
-        info!("Signer address: {:?}", signer.address());
-        info!("Total addresses: {}", addresses.len());
-        info!("Total amount: {}", amounts.iter().sum::<f64>());
-        info!("RPC URLs: {:?}", config.core.rpc_urls);
-        info!("RPC batch size: {}", options.rpc_batch_size);
-        info!("Target TPS: {}", options.target_tps);
+// Helper to find the kind of transaction through which a distribution pays its recipients
+fn payment_scope(
+    distribution_type: DistributionType,
+    sections: &BuilderSections,
+) -> Option<idempotency::PaymentScope> {
+    match distribution_type {
+        DistributionType::NativeDirect => Some(idempotency::PaymentScope::Transfer),
+        DistributionType::NativeBatch => {
+            sections
+                .token
+                .as_ref()
+                .map(|token_config| idempotency::PaymentScope::Call {
+                    contract: token_config.batch_sender_address,
+                    selector: builders::native::BatchSender::batchSendCall::SELECTOR.into(),
+                })
+        }
+        DistributionType::NFTMint => {
+            sections
+                .nft
+                .as_ref()
+                .map(|nft_config| idempotency::PaymentScope::Call {
+                    contract: nft_config.token_address,
+                    selector: builders::nft::SoulboundMinter::batchMintCall::SELECTOR.into(),
+                })
+        }
+        DistributionType::MerkleClaim => sections
+            .merkle
+            .as_ref()
+            .and_then(|merkle_config| merkle_config.distributor_address)
+            .map(|distributor| idempotency::PaymentScope::Call {
+                contract: distributor,
+                selector: builders::merkle::MerkleDistributor::claimCall::SELECTOR.into(),
+            }),
+        _ => None,
+    }
+}
 
-        info!("\nPress Enter to continue or Ctrl+C to cancel...");
-        let mut input = String::new();
-        std::io::stdin().read_line(&mut input)?;
-        if input.trim() != "" {
-            error!("Aborted");
-            std::process::exit(1);
+// Whether a distribution type pays a fixed list of recipients, each of them once
+fn takes_recipients(distribution_type: DistributionType) -> bool {
+    matches!(
+        distribution_type,
+        DistributionType::NativeDirect
+            | DistributionType::NativeBatch
+            | DistributionType::NFTMint
+            | DistributionType::Merkle
+            | DistributionType::MerkleClaim
+    )
+}
+
+// Whether a distribution type must be built only once in a scenario. Besides paying a fixed list
+// of recipients, contract calls would repeat their rows and counters, and counter updates would
+// start again from a value that pending updates have not yet changed
+fn builds_once(distribution_type: DistributionType) -> bool {
+    takes_recipients(distribution_type)
+        || matches!(
+            distribution_type,
+            DistributionType::ContractCall | DistributionType::StrictCounter
+        )
+}
+
+// Helper to drop recipients, keeping amounts aligned
+fn retain_recipients(
+    addresses: Vec<Address>,
+    amounts: Vec<U256>,
+    mut keep: impl FnMut(&Address) -> bool,
+) -> (Vec<Address>, Vec<U256>) {
+    let has_amounts = !amounts.is_empty();
+    let mut kept_addresses = Vec::new();
+    let mut kept_amounts = Vec::new();
+    for (i, address) in addresses.into_iter().enumerate() {
+        if !keep(&address) {
+            continue;
+        }
+        kept_addresses.push(address);
+        if has_amounts {
+            kept_amounts.push(amounts[i]);
         }
     }
+    (kept_addresses, kept_amounts)
+}
 
-    // Create appropriate builder based on distribution type
+// What builders are created for, beyond their own settings
+struct BuildContext<'a> {
+    rpc_urls: &'a [String],
+    /// Write files a run depends on, such as Merkle proofs, which plans leave untouched
+    write_files: bool,
+}
+
+// Helper to create the builder and distributor for a distribution type
+fn create_builder<P: Provider + Clone + 'static>(
+    distribution_type: DistributionType,
+    (addresses_file, addresses_config): (Option<&String>, &AddressesConfig),
+    sections: &BuilderSections,
+    (addresses, amounts): (Vec<Address>, Vec<U256>),
+    signer: PrivateKeySigner,
+    provider: &P,
+    context: &BuildContext,
+) -> anyhow::Result<(Box<dyn Builder + Send + Sync>, Box<dyn Distributor>)> {
     let builder: Box<dyn Builder + Send + Sync>;
     let distributor: Box<dyn Distributor>;
-    match config.core.distribution_type {
+    match distribution_type {
         DistributionType::NativeDirect => {
             builder = Box::new(builders::native::NativeTransferBuilder {
                 signer: signer.clone(),
@@ -212,49 +290,1125 @@ pub async fn main() -> anyhow::Result<()> {
             builder = Box::new(builders::native::NativeBatchSenderBuilder {
                 signer: signer.clone(),
                 provider: Box::new(provider.clone()),
-                contract_address: config.token.unwrap().batch_sender_address,
+                contract_address: sections
+                    .token
+                    .as_ref()
+                    .ok_or_else(|| {
+                        anyhow::anyhow!(
+                            "Token config must be set for distribution type {:?}",
+                            distribution_type
+                        )
+                    })?
+                    .batch_sender_address,
                 recipients: addresses,
                 amounts,
+                batch: sections
+                    .token
+                    .as_ref()
+                    .map(|token_config| token_config.batch.clone())
+                    .unwrap_or_default(),
+                estimate_providers: estimate_providers(context.rpc_urls),
             });
             distributor = Box::new(distributors::finite::FiniteGroupDistributor { signer });
         }
         DistributionType::NFTMint => {
-            let nft_config = config.nft.expect(&format!(
-                "NFT config must be set for distribution type {:?}",
-                config.core.distribution_type
-            ));
-            if nft_config.soulbound {
-                builder = Box::new(builders::nft::SoulboundNFTMintBuilder {
-                    signer: signer.clone(),
-                    provider: Box::new(provider.clone()),
-                    contract_address: nft_config.token_address,
-                    recipients: addresses,
-                    image_url: nft_config.image_url,
-                })
-            } else {
-                unimplemented!("Non-soulbound NFT minting is not implemented yet");
+            let nft_config = sections.nft.as_ref().ok_or_else(|| {
+                anyhow::anyhow!(
+                    "NFT config must be set for distribution type {:?}",
+                    distribution_type
+                )
+            })?;
+            if !nft_config.soulbound {
+                return Err(anyhow::anyhow!(
+                    "Non-soulbound NFT minting is not implemented yet"
+                ));
             }
+            builder = Box::new(builders::nft::SoulboundNFTMintBuilder {
+                signer: signer.clone(),
+                provider: Box::new(provider.clone()),
+                contract_address: nft_config.token_address,
+                recipients: addresses,
+                image_url: nft_config.image_url.clone(),
+                batch: nft_config.batch.clone(),
+                estimate_providers: estimate_providers(context.rpc_urls),
+            });
             distributor = Box::new(distributors::finite::FiniteGroupDistributor { signer });
         }
         DistributionType::Swapper => {
+            let swapper_config = sections.swapper.as_ref().ok_or_else(|| {
+                anyhow::anyhow!(
+                    "Swapper config must be set for distribution type {:?}",
+                    distribution_type
+                )
+            })?;
             builder = Box::new(builders::swapper::SwapperBuilder {
                 signer: signer.clone(),
                 provider: Box::new(provider.clone()),
-                contract_address: config
-                    .swapper
-                    .as_ref()
-                    .expect("Swapper config must be set")
-                    .swapper_address,
-                max_swaps: config.swapper.as_ref().unwrap().max_swaps,
+                contract_address: swapper_config.swapper_address,
+                max_swaps: swapper_config.max_swaps,
+                amount: swapper_config.amount.clone(),
+                direction: swapper_config.direction.clone(),
+                max_slippage_bps: swapper_config.max_slippage_bps,
+            });
+            distributor = Box::new(distributors::continuous::ContinuousDistributor { signer });
+        }
+        DistributionType::UniswapV2 => {
+            let uniswap_config = sections.uniswap_v2.as_ref().ok_or_else(|| {
+                anyhow::anyhow!(
+                    "Uniswap V2 config must be set for distribution type {:?}",
+                    distribution_type
+                )
+            })?;
+            builder = Box::new(builders::uniswap_v2::UniswapV2Builder {
+                signer: signer.clone(),
+                provider: Box::new(provider.clone()),
+                router_address: uniswap_config.router_address,
+                path: uniswap_config.path.clone(),
+                amount_in: uniswap_config.amount_in,
+                native_in: uniswap_config.native_in,
+                max_swaps: uniswap_config.max_swaps,
+                max_slippage_bps: uniswap_config.max_slippage_bps,
+                deadline_secs: uniswap_config.deadline_secs,
+                approval_sent: Default::default(),
             });
             distributor = Box::new(distributors::continuous::ContinuousDistributor { signer });
         }
+        DistributionType::ContractCall => {
+            let call_config = sections.contract_call.as_ref().ok_or_else(|| {
+                anyhow::anyhow!(
+                    "Contract call config must be set for distribution type {:?}",
+                    distribution_type
+                )
+            })?;
+            let function = alloy::json_abi::Function::parse(&call_config.function_signature)?;
+            let rows = if call_config
+                .args
+                .iter()
+                .any(|arg| matches!(arg, ArgSource::Column { .. }))
+            {
+                let addresses_file = addresses_file.ok_or_else(|| {
+                    anyhow::anyhow!(
+                        "Addresses file must be specified when contract call arguments use columns"
+                    )
+                })?;
+                addresses::read_rows(addresses_file, addresses_config)?
+            } else {
+                vec![]
+            };
+            // `read_rows` has already checked that every row starts with an address
+            let recipients = rows
+                .iter()
+                .map(|row| row[0].parse())
+                .collect::<Result<Vec<Address>, _>>()?;
+            builder = Box::new(builders::contract_call::ContractCallBuilder {
+                signer: signer.clone(),
+                provider: Box::new(provider.clone()),
+                contract_address: call_config.contract_address,
+                function,
+                args: call_config.args.clone(),
+                rows,
+                recipients,
+                max_calls: call_config.max_calls,
+                value: call_config.value,
+            });
+            distributor = Box::new(distributors::finite::FiniteGroupDistributor { signer });
+        }
+        DistributionType::Merkle => {
+            let merkle_config = sections.merkle.as_ref().ok_or_else(|| {
+                anyhow::anyhow!(
+                    "Merkle config must be set for distribution type {:?}",
+                    distribution_type
+                )
+            })?;
+            let proofs = merkle::build_proofs(&addresses, &amounts)?;
+            info!("Merkle root: {}", proofs.root);
+            if context.write_files {
+                proofs.write(&merkle_config.proofs_file)?;
+                info!(
+                    "Wrote {} claims to {}",
+                    proofs.claims.len(),
+                    merkle_config.proofs_file
+                );
+            }
+            let bytecode = match merkle_config.distributor_address {
+                Some(_) => None,
+                None => Some(merkle::read_forge_bytecode(&merkle_config.artifact_file)?),
+            };
+            builder = Box::new(builders::merkle::MerkleDistributorBuilder {
+                signer: signer.clone(),
+                provider: Box::new(provider.clone()),
+                distributor_address: merkle_config.distributor_address,
+                bytecode,
+                root: proofs.root,
+                total: proofs.total,
+                fund: merkle_config.fund,
+                distributor: std::sync::Mutex::new(None),
+            });
+            distributor = Box::new(distributors::finite::FiniteGroupDistributor { signer });
+        }
+        DistributionType::MerkleClaim => {
+            let merkle_config = sections.merkle.as_ref().ok_or_else(|| {
+                anyhow::anyhow!(
+                    "Merkle config must be set for distribution type {:?}",
+                    distribution_type
+                )
+            })?;
+            let distributor_address = merkle_config.distributor_address.ok_or_else(|| {
+                anyhow::anyhow!("A distributor address must be set to claim from it")
+            })?;
+            // Only claim for recipients that survived filtering and idempotency checks
+            let remaining = addresses.into_iter().collect::<HashSet<_>>();
+            let proofs = merkle::ProofsFile::read(&merkle_config.proofs_file)?;
+            let claims = proofs
+                .claims
+                .into_iter()
+                .filter(|claim| remaining.contains(&claim.account))
+                .collect();
+            builder = Box::new(builders::merkle::MerkleClaimBuilder::new(
+                signer.clone(),
+                Box::new(provider.clone()),
+                distributor_address,
+                proofs.root,
+                claims,
+                merkle_config.skip_claimed,
+            ));
+            distributor = Box::new(distributors::finite::FiniteGroupDistributor { signer });
+        }
+        DistributionType::StrictCounter => {
+            let counter_config = sections.strict_counter.as_ref().ok_or_else(|| {
+                anyhow::anyhow!(
+                    "Strict counter config must be set for distribution type {:?}",
+                    distribution_type
+                )
+            })?;
+            builder = Box::new(builders::strict_counter::StrictCounterBuilder {
+                signer: signer.clone(),
+                provider: Box::new(provider.clone()),
+                contract_address: counter_config.counter_address,
+                num_updates: counter_config.num_updates,
+                expected: std::sync::Mutex::new(None),
+            });
+            distributor = Box::new(distributors::finite::FiniteGroupDistributor { signer });
+        }
+        DistributionType::Scenario => {
+            return Err(anyhow::anyhow!("Scenario workloads cannot be nested"));
+        }
     };
+    Ok((builder, distributor))
+}
+
+// Helper to read what each recipient in the addresses file should have received
+//
+// Recipients `eligible` rejects were filtered out of the run and are not expected.
+fn expected_amounts(
+    distribution_type: DistributionType,
+    addresses_file: Option<&String>,
+    addresses_config: &AddressesConfig,
+    sections: &BuilderSections,
+    eligible: impl Fn(&Address) -> bool,
+) -> anyhow::Result<Vec<(Address, ExpectedAmount)>> {
+    let addresses_file = addresses_file.ok_or_else(|| {
+        anyhow::anyhow!(
+            "Addresses file must be specified to verify distribution type {:?}",
+            distribution_type
+        )
+    })?;
+    let recipients = addresses::read_recipients(addresses_file, addresses_config)?
+        .into_iter()
+        .filter(|recipient| eligible(&recipient.address))
+        .collect::<Vec<_>>();
+    match distribution_type {
+        DistributionType::NativeDirect | DistributionType::NativeBatch => {}
+        DistributionType::NFTMint => {
+            return Ok(recipients
+                .iter()
+                .map(|recipient| (recipient.address, ExpectedAmount::Token))
+                .collect());
+        }
+        _ => {
+            return Err(anyhow::anyhow!(
+                "Verification is not supported for distribution type {:?}",
+                distribution_type
+            ))
+        }
+    }
+
+    // Unseeded random amounts can only be checked against their range
+    let strategy = sections.token.as_ref().map(TokenConfig::allocation);
+    if let Some(AllocationConfig::Random { low, high, .. }) =
+        strategy.filter(|strategy| !allocation::is_deterministic(strategy))
+    {
+        return Ok(recipients
+            .iter()
+            .map(|recipient| {
+                let expected = match recipient.amount {
+                    Some(amount) => ExpectedAmount::Exact(amount),
+                    None => ExpectedAmount::Range(low, high),
+                };
+                (recipient.address, expected)
+            })
+            .collect());
+    }
+
+    // Everything else allocates the same amounts again, as when distributing
+    let amounts = allocate_amounts(distribution_type, &recipients, sections)?;
+    Ok(recipients
+        .iter()
+        .zip(amounts)
+        .filter(|(_, amount)| !amount.is_zero())
+        .map(|(recipient, amount)| (recipient.address, ExpectedAmount::Exact(amount)))
+        .collect())
+}
+
+// Verify a finished run against the addresses file and report discrepancies
+async fn run_verify(config: &UnifiedConfig, matches: &ArgMatches<'_>) -> anyhow::Result<()> {
+    let report_file = matches
+        .value_of("report")
+        .or(config.core.journal_file.as_deref())
+        .ok_or_else(|| anyhow::anyhow!("A report file or core.journal_file must be specified"))?;
+    let entries = Journal::read(report_file)?;
+
+    // Filters were applied against balances and code as they were before the run, so expect the
+    // recipients the run recorded as kept rather than those the filters keep now
+    let (eligible, excluded) = match &config.filters {
+        Some(filter_config) => match Journal::read_eligible(report_file)? {
+            Some(eligible) => (Some(eligible), HashMap::new()),
+            None => {
+                warn!(
+                    "{} does not record the recipients filters kept, applying only exclude files",
+                    report_file
+                );
+                (None, filters::excluded(filter_config, &config.addresses)?)
+            }
+        },
+        None => (None, HashMap::new()),
+    };
+    let expected = expected_amounts(
+        config.core.distribution_type,
+        config.core.addresses_file.as_ref(),
+        &config.addresses,
+        &config.sections,
+        |address| {
+            eligible
+                .as_ref()
+                .is_none_or(|eligible| eligible.contains(address))
+                && !excluded.contains_key(address)
+        },
+    )?;
+    info!(
+        "Verifying {} transactions against {} recipients",
+        entries.len(),
+        expected.len()
+    );
+
+    let provider = ProviderBuilder::new().on_http(read_rpc_url(config).parse().unwrap());
+    let (deliveries, mut discrepancies) = verify::mined_deliveries(&provider, &entries).await?;
+    discrepancies.extend(verify::compare(&expected, &deliveries));
+
+    for discrepancy in &discrepancies {
+        warn!("{}", discrepancy);
+    }
+    if let Some(output_file) = matches.value_of("output") {
+        std::fs::write(output_file, serde_json::to_string_pretty(&discrepancies)?)?;
+        info!("Discrepancies written to {}", output_file);
+    }
+    if !discrepancies.is_empty() {
+        error!("Found {} discrepancies", discrepancies.len());
+        std::process::exit(1);
+    }
+    info!("All {} recipients verified", expected.len());
+    Ok(())
+}
+
+// Read the config with its environment and --set overrides, exiting on any problem
+fn load_config(matches: &ArgMatches<'_>) -> anyhow::Result<UnifiedConfig> {
+    let config_file = matches
+        .value_of("config")
+        .expect("Config file must be specified");
 
+    // Environment variables override the file, and --set overrides both
+    let mut config_overrides = Override::from_env(std::env::vars())?;
+    for arg in matches.values_of("set").into_iter().flatten() {
+        config_overrides.push(Override::parse_set(arg)?);
+    }
+    let loaded = match validate::load_config(config_file, &config_overrides) {
+        Ok(loaded) => loaded,
+        Err(errors) => {
+            for e in &errors {
+                error!("{}", e);
+            }
+            error!("Found {} problems in the config", errors.len());
+            std::process::exit(1);
+        }
+    };
+
+    for o in &config_overrides {
+        info!("Overriding {} from {}", o.path, o.source);
+    }
+    info!(
+        "Effective config:\n{}",
+        serde_yaml::to_string(&overrides::redacted(&loaded.effective))?
+    );
+    Ok(loaded.config)
+}
+
+// Helper to create distribution options from the config
+fn distribution_options(config: &UnifiedConfig) -> anyhow::Result<DistributionOptions> {
+    Ok(DistributionOptions {
+        rpc_urls: config.core.rpc_urls.clone(),
+        rpc_batch_size: config.core.rpc_batch_size,
+        target_tps: config.core.target_tps,
+        wait_for_confirmation: true,
+        confirmation_timeout: 120,
+        journal: config
+            .core
+            .journal_file
+            .as_deref()
+            .map(Journal::open)
+            .transpose()?
+            .map(Arc::new),
+        rpc_pool: config.rpc_pool.clone(),
+        stop: config.stop.clone(),
+        shutdown: Shutdown::default(),
+    })
+}
+
+// Recipients left to pay once the addresses file is read, filtered and checked for payments
+struct Recipients {
+    scenario: Option<ScenarioConfig>,
+    /// Recipients of each scenario workload, in the scenario's order
+    workloads: Vec<(Vec<Address>, Vec<U256>)>,
+    addresses: Vec<Address>,
+    amounts: Vec<U256>,
+    /// Every recipient left after the filters, already-paid ones included
+    eligible: Vec<Address>,
+}
+
+impl Recipients {
+    fn count(&self) -> usize {
+        self.addresses.len() + self.workloads.iter().map(|(a, _)| a.len()).sum::<usize>()
+    }
+
+    fn total(&self) -> U256 {
+        self.amounts
+            .iter()
+            .chain(self.workloads.iter().flat_map(|(_, amounts)| amounts))
+            .sum()
+    }
+}
+
+// Helper to load every recipient that a run would pay
+async fn prepare_recipients<P: Provider>(
+    config: &UnifiedConfig,
+    signer: Address,
+    provider: &P,
+) -> anyhow::Result<Recipients> {
+    // Load every scenario workload up front so the summary covers all of them
+    let mut workloads = Vec::new();
+    let mut eligible = Vec::new();
+    let scenario = if let DistributionType::Scenario = config.core.distribution_type {
+        let scenario_file = config.core.scenario_file.as_ref().ok_or_else(|| {
+            anyhow::anyhow!("Scenario file must be specified for distribution type Scenario")
+        })?;
+        let scenario = read_config_file::<ScenarioConfig>(scenario_file)?;
+        // Keys held in different variables can still be the same, so compare addresses too
+        let mut signer_workloads = HashMap::new();
+        for workload in &scenario.workloads {
+            info!("Preparing recipients of workload {}", workload.name);
+            let signers = workload
+                .signer_keys
+                .iter()
+                .map(|signer_key| Ok(read_signer(signer_key)?.address()))
+                .collect::<anyhow::Result<Vec<_>>>()?;
+            for signer in &signers {
+                if let Some(other) = signer_workloads.insert(*signer, &workload.name) {
+                    return Err(anyhow::anyhow!(
+                        "Signer {} of workload {} is already used by workload {}",
+                        signer,
+                        workload.name,
+                        other
+                    ));
+                }
+            }
+            workloads.push(
+                prepare_distribution(
+                    config,
+                    workload.distribution_type,
+                    workload.addresses_file.as_ref(),
+                    &workload.sections,
+                    &signers,
+                    provider,
+                    &mut eligible,
+                )
+                .await?,
+            );
+        }
+        Some(scenario)
+    } else {
+        None
+    };
+
+    let (addresses, amounts) = prepare_distribution(
+        config,
+        config.core.distribution_type,
+        config.core.addresses_file.as_ref(),
+        &config.sections,
+        &[signer],
+        provider,
+        &mut eligible,
+    )
+    .await?;
+
+    Ok(Recipients {
+        scenario,
+        workloads,
+        addresses,
+        amounts,
+        eligible,
+    })
+}
+
+// Helper to load one distribution's recipients, dropping those filtered out or already paid
+// by any of `signers`. Those left after the filters are added to `eligible`
+async fn prepare_distribution<P: Provider>(
+    config: &UnifiedConfig,
+    distribution_type: DistributionType,
+    addresses_file: Option<&String>,
+    sections: &BuilderSections,
+    signers: &[Address],
+    provider: &P,
+    eligible: &mut Vec<Address>,
+) -> anyhow::Result<(Vec<Address>, Vec<U256>)> {
+    let recipients = load_recipients(
+        distribution_type,
+        addresses_file,
+        &config.addresses,
+        sections,
+    )?;
+
+    // Recipients any of the signers has already paid the same way
+    let scope = payment_scope(distribution_type, sections);
+    let mut paid = HashSet::new();
+    if let (Some(idempotency_config), Some(scope)) = (&config.idempotency, scope) {
+        if let (true, Some(journal_file)) =
+            (idempotency_config.use_journal, &config.core.journal_file)
+        {
+            paid.extend(
+                idempotency::paid_from_journal(provider, journal_file, signers, scope).await?,
+            );
+        }
+        if let Some(from_block) = idempotency_config.scan_from_block {
+            paid.extend(
+                idempotency::paid_on_chain(
+                    provider,
+                    signers,
+                    scope,
+                    from_block,
+                    idempotency_config.scan_to_block,
+                )
+                .await?,
+            );
+        }
+    }
+
+    // Drop excluded recipients and those failing the on-chain filters, before any budget is
+    // split so that their share goes to those left. Paid recipients keep their share, as it was
+    // already sent
+    let recipients = if let Some(filter_config) = &config.filters {
+        filters::retain_eligible(
+            provider,
+            recipients,
+            &paid,
+            filter_config,
+            &config.addresses,
+        )
+        .await?
+    } else {
+        recipients
+    };
+    eligible.extend(recipients.iter().map(|recipient| recipient.address));
+    let (addresses, amounts) = recipient_amounts(distribution_type, &recipients, sections)?;
+
+    if config.idempotency.is_none() || scope.is_none() {
+        return Ok((addresses, amounts));
+    }
+    let total = addresses.len();
+    let (addresses, amounts) = retain_recipients(addresses, amounts, |address| {
+        let paid = paid.contains(address);
+        if paid {
+            info!("Skipping already-paid recipient: {}", address);
+        }
+        !paid
+    });
+    info!(
+        "Skipped {} already-paid recipients",
+        total - addresses.len()
+    );
+    Ok((addresses, amounts))
+}
+
+// Helper to log what a run is about to do and summarize it for pipelines
+fn plan_summary(
+    config: &UnifiedConfig,
+    signer: Address,
+    recipients: &Recipients,
+    options: &DistributionOptions,
+) -> PlanSummary {
+    let total = recipients.total();
+    info!("Distribution type: {:?}", config.core.distribution_type);
+    info!("Signer address: {:?}", signer);
+    info!("Total addresses: {}", recipients.count());
+    info!("Total amount: {}", format_ether(total));
+    let workloads = recipients.scenario.iter().flat_map(|s| &s.workloads);
+    for (workload, (addresses, _)) in workloads.zip(&recipients.workloads) {
+        info!(
+            "Workload {}: {:?}, weight {}, {} signers, {} recipients",
+            workload.name,
+            workload.distribution_type,
+            workload.weight,
+            workload.signer_keys.len(),
+            addresses.len()
+        );
+    }
+    info!("RPC URLs: {:?}", config.core.rpc_urls);
+    info!("RPC batch size: {}", options.rpc_batch_size);
+    info!("Target TPS: {}", options.target_tps);
+
+    PlanSummary {
+        distribution_type: format!("{:?}", config.core.distribution_type),
+        signer,
+        recipients: recipients.count(),
+        total: total.to_string(),
+        rpc_urls: config.core.rpc_urls.clone(),
+        rpc_batch_size: options.rpc_batch_size,
+        target_tps: options.target_tps,
+        transactions: None,
+    }
+}
+
+// Ask before sending, unless told not to or the plan matches the expected totals
+fn confirm(matches: &ArgMatches<'_>, recipients: usize, total: U256) -> anyhow::Result<()> {
+    let expectations = Expectations {
+        total: matches
+            .value_of("expect-total")
+            .map(U256::from_str)
+            .transpose()
+            .map_err(|e| anyhow::anyhow!("Invalid --expect-total: {}", e))?,
+        count: matches
+            .value_of("expect-count")
+            .map(str::parse)
+            .transpose()
+            .map_err(|e| anyhow::anyhow!("Invalid --expect-count: {}", e))?,
+    };
+
+    // Expected totals confirm the plan on their own, so a mismatch must never start a run
+    let mismatches = expectations.mismatches(recipients, total);
+    if !mismatches.is_empty() {
+        for mismatch in &mismatches {
+            error!("Plan does not match: {}", mismatch);
+        }
+        std::process::exit(1);
+    }
+    if expectations.is_empty() && !matches.is_present("yes") {
+        info!("\nPress Enter to continue or Ctrl+C to cancel...");
+        let mut input = String::new();
+        std::io::stdin().read_line(&mut input)?;
+        if input.trim() != "" {
+            error!("Aborted");
+            std::process::exit(1);
+        }
+    }
+    Ok(())
+}
+
+// Helper to create the builder and distributor for the configured distribution
+fn create_distribution<P: Provider + Clone + 'static>(
+    config: &UnifiedConfig,
+    recipients: Recipients,
+    signer: PrivateKeySigner,
+    provider: &P,
+    write_files: bool,
+) -> anyhow::Result<(Box<dyn Builder + Send + Sync>, Box<dyn Distributor>)> {
+    let context = BuildContext {
+        rpc_urls: &config.core.rpc_urls,
+        write_files,
+    };
+    let Some(scenario) = recipients.scenario else {
+        return create_builder(
+            config.core.distribution_type,
+            (config.core.addresses_file.as_ref(), &config.addresses),
+            &config.sections,
+            (recipients.addresses, recipients.amounts),
+            signer,
+            provider,
+            &context,
+        );
+    };
+
+    // One builder per workload signer, sharing the workload's weight
+    let mut weighted_builders = Vec::new();
+    for (workload, (addresses, amounts)) in scenario.workloads.into_iter().zip(recipients.workloads)
+    {
+        // Workloads paying a recipient list pay each recipient once, split across their signers
+        let split = takes_recipients(workload.distribution_type);
+        let finite = builds_once(workload.distribution_type);
+        let shares = if split {
+            builders::mixed::split_recipients(&addresses, &amounts, workload.signer_keys.len())
+        } else {
+            vec![(addresses, amounts); workload.signer_keys.len()]
+        };
+        let signers = workload
+            .signer_keys
+            .iter()
+            .zip(shares)
+            .filter(|(_, (addresses, _))| !split || !addresses.is_empty())
+            .collect::<Vec<_>>();
+        if signers.is_empty() {
+            warn!("Workload {} has no recipients left to pay", workload.name);
+            continue;
+        }
+        let weight = workload.weight / signers.len() as f64;
+        for (signer_key, share) in signers {
+            let (builder, _) = create_builder(
+                workload.distribution_type,
+                (workload.addresses_file.as_ref(), &config.addresses),
+                &workload.sections,
+                share,
+                read_signer(signer_key)?,
+                provider,
+                &context,
+            )?;
+            weighted_builders.push(builders::mixed::WeightedBuilder {
+                name: format!("{}/{}", workload.name, signer_key),
+                weight,
+                builder,
+                finite,
+            });
+        }
+    }
+    let builder: Box<dyn Builder + Send + Sync> = Box::new(builders::mixed::MixedBuilder::new(
+        weighted_builders,
+        config.core.target_tps as usize,
+    ));
+    let distributor: Box<dyn Distributor> =
+        Box::new(distributors::continuous::ContinuousDistributor { signer });
+    Ok((builder, distributor))
+}
+
+// Send a distribution, the default when no subcommand is given
+async fn run(config: UnifiedConfig, matches: &ArgMatches<'_>) -> anyhow::Result<()> {
+    let options = distribution_options(&config)?;
+    let signer = read_signer("PRIVATE_KEY")?;
+    // Create provider for the chain (using the first HTTP RPC URL for builder operations)
+    let provider = ProviderBuilder::new().on_http(read_rpc_url(&config).parse().unwrap());
+
+    let recipients = prepare_recipients(&config, signer.address(), &provider).await?;
+    let summary = plan_summary(&config, signer.address(), &recipients, &options);
+    println!("{}", summary.to_json());
+    confirm(matches, summary.recipients, U256::from_str(&summary.total)?)?;
+
+    // The filters look at balances and code that paying recipients changes, so verification
+    // checks against the recipients they kept now
+    if let (Some(journal), Some(_)) = (&options.journal, &config.filters) {
+        journal.record_eligible(&recipients.eligible)?;
+    }
+    let (builder, distributor) = create_distribution(&config, recipients, signer, &provider, true)?;
+
+    // From here on a signal drains in-flight batches instead of killing the process
+    let shutdown = options.shutdown.clone();
+    shutdown.listen_for_signals()?;
     distributor
         .send_transactions(config.core.rpc_urls, builder, options)
         .await?;
 
-    info!("Distribution completed successfully!");
+    if shutdown.is_triggered() {
+        warn!("Distribution stopped by a shutdown signal");
+    } else {
+        info!("Distribution completed successfully!");
+    }
+    Ok(())
+}
+
+// Build and sign everything a run would send, without sending it
+//
+// Continuous distribution types are planned and signed one round at a time. Signing for
+// `broadcast` writes the files the run depends on and groups transactions as `broadcast` sends
+// them; planning leaves files alone and groups them as a run would.
+async fn build_signed(
+    config: &UnifiedConfig,
+    for_broadcast: bool,
+) -> anyhow::Result<(PlanSummary, builders::presigned::PresignedBuilder)> {
+    let options = distribution_options(config)?;
+    let signer = read_signer("PRIVATE_KEY")?;
+    let provider = ProviderBuilder::new().on_http(read_rpc_url(config).parse().unwrap());
+
+    let recipients = prepare_recipients(config, signer.address(), &provider).await?;
+    let mut summary = plan_summary(config, signer.address(), &recipients, &options);
+    let (builder, distributor) =
+        create_distribution(config, recipients, signer, &provider, for_broadcast)?;
+
+    info!("Signing transactions...");
+    let transactions = builder.build_transactions(None).await?;
+    let groups = if for_broadcast {
+        distributors::finite::groups(transactions.len())
+    } else {
+        distributor.dispatch_groups(transactions.len(), &options)
+    };
+    let plan = TransactionPlan::new(&transactions, &groups);
+    info!(
+        "{} transactions in {} batches, gas limit {}, value {}, fees at most {}",
+        plan.count,
+        plan.batches.len(),
+        plan.gas_limit,
+        format_ether(U256::from_str(&plan.value)?),
+        format_ether(U256::from_str(&plan.max_fee_cost)?)
+    );
+    summary.transactions = Some(plan);
+    let presigned = builders::presigned::PresignedBuilder {
+        recipients: builder.recipients().to_vec(),
+        transactions,
+    };
+    Ok((summary, presigned))
+}
+
+// Show the batches, totals and gas estimates of a run
+//
+// Batches, nonces and gas come from signing, so planning for `--address` shows only the
+// recipients and totals.
+async fn plan(config: UnifiedConfig, matches: &ArgMatches<'_>) -> anyhow::Result<()> {
+    if let Some(address) = matches.value_of("address") {
+        let address = address.parse::<Address>()?;
+        let options = distribution_options(&config)?;
+        let provider = ProviderBuilder::new().on_http(read_rpc_url(&config).parse().unwrap());
+        let recipients = prepare_recipients(&config, address, &provider).await?;
+        let summary = plan_summary(&config, address, &recipients, &options);
+        info!("Not signing without PRIVATE_KEY, so batches, nonces and gas are left out");
+        println!("{}", summary.to_json());
+        return Ok(());
+    }
+    let (summary, _) = build_signed(&config, false).await?;
+    for (i, batch) in summary
+        .transactions
+        .iter()
+        .flat_map(|t| &t.batches)
+        .enumerate()
+    {
+        info!(
+            "Batch {}: {} transactions, nonces {}..={}, gas limit {}",
+            i + 1,
+            batch.transactions,
+            batch.first_nonce,
+            batch.last_nonce,
+            batch.gas_limit
+        );
+    }
+    println!("{}", summary.to_json());
+    Ok(())
+}
+
+// Sign a run's transactions into a file for `broadcast`
+async fn sign(config: UnifiedConfig, matches: &ArgMatches<'_>) -> anyhow::Result<()> {
+    let output_file = matches.value_of("output").expect("Output has a default");
+    let (summary, presigned) = build_signed(&config, true).await?;
+    presigned.write(output_file)?;
+    info!(
+        "Wrote {} signed transactions to {}",
+        presigned.transactions.len(),
+        output_file
+    );
+    println!("{}", summary.to_json());
+    Ok(())
+}
+
+// Send transactions signed earlier by `sign`, without needing the key
+async fn broadcast(config: UnifiedConfig, matches: &ArgMatches<'_>) -> anyhow::Result<()> {
+    let input_file = matches.value_of("input").expect("Input has a default");
+    let options = distribution_options(&config)?;
+    let presigned = builders::presigned::PresignedBuilder::read(input_file)?;
+    let Some(sender) = presigned.sender()? else {
+        info!("No transactions to broadcast in {}", input_file);
+        return Ok(());
+    };
+
+    let plan = TransactionPlan::new(
+        &presigned.transactions,
+        &distributors::finite::groups(presigned.transactions.len()),
+    );
+    let value = U256::from_str(&plan.value)?;
+    info!("Sender address: {:?}", sender);
+    info!(
+        "Broadcasting {} transactions in {} batches, value {}",
+        plan.count,
+        plan.batches.len(),
+        format_ether(value)
+    );
+    println!("{}", serde_json::to_string(&plan)?);
+    confirm(matches, presigned.recipients.len(), value)?;
+
+    let shutdown = options.shutdown.clone();
+    shutdown.listen_for_signals()?;
+    distributors::finite::send_group(config.core.rpc_urls, Box::new(presigned), options, sender)
+        .await?;
+    if shutdown.is_triggered() {
+        warn!("Broadcast stopped by a shutdown signal");
+    } else {
+        info!("Broadcast completed successfully!");
+    }
     Ok(())
 }
+
+// Show the nonce, balance and pending transactions of the signer
+async fn status(config: UnifiedConfig, matches: &ArgMatches<'_>) -> anyhow::Result<()> {
+    let address = match matches.value_of("address") {
+        Some(address) => address.parse::<Address>()?,
+        None => read_signer("PRIVATE_KEY")?.address(),
+    };
+    let provider = ProviderBuilder::new().on_http(read_rpc_url(&config).parse().unwrap());
+    let nonce = provider.get_transaction_count(address).await?;
+    let pending_nonce = provider.get_transaction_count(address).pending().await?;
+    let balance = provider.get_balance(address).await?;
+
+    info!("Address: {:?}", address);
+    info!("Nonce: {}", nonce);
+    info!(
+        "Pending transactions: {}",
+        pending_nonce.saturating_sub(nonce)
+    );
+    info!("Balance: {}", format_ether(balance));
+    println!(
+        "{}",
+        serde_json::json!({
+            "address": address,
+            "nonce": nonce,
+            "pending_nonce": pending_nonce,
+            "pending": pending_nonce.saturating_sub(nonce),
+            "balance": balance.to_string(),
+        })
+    );
+    Ok(())
+}
+
+// Summarize a journal, checking how much of it the chain has confirmed
+async fn report(config: UnifiedConfig, matches: &ArgMatches<'_>) -> anyhow::Result<()> {
+    let journal_file = matches
+        .value_of("journal")
+        .or(config.core.journal_file.as_deref())
+        .ok_or_else(|| anyhow::anyhow!("A journal file or core.journal_file must be specified"))?;
+    let summary = JournalSummary::new(&Journal::read(journal_file)?);
+    let provider = ProviderBuilder::new().on_http(read_rpc_url(&config).parse().unwrap());
+
+    info!("Journal: {}", journal_file);
+    info!(
+        "Transactions: {} to {} recipients",
+        summary.transactions, summary.recipients
+    );
+    info!("Gas limit: {}", summary.gas_limit);
+    info!("Value: {}", format_ether(summary.value));
+    let mut senders = serde_json::Map::new();
+    for (sender, nonces) in &summary.senders {
+        // Nonces below the sender's on-chain count have been mined
+        let confirmed_nonce = provider
+            .get_transaction_count(*sender)
+            .await?
+            .checked_sub(1);
+        let confirmed = match confirmed_nonce {
+            Some(confirmed) if confirmed >= nonces.last => "all confirmed".to_string(),
+            Some(confirmed) if confirmed >= nonces.first => {
+                format!("confirmed up to nonce {}", confirmed)
+            }
+            _ => "none confirmed".to_string(),
+        };
+        info!(
+            "Sender {:?}: nonces {}..={}, {}",
+            sender, nonces.first, nonces.last, confirmed
+        );
+        senders.insert(
+            sender.to_string(),
+            serde_json::json!({
+                "first_nonce": nonces.first,
+                "last_nonce": nonces.last,
+                "confirmed_nonce": confirmed_nonce,
+            }),
+        );
+    }
+    println!(
+        "{}",
+        serde_json::json!({
+            "transactions": summary.transactions,
+            "recipients": summary.recipients,
+            "gas_limit": summary.gas_limit,
+            "value": summary.value.to_string(),
+            "senders": senders,
+        })
+    );
+    Ok(())
+}
+
+#[tokio::main]
+pub async fn main() -> anyhow::Result<()> {
+    // Initialize logging on stderr, leaving stdout to machine-readable output
+    tracing_subscriber::fmt()
+        .with_writer(std::io::stderr)
+        .with_env_filter(
+            EnvFilter::try_from_default_env().unwrap_or_else(|_| EnvFilter::new("info")),
+        )
+        .init();
+
+    dotenv().ok();
+
+    // Shared by every subcommand, and accepted before or after it
+    let config_args = [
+        Arg::with_name("config")
+            .short("c")
+            .long("config")
+            .value_name("FILE")
+            .help("Sets a custom config file")
+            .default_value("config.yml")
+            .takes_value(true)
+            .global(true),
+        Arg::with_name("set")
+            .long("set")
+            .value_name("FIELD=VALUE")
+            .help("Overrides a config field, such as core.target_tps=2000 (repeatable)")
+            .takes_value(true)
+            .multiple(true)
+            .number_of_values(1)
+            .global(true),
+    ];
+    // Shared by the subcommands that send
+    let confirm_args = [
+        Arg::with_name("yes")
+            .short("y")
+            .long("yes")
+            .help("Starts without asking for confirmation"),
+        Arg::with_name("expect-total")
+            .long("expect-total")
+            .value_name("WEI")
+            .help("Starts without asking only if the plan sends exactly this total")
+            .takes_value(true),
+        Arg::with_name("expect-count")
+            .long("expect-count")
+            .value_name("N")
+            .help("Starts without asking only if the plan has exactly this many recipients")
+            .takes_value(true),
+    ];
+
+    // Parse command-line arguments
+    let matches = App::new("rainmaker")
+        .about("Distributes native tokens, NFTs and load test traffic")
+        .args(&config_args)
+        .args(&confirm_args)
+        .subcommand(
+            SubCommand::with_name("run")
+                .about("Sends the distribution [default]")
+                .args(&confirm_args),
+        )
+        .subcommand(
+            SubCommand::with_name("plan")
+                .about("Shows the batches, totals and gas estimates of a run without sending")
+                .arg(
+                    Arg::with_name("address")
+                        .short("a")
+                        .long("address")
+                        .value_name("ADDRESS")
+                        .help("Plans recipients and totals for this address, without signing")
+                        .takes_value(true),
+                ),
+        )
+        .subcommand(
+            SubCommand::with_name("sign")
+                .about("Signs a run's transactions into a file without sending them")
+                .arg(
+                    Arg::with_name("output")
+                        .short("o")
+                        .long("output")
+                        .value_name("FILE")
+                        .help("Where to write the signed transactions")
+                        .default_value("signed.json")
+                        .takes_value(true),
+                ),
+        )
+        .subcommand(
+            SubCommand::with_name("broadcast")
+                .about("Sends transactions written by sign, without needing the key")
+                .args(&confirm_args)
+                .arg(
+                    Arg::with_name("input")
+                        .short("i")
+                        .long("input")
+                        .value_name("FILE")
+                        .help("Signed transactions to send")
+                        .default_value("signed.json")
+                        .takes_value(true),
+                ),
+        )
+        .subcommand(
+            SubCommand::with_name("status")
+                .about("Shows the nonce, balance and pending transactions of the signer")
+                .arg(
+                    Arg::with_name("address")
+                        .short("a")
+                        .long("address")
+                        .value_name("ADDRESS")
+                        .help("Address to check instead of the PRIVATE_KEY signer")
+                        .takes_value(true),
+                ),
+        )
+        .subcommand(
+            SubCommand::with_name("verify")
+                .about("Checks that every recipient received exactly what was intended")
+                .arg(
+                    Arg::with_name("report")
+                        .short("r")
+                        .long("report")
+                        .value_name("FILE")
+                        .help("Journal of the run to verify [default: core.journal_file]")
+                        .takes_value(true),
+                )
+                .arg(
+                    Arg::with_name("output")
+                        .short("o")
+                        .long("output")
+                        .value_name("FILE")
+                        .help("Writes discrepancies to this file as JSON")
+                        .takes_value(true),
+                ),
+        )
+        .subcommand(
+            SubCommand::with_name("report")
+                .about("Summarizes a run's journal and how much of it is confirmed")
+                .arg(
+                    Arg::with_name("journal")
+                        .short("j")
+                        .long("journal")
+                        .value_name("FILE")
+                        .help("Journal to summarize [default: core.journal_file]")
+                        .takes_value(true),
+                ),
+        )
+        .subcommand(
+            SubCommand::with_name("validate")
+                .about("Checks the config and reports every problem, without needing a key"),
+        )
+        .get_matches();
+
+    // Global arguments are propagated down, so the subcommand's matches hold every value
+    let (subcommand, subcommand_matches) = matches.subcommand();
+    let subcommand_matches = subcommand_matches.unwrap_or(&matches);
+    let config = load_config(subcommand_matches)?;
+
+    match subcommand {
+        "" | "run" => run(config, subcommand_matches).await,
+        "plan" => plan(config, subcommand_matches).await,
+        "sign" => sign(config, subcommand_matches).await,
+        "broadcast" => broadcast(config, subcommand_matches).await,
+        "status" => status(config, subcommand_matches).await,
+        "verify" => run_verify(&config, subcommand_matches).await,
+        "report" => report(config, subcommand_matches).await,
+        "validate" => {
+            info!("Config is valid");
+            Ok(())
+        }
+        _ => unreachable!("Unknown subcommands are rejected by clap"),
+    }
+}
@@ -0,0 +1,270 @@
+use std::collections::HashMap;
+use std::future::Future;
+use std::ops::Range;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+use alloy::rpc::types::{BlockNumberOrTag, BlockTransactionsKind};
+use alloy::transports::RpcError;
+use futures::{StreamExt, TryStreamExt};
+
+use crate::prelude::*;
+
+/// Gas every transaction pays before running any code
+const INTRINSIC_GAS: u64 = 21_000;
+
+/// A batch call covering `recipients`, with the gas limit it was estimated to need
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PlannedBatch {
+    pub recipients: Range<usize>,
+    pub gas_limit: u64,
+}
+
+/// Providers gas is estimated on, taken in turn
+pub struct Endpoints<'a> {
+    providers: Vec<&'a dyn Provider>,
+    next: AtomicUsize,
+}
+
+impl<'a> Endpoints<'a> {
+    /// Every provider in `others`, or `primary` when there are none
+    pub fn new(primary: &'a dyn Provider, others: &'a [Box<dyn Provider>]) -> Self {
+        let mut providers = others.iter().map(AsRef::as_ref).collect::<Vec<_>>();
+        if providers.is_empty() {
+            providers.push(primary);
+        }
+        Self {
+            providers,
+            next: AtomicUsize::new(0),
+        }
+    }
+
+    /// Estimate `tx` on the next provider, trying the others in turn while they cannot be reached
+    ///
+    /// An error response, such as a revert, would be the same everywhere and is returned as is.
+    pub async fn estimate_gas(&self, tx: &TransactionRequest) -> Result<u64> {
+        let first = self.next.fetch_add(1, Ordering::Relaxed);
+        let mut last_error = None;
+        for i in 0..self.providers.len() {
+            let provider = self.providers[(first + i) % self.providers.len()];
+            match provider.estimate_gas(tx).await {
+                Ok(gas) => return Ok(gas),
+                Err(e @ RpcError::ErrorResp(_)) => return Err(e.into()),
+                Err(e) => {
+                    warn!("Gas estimate failed, trying another endpoint: {}", e);
+                    last_error = Some(e);
+                }
+            }
+        }
+        Err(last_error
+            .expect("there is always at least one provider")
+            .into())
+    }
+}
+
+/// Split `len` recipients into batch calls and estimate each one's gas limit
+///
+/// Without a gas target every call covers `config.size` (or `default_size`) recipients. With one,
+/// the per-recipient cost is calibrated from two estimates and calls are packed up to the target;
+/// when a call's own estimate still comes out above, it is shrunk by binary search and the calls
+/// after it are planned at the size that fit.
+pub async fn plan_batches<F, Fut>(
+    provider: &dyn Provider,
+    config: &BatchConfig,
+    default_size: usize,
+    len: usize,
+    estimate: F,
+) -> Result<Vec<PlannedBatch>>
+where
+    F: Fn(Range<usize>) -> Fut,
+    Fut: Future<Output = Result<u64>>,
+{
+    if len == 0 {
+        return Ok(vec![]);
+    }
+    let max_size = config.size.unwrap_or(default_size).max(1);
+    let mut estimates = Estimates::new(config, &estimate);
+    let Some(target_gas) = target_gas(provider, config).await? else {
+        let ranges = split(0..len, max_size);
+        let gas_limits = if config.extrapolate {
+            let representative = estimates.one(ranges[0].clone()).await?;
+            let per_recipient =
+                representative.saturating_sub(INTRINSIC_GAS) as f64 / ranges[0].len() as f64;
+            ranges
+                .iter()
+                .map(|range| INTRINSIC_GAS + (per_recipient * range.len() as f64).ceil() as u64)
+                .collect()
+        } else {
+            estimates.all(&ranges).await?
+        };
+        return Ok(zip(ranges, gas_limits));
+    };
+
+    // Cost of the first recipient, and of every one after it
+    let first = estimates.one(0..1).await?;
+    let sample = max_size.min(len);
+    let per_recipient = if sample > 1 {
+        let sampled = estimates.one(0..sample).await?;
+        (sampled.saturating_sub(first) / (sample as u64 - 1)).max(1)
+    } else {
+        first
+    };
+    let base = first.saturating_sub(per_recipient);
+    let mut size =
+        (target_gas.saturating_sub(base) / per_recipient).clamp(1, max_size as u64) as usize;
+    info!(
+        "Packing {} recipients per batch under {} gas ({} per recipient)",
+        size, target_gas, per_recipient
+    );
+    if config.extrapolate {
+        let ranges = split(0..len, size);
+        let gas_limits = ranges
+            .iter()
+            .map(|range| base + per_recipient * range.len() as u64)
+            .collect();
+        return Ok(zip(ranges, gas_limits));
+    }
+
+    let mut batches = Vec::new();
+    let mut start = 0;
+    while start < len {
+        let ranges = split(start..len, size);
+        let gas_limits = estimates.all(&ranges).await?;
+        let over = gas_limits.iter().position(|gas| *gas > target_gas);
+        let fitting = over.unwrap_or(ranges.len());
+        batches.extend(zip(
+            ranges[..fitting].to_vec(),
+            gas_limits[..fitting].to_vec(),
+        ));
+        let Some(over) = over else {
+            break;
+        };
+
+        // The largest count known to fit, and the smallest known not to
+        let range = &ranges[over];
+        let (mut fits, mut low, mut high) = (None, 1, range.len());
+        while low < high {
+            let mid = low + (high - low) / 2;
+            let gas = estimates.one(range.start..range.start + mid).await?;
+            if gas <= target_gas {
+                fits = Some((mid, gas));
+                low = mid + 1;
+            } else {
+                high = mid;
+            }
+        }
+        let (count, gas_limit) = fits.ok_or_else(|| {
+            anyhow::anyhow!(
+                "A single recipient needs more than the target of {} gas",
+                target_gas
+            )
+        })?;
+        batches.push(PlannedBatch {
+            recipients: range.start..range.start + count,
+            gas_limit,
+        });
+        start = range.start + count;
+        size = count;
+    }
+    Ok(batches)
+}
+
+/// Gas estimates of a single plan, run concurrently and optionally cached by recipient count
+///
+/// The cache assumes every recipient costs the same, while paying an account that does not exist
+/// yet costs more, so two batches of the same size can need different gas limits.
+struct Estimates<'a, F> {
+    config: &'a BatchConfig,
+    estimate: &'a F,
+    cache: HashMap<usize, u64>,
+}
+
+impl<'a, F, Fut> Estimates<'a, F>
+where
+    F: Fn(Range<usize>) -> Fut,
+    Fut: Future<Output = Result<u64>>,
+{
+    fn new(config: &'a BatchConfig, estimate: &'a F) -> Self {
+        Self {
+            config,
+            estimate,
+            cache: HashMap::new(),
+        }
+    }
+
+    async fn one(&mut self, range: Range<usize>) -> Result<u64> {
+        Ok(self.all(&[range]).await?[0])
+    }
+
+    async fn all(&mut self, ranges: &[Range<usize>]) -> Result<Vec<u64>> {
+        if !self.config.cache {
+            return self.run(ranges.to_vec()).await;
+        }
+        // Only the first call of each size not seen yet is estimated
+        let mut missing = Vec::new();
+        for range in ranges {
+            if !self.cache.contains_key(&range.len())
+                && !missing
+                    .iter()
+                    .any(|m: &Range<usize>| m.len() == range.len())
+            {
+                missing.push(range.clone());
+            }
+        }
+        let lens = missing.iter().map(|range| range.len()).collect::<Vec<_>>();
+        let gas_limits = self.run(missing).await?;
+        self.cache.extend(lens.into_iter().zip(gas_limits));
+        Ok(ranges
+            .iter()
+            .map(|range| self.cache[&range.len()])
+            .collect())
+    }
+
+    async fn run(&self, ranges: Vec<Range<usize>>) -> Result<Vec<u64>> {
+        if ranges.len() > 1 {
+            info!(
+                "---> Estimating gas for {} batches, {} at a time",
+                ranges.len(),
+                self.config.concurrency
+            );
+        }
+        futures::stream::iter(ranges)
+            .map(self.estimate)
+            .buffered(self.config.concurrency.max(1))
+            .try_collect()
+            .await
+    }
+}
+
+fn split(range: Range<usize>, size: usize) -> Vec<Range<usize>> {
+    range
+        .clone()
+        .step_by(size)
+        .map(|start| start..(start + size).min(range.end))
+        .collect()
+}
+
+fn zip(ranges: Vec<Range<usize>>, gas_limits: Vec<u64>) -> Vec<PlannedBatch> {
+    ranges
+        .into_iter()
+        .zip(gas_limits)
+        .map(|(recipients, gas_limit)| PlannedBatch {
+            recipients,
+            gas_limit,
+        })
+        .collect()
+}
+
+/// Gas limit batch calls are packed under, if any
+async fn target_gas(provider: &dyn Provider, config: &BatchConfig) -> Result<Option<u64>> {
+    if let Some(target_gas) = config.target_gas {
+        return Ok(Some(target_gas));
+    }
+    let Some(fraction) = config.block_gas_fraction else {
+        return Ok(None);
+    };
+    let block = provider
+        .get_block_by_number(BlockNumberOrTag::Latest, BlockTransactionsKind::Hashes)
+        .await?
+        .ok_or_else(|| anyhow::anyhow!("Latest block not found"))?;
+    Ok(Some((block.header.gas_limit as f64 * fraction) as u64))
+}
@@ -0,0 +1,152 @@
+use crate::prelude::*;
+use alloy::dyn_abi::{DynSolType, DynSolValue, JsonAbiExt, Specifier};
+use alloy::json_abi::Function;
+use futures::{StreamExt, TryStreamExt};
+
+use super::native::float_to_u256;
+
+/// `eth_estimateGas` calls in flight at once
+const ESTIMATE_CONCURRENCY: usize = 16;
+
+// Generic contract call builder, driven by a function signature and an argument template
+pub struct ContractCallBuilder {
+    pub signer: PrivateKeySigner,
+    pub provider: Box<dyn Provider>,
+    pub contract_address: Address,
+    pub function: Function,
+    pub args: Vec<ArgSource>,
+    /// Rows of the addresses file, one call per row. When empty, `max_calls` calls are built
+    pub rows: Vec<Vec<String>>,
+    /// Address in the first column of each row, which calls' recipient indices refer to
+    pub recipients: Vec<Address>,
+    pub max_calls: u64,
+    pub value: f64,
+}
+
+impl ContractCallBuilder {
+    /// Render the raw argument strings for every call, in nonce order
+    fn render_args(&self, num_calls: usize) -> Result<Vec<Vec<String>>> {
+        let mut rng = rand::rng();
+        (0..num_calls)
+            .map(|i| {
+                self.args
+                    .iter()
+                    .map(|arg| {
+                        Ok(match arg {
+                            ArgSource::Column { index } => self
+                                .rows
+                                .get(i)
+                                .and_then(|row| row.get(*index))
+                                .ok_or_else(|| {
+                                    anyhow::anyhow!("Row {} has no column {}", i, index)
+                                })?
+                                .clone(),
+                            ArgSource::Counter { start, step } => {
+                                (*start + *step * i as u64).to_string()
+                            }
+                            ArgSource::Random { low, high } => {
+                                rng.random_range(*low..=*high).to_string()
+                            }
+                            ArgSource::Constant { value } => value.clone(),
+                        })
+                    })
+                    .collect()
+            })
+            .collect()
+    }
+
+    /// ABI-encode the call data for a single call
+    fn encode_call(&self, types: &[DynSolType], args: &[String]) -> Result<Vec<u8>> {
+        let values = types
+            .iter()
+            .zip(args)
+            .map(|(ty, arg)| ty.coerce_str(arg))
+            .collect::<Result<Vec<DynSolValue>, _>>()?;
+        Ok(self.function.abi_encode_input(&values)?)
+    }
+}
+
+#[async_trait]
+impl Builder for ContractCallBuilder {
+    async fn build_transactions(
+        &self,
+        start_nonce_override: Option<u64>,
+    ) -> Result<Vec<SignedTransaction>> {
+        if self.args.len() != self.function.inputs.len() {
+            return Err(anyhow::anyhow!(
+                "Function {} takes {} arguments, but {} were configured",
+                self.function.signature(),
+                self.function.inputs.len(),
+                self.args.len()
+            ));
+        }
+        let types = self
+            .function
+            .inputs
+            .iter()
+            .map(|param| param.resolve())
+            .collect::<Result<Vec<_>, _>>()?;
+
+        let starting_nonce = if let Some(start_nonce) = start_nonce_override {
+            start_nonce
+        } else {
+            self.provider
+                .get_transaction_count(self.signer.address())
+                .await?
+        };
+        let num_calls = if self.rows.is_empty() {
+            self.max_calls as usize
+        } else {
+            self.rows.len()
+        };
+        let calldata = self
+            .render_args(num_calls)?
+            .iter()
+            .map(|args| self.encode_call(&types, args))
+            .collect::<Result<Vec<_>>>()?;
+        let value = float_to_u256(self.value);
+
+        // Arguments change the storage a call touches, so every call gets its own estimate
+        info!("Estimating gas for {} calls...", calldata.len());
+        let gas_limits = futures::stream::iter(calldata.clone())
+            .map(|input| {
+                let sim_tx = TransactionRequest::default()
+                    .from(self.signer.address())
+                    .to(self.contract_address)
+                    .value(value)
+                    .input(input.into());
+                estimate_gas(self.provider.as_ref(), sim_tx)
+            })
+            .buffered(ESTIMATE_CONCURRENCY)
+            .try_collect::<Vec<_>>()
+            .await?;
+
+        calldata
+            .into_par_iter()
+            .zip(gas_limits)
+            .enumerate()
+            .map(|(i, (input, gas_limit))| {
+                let tx = TxLegacy {
+                    nonce: starting_nonce + i as u64,
+                    gas_price: 52_000_000_000u128,
+                    gas_limit,
+                    to: TxKind::Call(self.contract_address),
+                    value,
+                    input: input.into(),
+                    chain_id: Some(10143),
+                };
+                // Calls only cover a recipient when driven by the addresses file
+                let recipients = if self.rows.is_empty() { 0..0 } else { i..i + 1 };
+                SignedTransaction::sign_legacy(&self.signer, tx, recipients)
+            })
+            .collect()
+    }
+
+    fn recipients(&self) -> &[Address] {
+        &self.recipients
+    }
+}
+
+async fn estimate_gas(provider: &dyn Provider, tx: TransactionRequest) -> Result<u64> {
+    Ok(provider.estimate_gas(&tx).await? * 105 / 100)
+}
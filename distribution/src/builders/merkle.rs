@@ -0,0 +1,342 @@
+use std::sync::Mutex;
+
+use alloy::primitives::B256;
+use alloy::sol_types::SolValue;
+use futures::{stream, StreamExt, TryStreamExt};
+
+use crate::merkle::{leaf, verify_proof, Claim};
+use crate::prelude::*;
+
+sol! {
+    contract MerkleDistributor {
+        event Claimed(uint256 index, address account, uint256 amount);
+
+        function merkleRoot() external view returns (bytes32);
+        function setMerkleRoot(bytes32 root) external;
+        function isClaimed(uint256 index) external view returns (bool);
+        function claim(uint256 index, address account, uint256 amount, bytes32[] calldata merkleProof) external;
+    }
+}
+
+/// Gas for funding a distributor that is deployed in the same run and cannot be estimated yet
+const FUND_GAS_LIMIT: u64 = 50_000;
+
+// Deploys or re-roots a MerkleDistributor, then funds it with the total of every claim
+pub struct MerkleDistributorBuilder {
+    pub signer: PrivateKeySigner,
+    pub provider: Box<dyn Provider>,
+    /// Existing distributor to point at `root`, a new one is deployed from `bytecode` when unset
+    pub distributor_address: Option<Address>,
+    pub bytecode: Option<Bytes>,
+    pub root: B256,
+    pub total: U256,
+    pub fund: bool,
+    /// Distributor address, known once transactions are built
+    pub distributor: Mutex<Option<Address>>,
+}
+
+async fn read_root(provider: &dyn Provider, distributor: Address) -> Result<B256> {
+    let call_tx = TransactionRequest::default()
+        .to(distributor)
+        .input(MerkleDistributor::merkleRootCall {}.abi_encode().into());
+    let output = provider.call(&call_tx).await?;
+    Ok(MerkleDistributor::merkleRootCall::abi_decode_returns(&output, true)?._0)
+}
+
+/// Whether each claim index has been claimed, checked concurrently
+async fn claimed(
+    provider: &dyn Provider,
+    distributor: Address,
+    indices: Vec<u64>,
+) -> Result<Vec<bool>> {
+    stream::iter(indices)
+        .map(|index| async move {
+            let call_tx = TransactionRequest::default().to(distributor).input(
+                MerkleDistributor::isClaimedCall {
+                    index: U256::from(index),
+                }
+                .abi_encode()
+                .into(),
+            );
+            let output = provider.call(&call_tx).await?;
+            Ok::<_, Error>(MerkleDistributor::isClaimedCall::abi_decode_returns(&output, true)?._0)
+        })
+        .buffered(16)
+        .try_collect()
+        .await
+}
+
+#[async_trait]
+impl Builder for MerkleDistributorBuilder {
+    async fn build_transactions(
+        &self,
+        start_nonce_override: Option<u64>,
+    ) -> Result<Vec<SignedTransaction>> {
+        let starting_nonce = if let Some(start_nonce) = start_nonce_override {
+            start_nonce
+        } else {
+            self.provider
+                .get_transaction_count(self.signer.address())
+                .await?
+        };
+
+        let (distributor, setup_tx) = if let Some(distributor) = self.distributor_address {
+            let input: Bytes = MerkleDistributor::setMerkleRootCall { root: self.root }
+                .abi_encode()
+                .into();
+            let sim_tx = TransactionRequest::default()
+                .from(self.signer.address())
+                .to(distributor)
+                .input(input.clone().into());
+            let gas_limit = self.provider.estimate_gas(&sim_tx).await? * 105 / 100;
+            let tx = TxLegacy {
+                nonce: starting_nonce,
+                gas_price: 52_000_000_000u128,
+                gas_limit,
+                to: TxKind::Call(distributor),
+                value: U256::ZERO,
+                input,
+                chain_id: Some(10143),
+            };
+            (distributor, tx)
+        } else {
+            let bytecode = self.bytecode.as_ref().ok_or_else(|| {
+                anyhow::anyhow!("Bytecode is required to deploy a Merkle distributor")
+            })?;
+            // Creation code followed by the constructor's root argument
+            let input: Bytes = [bytecode.as_ref(), &self.root.abi_encode()].concat().into();
+            let mut sim_tx = TransactionRequest::default()
+                .from(self.signer.address())
+                .input(input.clone().into());
+            sim_tx.to = Some(TxKind::Create);
+            let gas_limit = self.provider.estimate_gas(&sim_tx).await? * 105 / 100;
+            let tx = TxLegacy {
+                nonce: starting_nonce,
+                gas_price: 52_000_000_000u128,
+                gas_limit,
+                to: TxKind::Create,
+                value: U256::ZERO,
+                input,
+                chain_id: Some(10143),
+            };
+            (self.signer.address().create(starting_nonce), tx)
+        };
+        info!("Merkle distributor: {}", distributor);
+        *self.distributor.lock().unwrap() = Some(distributor);
+
+        // The distributor has no recipients of its own, claims pay them later
+        let mut txs = vec![SignedTransaction::sign_legacy(
+            &self.signer,
+            setup_tx,
+            0..0,
+        )?];
+        if self.fund {
+            let gas_limit = if self.distributor_address.is_some() {
+                let sim_tx = TransactionRequest::default()
+                    .from(self.signer.address())
+                    .to(distributor)
+                    .value(self.total);
+                self.provider.estimate_gas(&sim_tx).await? * 105 / 100
+            } else {
+                FUND_GAS_LIMIT
+            };
+            let tx = TxLegacy {
+                nonce: starting_nonce + 1,
+                gas_price: 52_000_000_000u128,
+                gas_limit,
+                to: TxKind::Call(distributor),
+                value: self.total,
+                input: vec![].into(),
+                chain_id: Some(10143),
+            };
+            txs.push(SignedTransaction::sign_legacy(&self.signer, tx, 0..0)?);
+        }
+        Ok(txs)
+    }
+
+    async fn verify(&self) -> Result<()> {
+        let Some(distributor) = *self.distributor.lock().unwrap() else {
+            return Ok(());
+        };
+        let root = read_root(self.provider.as_ref(), distributor).await?;
+        if root != self.root {
+            return Err(anyhow::anyhow!(
+                "Merkle distributor {} has root {}, expected {}",
+                distributor,
+                root,
+                self.root
+            ));
+        }
+        info!("Merkle distributor {} has root {}", distributor, root);
+        Ok(())
+    }
+}
+
+// Submits claims from a proofs file on behalf of their recipients
+pub struct MerkleClaimBuilder {
+    pub signer: PrivateKeySigner,
+    pub provider: Box<dyn Provider>,
+    pub distributor_address: Address,
+    pub root: B256,
+    pub claims: Vec<Claim>,
+    /// Check `isClaimed` and leave out claims that were already made
+    pub skip_claimed: bool,
+    /// Claim accounts, in the same order as `claims`
+    recipients: Vec<Address>,
+    /// Indices of the claims built, checked once mined
+    submitted: Mutex<Option<Vec<u64>>>,
+}
+
+impl MerkleClaimBuilder {
+    pub fn new(
+        signer: PrivateKeySigner,
+        provider: Box<dyn Provider>,
+        distributor_address: Address,
+        root: B256,
+        claims: Vec<Claim>,
+        skip_claimed: bool,
+    ) -> Self {
+        let recipients = claims.iter().map(|claim| claim.account).collect();
+        Self {
+            signer,
+            provider,
+            distributor_address,
+            root,
+            claims,
+            skip_claimed,
+            recipients,
+            submitted: Mutex::new(None),
+        }
+    }
+
+    fn claim_input(claim: &Claim) -> Bytes {
+        MerkleDistributor::claimCall {
+            index: U256::from(claim.index),
+            account: claim.account,
+            amount: claim.amount,
+            merkleProof: claim.proof.clone(),
+        }
+        .abi_encode()
+        .into()
+    }
+}
+
+#[async_trait]
+impl Builder for MerkleClaimBuilder {
+    async fn build_transactions(
+        &self,
+        start_nonce_override: Option<u64>,
+    ) -> Result<Vec<SignedTransaction>> {
+        // Claims against any other root would all revert
+        let root = read_root(self.provider.as_ref(), self.distributor_address).await?;
+        if root != self.root {
+            return Err(anyhow::anyhow!(
+                "Merkle distributor {} has root {}, but the proofs are for {}",
+                self.distributor_address,
+                root,
+                self.root
+            ));
+        }
+        for claim in &self.claims {
+            let hash = leaf(claim.index, claim.account, claim.amount);
+            if !verify_proof(&claim.proof, self.root, hash) {
+                return Err(anyhow::anyhow!(
+                    "Invalid proof for claim {} of {}",
+                    claim.index,
+                    claim.account
+                ));
+            }
+        }
+
+        let claimed = if self.skip_claimed {
+            claimed(
+                self.provider.as_ref(),
+                self.distributor_address,
+                self.claims.iter().map(|claim| claim.index).collect(),
+            )
+            .await?
+        } else {
+            vec![false; self.claims.len()]
+        };
+        let pending = self
+            .claims
+            .iter()
+            .enumerate()
+            .filter(|(i, _)| !claimed[*i])
+            .collect::<Vec<_>>();
+        if pending.len() < self.claims.len() {
+            info!(
+                "Skipping {} claims already made",
+                self.claims.len() - pending.len()
+            );
+        }
+        *self.submitted.lock().unwrap() =
+            Some(pending.iter().map(|(_, claim)| claim.index).collect());
+
+        // The longest proof costs the most to verify, so its estimate covers every claim
+        let Some((_, longest)) = pending.iter().max_by_key(|(_, claim)| claim.proof.len()) else {
+            return Ok(vec![]);
+        };
+        let sim_tx = TransactionRequest::default()
+            .from(self.signer.address())
+            .to(self.distributor_address)
+            .input(Self::claim_input(longest).into());
+        let gas_limit = self.provider.estimate_gas(&sim_tx).await? * 105 / 100;
+
+        let starting_nonce = if let Some(start_nonce) = start_nonce_override {
+            start_nonce
+        } else {
+            self.provider
+                .get_transaction_count(self.signer.address())
+                .await?
+        };
+
+        pending
+            .into_par_iter()
+            .enumerate()
+            .map(|(i, (recipient, claim))| {
+                let tx = TxLegacy {
+                    nonce: starting_nonce + i as u64,
+                    gas_price: 52_000_000_000u128,
+                    gas_limit,
+                    to: TxKind::Call(self.distributor_address),
+                    value: U256::ZERO,
+                    input: Self::claim_input(claim),
+                    chain_id: Some(10143),
+                };
+                SignedTransaction::sign_legacy(&self.signer, tx, recipient..recipient + 1)
+            })
+            .collect()
+    }
+
+    fn recipients(&self) -> &[Address] {
+        &self.recipients
+    }
+
+    async fn verify(&self) -> Result<()> {
+        let Some(submitted) = self.submitted.lock().unwrap().clone() else {
+            return Ok(());
+        };
+        let claimed = claimed(
+            self.provider.as_ref(),
+            self.distributor_address,
+            submitted.clone(),
+        )
+        .await?;
+        let unclaimed = submitted
+            .iter()
+            .zip(claimed)
+            .filter(|(_, claimed)| !claimed)
+            .map(|(index, _)| index.to_string())
+            .collect::<Vec<_>>();
+        if !unclaimed.is_empty() {
+            return Err(anyhow::anyhow!(
+                "{} claims are still unclaimed: {}",
+                unclaimed.len(),
+                unclaimed.join(", ")
+            ));
+        }
+        info!("All {} claims were made", submitted.len());
+        Ok(())
+    }
+}
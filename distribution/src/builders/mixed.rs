@@ -0,0 +1,178 @@
+use crate::prelude::*;
+use std::collections::VecDeque;
+use tokio::sync::Mutex;
+
+/// A single builder in a mixed workload, scheduled according to its weight
+pub struct WeightedBuilder {
+    pub name: String,
+    pub weight: f64,
+    pub builder: Box<dyn Builder + Send + Sync>,
+    /// Built once and dropped from the schedule when drained instead of being rebuilt, for
+    /// builders that pay a fixed list of recipients or carry state from one build to the next
+    pub finite: bool,
+}
+
+#[derive(Default)]
+struct SchedulerState {
+    /// Signed transactions not yet handed to the distributor, per builder
+    queues: Vec<VecDeque<SignedTransaction>>,
+    /// Next nonce per builder, once it has built at least once
+    next_nonces: Vec<Option<u64>>,
+    /// Smooth weighted round-robin credits per builder
+    credits: Vec<f64>,
+    /// Finite builders that have sent everything they built
+    exhausted: Vec<bool>,
+}
+
+// Interleaves several builders into one transaction stream, in proportion to their weights
+pub struct MixedBuilder {
+    pub builders: Vec<WeightedBuilder>,
+    /// Number of transactions emitted per call to `build_transactions`
+    pub round_size: usize,
+    /// Every builder's recipients back to back, which emitted transactions' ranges refer to
+    recipients: Vec<Address>,
+    /// Where each builder's recipients start in `recipients`
+    offsets: Vec<usize>,
+    state: Mutex<SchedulerState>,
+}
+
+impl MixedBuilder {
+    pub fn new(builders: Vec<WeightedBuilder>, round_size: usize) -> Self {
+        let state = SchedulerState {
+            queues: builders.iter().map(|_| VecDeque::new()).collect(),
+            next_nonces: vec![None; builders.len()],
+            credits: vec![0.0; builders.len()],
+            exhausted: vec![false; builders.len()],
+        };
+        let mut recipients = Vec::new();
+        let mut offsets = Vec::with_capacity(builders.len());
+        for weighted in &builders {
+            offsets.push(recipients.len());
+            recipients.extend_from_slice(weighted.builder.recipients());
+        }
+        Self {
+            builders,
+            round_size,
+            recipients,
+            offsets,
+            state: Mutex::new(state),
+        }
+    }
+
+    /// Pick the next builder that is not exhausted using smooth weighted round-robin
+    fn next_index(&self, credits: &mut [f64], exhausted: &[bool]) -> Option<usize> {
+        let active = || {
+            self.builders
+                .iter()
+                .zip(exhausted)
+                .enumerate()
+                .filter(|(_, (_, exhausted))| !**exhausted)
+                .map(|(i, (builder, _))| (i, builder))
+        };
+        let total_weight = active().map(|(_, b)| b.weight).sum::<f64>();
+        for (i, builder) in active() {
+            credits[i] += builder.weight;
+        }
+        let (index, _) = active().max_by(|(a, _), (b, _)| credits[*a].total_cmp(&credits[*b]))?;
+        credits[index] -= total_weight;
+        Some(index)
+    }
+}
+
+/// Split recipients into one contiguous share per signer, as even as possible
+pub fn split_recipients(
+    addresses: &[Address],
+    amounts: &[U256],
+    signers: usize,
+) -> Vec<(Vec<Address>, Vec<U256>)> {
+    let signers = signers.max(1);
+    (0..signers)
+        .map(|i| {
+            let start = addresses.len() * i / signers;
+            let end = addresses.len() * (i + 1) / signers;
+            let amounts = if amounts.is_empty() {
+                vec![]
+            } else {
+                amounts[start..end].to_vec()
+            };
+            (addresses[start..end].to_vec(), amounts)
+        })
+        .collect()
+}
+
+#[async_trait]
+impl Builder for MixedBuilder {
+    /// Each builder tracks its own signer's nonces, so the override is ignored
+    ///
+    /// Returns fewer than `round_size` transactions once only exhausted finite builders are left.
+    async fn build_transactions(
+        &self,
+        _start_nonce_override: Option<u64>,
+    ) -> Result<Vec<SignedTransaction>> {
+        let mut state = self.state.lock().await;
+        let state = &mut *state;
+        let mut txs = Vec::with_capacity(self.round_size);
+
+        while txs.len() < self.round_size {
+            let Some(index) = self.next_index(&mut state.credits, &state.exhausted) else {
+                break;
+            };
+            let weighted = &self.builders[index];
+            if state.queues[index].is_empty() {
+                if weighted.finite && state.next_nonces[index].is_some() {
+                    info!("Workload {} has sent everything", weighted.name);
+                    state.exhausted[index] = true;
+                    continue;
+                }
+                debug!("Refilling workload {}", weighted.name);
+                let built = weighted
+                    .builder
+                    .build_transactions(state.next_nonces[index])
+                    .await?;
+                let Some(last_tx) = built.last() else {
+                    if weighted.finite {
+                        info!("Workload {} has nothing to send", weighted.name);
+                        state.exhausted[index] = true;
+                        continue;
+                    }
+                    return Err(anyhow::anyhow!(
+                        "Workload {} built no transactions",
+                        weighted.name
+                    ));
+                };
+                state.next_nonces[index] = Some(last_tx.nonce + 1);
+                state.queues[index].extend(built);
+            }
+            if let Some(mut tx) = state.queues[index].pop_front() {
+                let offset = self.offsets[index];
+                tx.recipients = tx.recipients.start + offset..tx.recipients.end + offset;
+                txs.push(tx);
+            }
+        }
+
+        Ok(txs)
+    }
+
+    fn recipients(&self) -> &[Address] {
+        &self.recipients
+    }
+
+    /// Runs every builder's check, so one failing workload does not hide another
+    async fn verify(&self) -> Result<()> {
+        let mut failed = Vec::new();
+        for weighted in &self.builders {
+            if let Err(e) = weighted.builder.verify().await {
+                error!("Workload {} failed verification: {}", weighted.name, e);
+                failed.push(weighted.name.as_str());
+            }
+        }
+        if failed.is_empty() {
+            Ok(())
+        } else {
+            Err(anyhow::anyhow!(
+                "Verification failed for workloads: {}",
+                failed.join(", ")
+            ))
+        }
+    }
+}
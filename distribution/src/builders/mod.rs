@@ -1,3 +1,10 @@
+pub mod batching;
+pub mod contract_call;
+pub mod merkle;
+pub mod mixed;
 pub mod native;
 pub mod nft;
+pub mod presigned;
+pub mod strict_counter;
 pub mod swapper;
+pub mod uniswap_v2;
@@ -1,3 +1,6 @@
+use std::ops::Range;
+
+use crate::builders::batching::{plan_batches, Endpoints};
 use crate::prelude::*;
 
 sol! {
@@ -7,7 +10,7 @@ sol! {
     }
 }
 
-fn float_to_u256(amount: f64) -> U256 {
+pub fn float_to_u256(amount: f64) -> U256 {
     // Create a U256 representation of 10^18 (1 ETH in wei)
     let one_eth_in_wei = U256::from(10).pow(U256::from(18));
 
@@ -27,12 +30,16 @@ pub struct NativeTransferBuilder {
     pub provider: Box<dyn Provider>,
     pub signer: PrivateKeySigner,
     pub recipients: Vec<Address>,
-    pub amounts: Vec<f64>,
+    /// Amount for each recipient, in wei
+    pub amounts: Vec<U256>,
 }
 
 #[async_trait]
 impl Builder for NativeTransferBuilder {
-    async fn build_transactions(&self, start_nonce_override: Option<u64>) -> Result<Vec<String>> {
+    async fn build_transactions(
+        &self,
+        start_nonce_override: Option<u64>,
+    ) -> Result<Vec<SignedTransaction>> {
         assert!(
             self.recipients.len() == self.amounts.len(),
             "Recipients and amounts must be the same length"
@@ -45,31 +52,26 @@ impl Builder for NativeTransferBuilder {
                 .get_transaction_count(self.signer.address())
                 .await?
         };
-        Ok(self
-            .recipients
+        self.recipients
             .par_iter()
             .enumerate()
             .map(|(i, recipient)| {
-                let mut tx = TxLegacy::default();
-                tx.nonce = starting_nonce + i as u64;
-                tx.gas_price = 52_000_000_000u128;
-                tx.gas_limit = gas_limit;
-                tx.to = TxKind::Call(*recipient);
-                tx.value = float_to_u256(self.amounts[i]);
-                tx.input = vec![].into();
-                tx.chain_id = Some(10143);
-
-                let signature = self
-                    .signer
-                    .sign_transaction_sync(&mut tx)
-                    .expect("Failed to sign transaction");
-                let signed_tx = tx.into_signed(signature);
-
-                let mut buf = Vec::new();
-                signed_tx.rlp_encode(&mut buf);
-                format!("0x{}", hex::encode(buf))
+                let tx = TxLegacy {
+                    nonce: starting_nonce + i as u64,
+                    gas_price: 52_000_000_000u128,
+                    gas_limit,
+                    to: TxKind::Call(*recipient),
+                    value: self.amounts[i],
+                    input: vec![].into(),
+                    chain_id: Some(10143),
+                };
+                SignedTransaction::sign_legacy(&self.signer, tx, i..i + 1)
             })
-            .collect())
+            .collect()
+    }
+
+    fn recipients(&self) -> &[Address] {
+        &self.recipients
     }
 }
 
@@ -79,12 +81,31 @@ pub struct NativeBatchSenderBuilder {
     pub provider: Box<dyn Provider>,
     pub contract_address: Address,
     pub recipients: Vec<Address>,
-    pub amounts: Vec<f64>,
+    /// Amount for each recipient, in wei
+    pub amounts: Vec<U256>,
+    pub batch: BatchConfig,
+    /// Endpoints gas estimates are spread over, `provider` alone when empty
+    pub estimate_providers: Vec<Box<dyn Provider>>,
+}
+
+impl NativeBatchSenderBuilder {
+    /// Recipients per call unless configured otherwise
+    pub const DEFAULT_BATCH_SIZE: usize = 1000;
+
+    fn batch_send_call(&self, recipients: Range<usize>) -> BatchSender::batchSendCall {
+        BatchSender::batchSendCall {
+            recipients: self.recipients[recipients.clone()].to_vec(),
+            amounts: self.amounts[recipients].to_vec(),
+        }
+    }
 }
 
 #[async_trait]
 impl Builder for NativeBatchSenderBuilder {
-    async fn build_transactions(&self, start_nonce_override: Option<u64>) -> Result<Vec<String>> {
+    async fn build_transactions(
+        &self,
+        start_nonce_override: Option<u64>,
+    ) -> Result<Vec<SignedTransaction>> {
         assert!(
             self.recipients.len() == self.amounts.len(),
             "Recipients and amounts must be the same length"
@@ -96,65 +117,48 @@ impl Builder for NativeBatchSenderBuilder {
                 .get_transaction_count(self.signer.address())
                 .await?
         };
-        let batch_addresses = self.recipients.chunks(1000).collect::<Vec<_>>();
-        let batch_amounts = self
-            .amounts
-            .clone()
-            .into_iter()
-            .map(float_to_u256)
-            .collect::<Vec<_>>();
-        let batch_amounts = batch_amounts.chunks(1000).collect::<Vec<_>>();
-
-        let mut gas_limits = Vec::new();
+
         info!("Estimating batch gas limits...");
-        for i in 0..batch_addresses.len() {
-            info!("---> Estimating gas for batch {}", i);
-            let batch_transfer_call = BatchSender::batchSendCall {
-                recipients: batch_addresses[i].to_vec(),
-                amounts: batch_amounts[i].to_vec(),
-            };
-            let calldata = batch_transfer_call.abi_encode();
-            let sim_tx = TransactionRequest::default()
-                .from(self.signer.address())
-                .to(self.contract_address)
-                .value(batch_amounts[i].iter().sum())
-                .input(calldata.into());
-            gas_limits.push(self.provider.estimate_gas(&sim_tx).await? * 110 / 100);
-        }
+        let endpoints = Endpoints::new(self.provider.as_ref(), &self.estimate_providers);
+        let batches = plan_batches(
+            self.provider.as_ref(),
+            &self.batch,
+            Self::DEFAULT_BATCH_SIZE,
+            self.recipients.len(),
+            |recipients| {
+                let batch_transfer_call = self.batch_send_call(recipients.clone());
+                let sim_tx = TransactionRequest::default()
+                    .from(self.signer.address())
+                    .to(self.contract_address)
+                    .value(self.amounts[recipients].iter().sum())
+                    .input(batch_transfer_call.abi_encode().into());
+                let endpoints = &endpoints;
+                async move { Ok(endpoints.estimate_gas(&sim_tx).await? * 110 / 100) }
+            },
+        )
+        .await?;
         info!("Batch gas limits estimated");
 
-        Ok(batch_addresses
+        batches
             .into_par_iter()
             .enumerate()
-            .map(|(i, recipients)| {
-                let amounts = batch_amounts[i].to_vec();
-                let gas_limit = gas_limits[i];
-
-                let mut tx = TxLegacy::default();
-                tx.nonce = starting_nonce + i as u64;
-                tx.gas_price = 52_000_000_000u128;
-                tx.gas_limit = gas_limit;
-                tx.to = TxKind::Call(self.contract_address);
-                tx.value = amounts.iter().sum();
-
-                let batch_transfer_call = BatchSender::batchSendCall {
-                    recipients: recipients.to_vec(),
-                    amounts,
+            .map(|(i, batch)| {
+                let batch_transfer_call = self.batch_send_call(batch.recipients.clone());
+                let tx = TxLegacy {
+                    nonce: starting_nonce + i as u64,
+                    gas_price: 52_000_000_000u128,
+                    gas_limit: batch.gas_limit,
+                    to: TxKind::Call(self.contract_address),
+                    value: batch_transfer_call.amounts.iter().sum(),
+                    input: batch_transfer_call.abi_encode().into(),
+                    chain_id: Some(10143),
                 };
-                let calldata = batch_transfer_call.abi_encode();
-                tx.input = calldata.into();
-                tx.chain_id = Some(10143);
-
-                let signature = self
-                    .signer
-                    .sign_transaction_sync(&mut tx)
-                    .expect("Failed to sign transaction");
-                let signed_tx = tx.into_signed(signature);
-
-                let mut buf = Vec::new();
-                signed_tx.rlp_encode(&mut buf);
-                format!("0x{}", hex::encode(buf))
+                SignedTransaction::sign_legacy(&self.signer, tx, batch.recipients)
             })
-            .collect())
+            .collect()
+    }
+
+    fn recipients(&self) -> &[Address] {
+        &self.recipients
     }
 }
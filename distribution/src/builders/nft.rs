@@ -1,7 +1,12 @@
+use std::ops::Range;
+
+use crate::builders::batching::{plan_batches, Endpoints};
 use crate::prelude::*;
 
 sol! {
     contract SoulboundMinter {
+        event Transfer(address indexed from, address indexed to, uint256 indexed tokenId);
+
         function batchMint(address[] calldata recipients, string memory uri) external;
     }
 }
@@ -12,66 +17,78 @@ pub struct SoulboundNFTMintBuilder {
     pub contract_address: Address,
     pub recipients: Vec<Address>,
     pub image_url: String,
+    pub batch: BatchConfig,
+    /// Endpoints gas estimates are spread over, `provider` alone when empty
+    pub estimate_providers: Vec<Box<dyn Provider>>,
+}
+
+impl SoulboundNFTMintBuilder {
+    /// Recipients per call unless configured otherwise
+    pub const DEFAULT_BATCH_SIZE: usize = 500;
+
+    fn batch_mint_call(&self, recipients: Range<usize>) -> SoulboundMinter::batchMintCall {
+        SoulboundMinter::batchMintCall {
+            recipients: self.recipients[recipients].to_vec(),
+            uri: self.image_url.clone(),
+        }
+    }
 }
 
 #[async_trait]
 impl Builder for SoulboundNFTMintBuilder {
-    async fn build_transactions(&self, _start_nonce_override: Option<u64>) -> Result<Vec<String>> {
-        let starting_nonce = self
-            .provider
-            .get_transaction_count(self.signer.address())
-            .await?;
-        let batch_addresses = self.recipients.chunks(500).collect::<Vec<_>>();
+    async fn build_transactions(
+        &self,
+        start_nonce_override: Option<u64>,
+    ) -> Result<Vec<SignedTransaction>> {
+        let starting_nonce = if let Some(start_nonce) = start_nonce_override {
+            start_nonce
+        } else {
+            self.provider
+                .get_transaction_count(self.signer.address())
+                .await?
+        };
 
-        let mut gas_limits = Vec::new();
         info!("Estimating batch gas limits...");
-        for i in 0..batch_addresses.len() {
-            info!("---> Estimating gas for batch {}", i);
-            let batch_mint_call = SoulboundMinter::batchMintCall {
-                recipients: batch_addresses[i].to_vec(),
-                uri: self.image_url.clone(),
-            };
-            let calldata = batch_mint_call.abi_encode();
-            let sim_tx = TransactionRequest::default()
-                .from(self.signer.address())
-                .to(self.contract_address)
-                .input(calldata.into());
-            gas_limits.push(self.provider.estimate_gas(&sim_tx).await? * 105 / 100);
-        }
+        let endpoints = Endpoints::new(self.provider.as_ref(), &self.estimate_providers);
+        let batches = plan_batches(
+            self.provider.as_ref(),
+            &self.batch,
+            Self::DEFAULT_BATCH_SIZE,
+            self.recipients.len(),
+            |recipients| {
+                let sim_tx = TransactionRequest::default()
+                    .from(self.signer.address())
+                    .to(self.contract_address)
+                    .input(self.batch_mint_call(recipients).abi_encode().into());
+                let endpoints = &endpoints;
+                async move { Ok(endpoints.estimate_gas(&sim_tx).await? * 105 / 100) }
+            },
+        )
+        .await?;
         info!("Batch gas limits estimated");
 
-        Ok(batch_addresses
+        batches
             .into_par_iter()
             .enumerate()
-            .map(|(i, recipients)| {
-                let gas_limit = gas_limits[i];
-
-                let mut tx = TxLegacy::default();
-                tx.nonce = starting_nonce + i as u64;
-                tx.gas_price = 52_000_000_000u128;
-                tx.gas_limit = gas_limit;
-                tx.to = TxKind::Call(self.contract_address);
-                tx.value = U256::from(0);
-
-                let batch_mint_call = SoulboundMinter::batchMintCall {
-                    recipients: recipients.to_vec(),
-                    uri: self.image_url.clone(),
+            .map(|(i, batch)| {
+                let tx = TxLegacy {
+                    nonce: starting_nonce + i as u64,
+                    gas_price: 52_000_000_000u128,
+                    gas_limit: batch.gas_limit,
+                    to: TxKind::Call(self.contract_address),
+                    value: U256::from(0),
+                    input: self
+                        .batch_mint_call(batch.recipients.clone())
+                        .abi_encode()
+                        .into(),
+                    chain_id: Some(10143),
                 };
-                let calldata = batch_mint_call.abi_encode();
-                tx.input = calldata.into();
-
-                tx.chain_id = Some(10143);
-
-                let signature = self
-                    .signer
-                    .sign_transaction_sync(&mut tx)
-                    .expect("Failed to sign transaction");
-                let signed_tx = tx.into_signed(signature);
-
-                let mut buf = Vec::new();
-                signed_tx.rlp_encode(&mut buf);
-                format!("0x{}", hex::encode(buf))
+                SignedTransaction::sign_legacy(&self.signer, tx, batch.recipients)
             })
-            .collect())
+            .collect()
+    }
+
+    fn recipients(&self) -> &[Address] {
+        &self.recipients
     }
 }
@@ -0,0 +1,63 @@
+use std::fs::File;
+use std::io::{BufReader, BufWriter};
+
+use crate::prelude::*;
+
+/// Transactions signed ahead of time, written by `sign` and sent as they are by `broadcast`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PresignedBuilder {
+    /// Recipients the transactions' recipient indices refer to, for the journal
+    pub recipients: Vec<Address>,
+    pub transactions: Vec<SignedTransaction>,
+}
+
+impl PresignedBuilder {
+    pub fn write(&self, path: &str) -> Result<()> {
+        let file = File::create(path)?;
+        serde_json::to_writer_pretty(BufWriter::new(file), self)?;
+        Ok(())
+    }
+
+    pub fn read(path: &str) -> Result<Self> {
+        let file = File::open(path)
+            .map_err(|e| anyhow::anyhow!("Failed to open signed transactions {}: {}", path, e))?;
+        Ok(serde_json::from_reader(BufReader::new(file))?)
+    }
+
+    /// Sender of every transaction, which a single file must not mix
+    pub fn sender(&self) -> Result<Option<Address>> {
+        let mut senders = self.transactions.iter().map(|tx| tx.sender);
+        let sender = senders.next();
+        if senders.any(|other| Some(other) != sender) {
+            return Err(anyhow::anyhow!(
+                "Signed transactions come from more than one sender"
+            ));
+        }
+        Ok(sender)
+    }
+}
+
+#[async_trait]
+impl Builder for PresignedBuilder {
+    async fn build_transactions(
+        &self,
+        start_nonce_override: Option<u64>,
+    ) -> Result<Vec<SignedTransaction>> {
+        // Nonces are part of the signatures, so they cannot be moved
+        if let (Some(start_nonce), Some(first)) = (start_nonce_override, self.transactions.first())
+        {
+            if start_nonce != first.nonce {
+                return Err(anyhow::anyhow!(
+                    "Signed transactions start at nonce {}, not {}",
+                    first.nonce,
+                    start_nonce
+                ));
+            }
+        }
+        Ok(self.transactions.clone())
+    }
+
+    fn recipients(&self) -> &[Address] {
+        &self.recipients
+    }
+}
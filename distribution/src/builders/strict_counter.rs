@@ -0,0 +1,99 @@
+use crate::prelude::*;
+use std::sync::Mutex;
+
+sol! {
+    contract StrictCounter {
+        function number() external view returns (uint256);
+        function update(uint256 newNumber) external;
+    }
+}
+
+// Ordering workload: each update only succeeds if every previous one was applied in nonce order
+pub struct StrictCounterBuilder {
+    pub signer: PrivateKeySigner,
+    pub provider: Box<dyn Provider>,
+    pub contract_address: Address,
+    pub num_updates: u64,
+    /// Counter value expected once every built update has been mined
+    pub expected: Mutex<Option<U256>>,
+}
+
+impl StrictCounterBuilder {
+    async fn read_number(&self) -> Result<U256> {
+        let call_tx = TransactionRequest::default()
+            .to(self.contract_address)
+            .input(StrictCounter::numberCall {}.abi_encode().into());
+        let output = self.provider.call(&call_tx).await?;
+        Ok(StrictCounter::numberCall::abi_decode_returns(&output, true)?._0)
+    }
+}
+
+#[async_trait]
+impl Builder for StrictCounterBuilder {
+    async fn build_transactions(
+        &self,
+        start_nonce_override: Option<u64>,
+    ) -> Result<Vec<SignedTransaction>> {
+        let starting_nonce = if let Some(start_nonce) = start_nonce_override {
+            start_nonce
+        } else {
+            self.provider
+                .get_transaction_count(self.signer.address())
+                .await?
+        };
+        let current = self.read_number().await?;
+        info!("Current counter value: {}", current);
+
+        let gas_limit = {
+            let sim_tx = TransactionRequest::default()
+                .from(self.signer.address())
+                .to(self.contract_address)
+                .input(
+                    StrictCounter::updateCall {
+                        newNumber: current + U256::from(1),
+                    }
+                    .abi_encode()
+                    .into(),
+                );
+            self.provider.estimate_gas(&sim_tx).await? * 105 / 100
+        };
+        *self.expected.lock().unwrap() = Some(current + U256::from(self.num_updates));
+
+        (0..self.num_updates)
+            .into_par_iter()
+            .map(|i| {
+                let call = StrictCounter::updateCall {
+                    newNumber: current + U256::from(i + 1),
+                };
+
+                let tx = TxLegacy {
+                    nonce: starting_nonce + i,
+                    gas_price: 52_000_000_000u128,
+                    gas_limit,
+                    to: TxKind::Call(self.contract_address),
+                    value: U256::ZERO,
+                    input: call.abi_encode().into(),
+                    chain_id: Some(10143),
+                };
+                // Counter updates have no recipients
+                SignedTransaction::sign_legacy(&self.signer, tx, 0..0)
+            })
+            .collect()
+    }
+
+    async fn verify(&self) -> Result<()> {
+        let Some(expected) = *self.expected.lock().unwrap() else {
+            return Ok(());
+        };
+        let number = self.read_number().await?;
+        if number != expected {
+            return Err(anyhow::anyhow!(
+                "Counter ordering check failed: expected {}, found {}",
+                expected,
+                number
+            ));
+        }
+        info!("Counter ordering check passed: {}", number);
+        Ok(())
+    }
+}
@@ -3,30 +3,134 @@ use crate::prelude::*;
 sol! {
     contract Swapper {
         function swap(uint256 amountIn, bool aToB) external;
+        function getTokens() external view returns (address, address);
+        function getPair() external view returns (address);
+    }
+
+    contract UniswapV2Pair {
+        function token0() external view returns (address);
+        function getReserves() external view returns (uint112 reserve0, uint112 reserve1, uint32 blockTimestampLast);
     }
 }
+
 pub struct SwapperBuilder {
     pub signer: PrivateKeySigner,
     pub provider: Box<dyn Provider>,
     pub contract_address: Address,
     pub max_swaps: u64,
+    pub amount: SwapAmountConfig,
+    pub direction: SwapDirectionConfig,
+    pub max_slippage_bps: Option<u64>,
+}
+
+impl SwapperBuilder {
+    async fn call<C: SolCall>(&self, to: Address, call: C) -> Result<C::Return> {
+        let call_tx = TransactionRequest::default()
+            .to(to)
+            .input(call.abi_encode().into());
+        let output = self.provider.call(&call_tx).await?;
+        Ok(C::abi_decode_returns(&output, true)?)
+    }
+
+    /// Read the pair reserves as `(reserveA, reserveB)`
+    async fn reserves(&self) -> Result<(U256, U256)> {
+        let token_a = self
+            .call(self.contract_address, Swapper::getTokensCall {})
+            .await?
+            ._0;
+        let pair = self
+            .call(self.contract_address, Swapper::getPairCall {})
+            .await?
+            ._0;
+        let token0 = self.call(pair, UniswapV2Pair::token0Call {}).await?._0;
+        let reserves = self.call(pair, UniswapV2Pair::getReservesCall {}).await?;
+        let (reserve0, reserve1) = (U256::from(reserves.reserve0), U256::from(reserves.reserve1));
+        Ok(if token0 == token_a {
+            (reserve0, reserve1)
+        } else {
+            (reserve1, reserve0)
+        })
+    }
+
+    /// Sample the `(amountIn, aToB)` pair for every swap, in nonce order
+    fn sample_swaps(&self) -> Vec<(U256, bool)> {
+        let mut rng = rand::rng();
+        (0..self.max_swaps)
+            .map(|i| {
+                let amount = match self.amount {
+                    SwapAmountConfig::Fixed { value } => value,
+                    SwapAmountConfig::Uniform { low, high } => rng.random_range(low..=high),
+                    SwapAmountConfig::LogNormal { mu, sigma } => {
+                        // Box-Muller transform for a standard normal sample
+                        let u1: f64 = 1.0 - rng.random::<f64>();
+                        let u2: f64 = rng.random();
+                        let z = (-2.0 * u1.ln()).sqrt() * (2.0 * std::f64::consts::PI * u2).cos();
+                        (mu + sigma * z).exp().max(1.0) as u64
+                    }
+                };
+                let a_to_b = match self.direction {
+                    SwapDirectionConfig::Alternating => i % 2 == 0,
+                    SwapDirectionConfig::Random => rng.random_bool(0.5),
+                    SwapDirectionConfig::Biased { a_to_b_probability } => {
+                        rng.random_bool(a_to_b_probability)
+                    }
+                };
+                (U256::from(amount), a_to_b)
+            })
+            .collect()
+    }
 }
 
 #[async_trait]
 impl Builder for SwapperBuilder {
-    async fn build_transactions(&self, _start_nonce_override: Option<u64>) -> Result<Vec<String>> {
-        let starting_nonce = self
-            .provider
-            .get_transaction_count(self.signer.address())
-            .await?;
+    async fn build_transactions(
+        &self,
+        start_nonce_override: Option<u64>,
+    ) -> Result<Vec<SignedTransaction>> {
+        let starting_nonce = if let Some(start_nonce) = start_nonce_override {
+            start_nonce
+        } else {
+            self.provider
+                .get_transaction_count(self.signer.address())
+                .await?
+        };
+        let mut swaps = self.sample_swaps();
+
+        if let Some(max_slippage_bps) = self.max_slippage_bps {
+            // Clamp amounts so the price impact against current reserves stays within bounds
+            let (reserve_a, reserve_b) = self.reserves().await?;
+            let bps = U256::from(max_slippage_bps.min(9_999));
+            let max_in = |reserve_in: U256| reserve_in * bps / (U256::from(10_000) - bps);
+            let (max_a, max_b) = (max_in(reserve_a), max_in(reserve_b));
+            let mut clamped = 0;
+            for (amount, a_to_b) in swaps.iter_mut() {
+                let max = if *a_to_b { max_a } else { max_b };
+                if *amount > max {
+                    *amount = max;
+                    clamped += 1;
+                }
+            }
+            if clamped > 0 {
+                warn!(
+                    "Clamped {} swaps to stay within {} bps slippage",
+                    clamped, max_slippage_bps
+                );
+            }
+        }
+
         let gas_limit = {
+            let (amount_in, a_to_b) = swaps
+                .iter()
+                .copied()
+                .max_by_key(|(amount, _)| *amount)
+                .unwrap_or((U256::from(100), false));
             let sim_tx = TransactionRequest::default()
                 .from(self.signer.address())
                 .to(self.contract_address)
                 .input(
                     Swapper::swapCall {
-                        amountIn: U256::from(100),
-                        aToB: false,
+                        amountIn: amount_in,
+                        aToB: a_to_b,
                     }
                     .abi_encode()
                     .into(),
@@ -34,34 +138,28 @@ impl Builder for SwapperBuilder {
             self.provider.estimate_gas(&sim_tx).await? * 105 / 100
         };
 
-        Ok((0..self.max_swaps)
+        swaps
             .into_par_iter()
-            .map(|i| {
-                let mut tx = TxLegacy::default();
-                tx.nonce = starting_nonce + i;
-                tx.gas_price = 52_000_000_000u128;
-                tx.gas_limit = gas_limit;
-                tx.to = TxKind::Call(self.contract_address);
-                tx.value = U256::ZERO;
-
+            .enumerate()
+            .map(|(i, (amount_in, a_to_b))| {
                 // Encode swap call
                 let call = Swapper::swapCall {
-                    amountIn: U256::from(100),
-                    aToB: i % 2 == 0,
+                    amountIn: amount_in,
+                    aToB: a_to_b,
                 };
-                tx.input = call.abi_encode().into();
-
-                tx.chain_id = Some(10143);
 
-                let signature = self
-                    .signer
-                    .sign_transaction_sync(&mut tx)
-                    .expect("Failed to sign transaction");
-                let signed_tx = tx.into_signed(signature);
-                let mut buf = Vec::new();
-                signed_tx.rlp_encode(&mut buf);
-                format!("0x{}", hex::encode(buf))
+                let tx = TxLegacy {
+                    nonce: starting_nonce + i as u64,
+                    gas_price: 52_000_000_000u128,
+                    gas_limit,
+                    to: TxKind::Call(self.contract_address),
+                    value: U256::ZERO,
+                    input: call.abi_encode().into(),
+                    chain_id: Some(10143),
+                };
+                // Swaps have no recipients
+                SignedTransaction::sign_legacy(&self.signer, tx, 0..0)
             })
-            .collect::<Vec<_>>())
+            .collect()
     }
 }
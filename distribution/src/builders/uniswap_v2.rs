@@ -0,0 +1,267 @@
+use std::sync::atomic::{AtomicBool, Ordering};
+
+use crate::prelude::*;
+use alloy::primitives::utils::parse_units;
+use alloy::rpc::types::{BlockNumberOrTag, BlockTransactionsKind};
+
+use super::swapper::UniswapV2Pair;
+
+sol! {
+    contract UniswapV2Router02 {
+        function swapExactTokensForTokens(uint256 amountIn, uint256 amountOutMin, address[] calldata path, address to, uint256 deadline) external returns (uint256[] memory amounts);
+        function swapExactETHForTokens(uint256 amountOutMin, address[] calldata path, address to, uint256 deadline) external payable returns (uint256[] memory amounts);
+        function factory() external pure returns (address);
+    }
+
+    contract UniswapV2Factory {
+        function getPair(address tokenA, address tokenB) external view returns (address pair);
+    }
+
+    contract ERC20 {
+        function decimals() external view returns (uint8);
+        function allowance(address owner, address spender) external view returns (uint256);
+        function approve(address spender, uint256 amount) external returns (bool);
+    }
+}
+
+// Swaps through a deployed Uniswap V2 router along a fixed token path
+pub struct UniswapV2Builder {
+    pub signer: PrivateKeySigner,
+    pub provider: Box<dyn Provider>,
+    pub router_address: Address,
+    pub path: Vec<Address>,
+    /// Amount of the first path token per swap, in whole tokens
+    pub amount_in: f64,
+    /// Spend the native token through `swapExactETHForTokens` (`path[0]` must be WETH)
+    pub native_in: bool,
+    pub max_swaps: u64,
+    pub max_slippage_bps: Option<u64>,
+    pub deadline_secs: u64,
+    /// Set once an approval has been built, so later rounds don't repeat it before it is mined
+    pub approval_sent: AtomicBool,
+}
+
+impl UniswapV2Builder {
+    async fn call<C: SolCall>(&self, to: Address, call: C) -> Result<C::Return> {
+        let call_tx = TransactionRequest::default()
+            .from(self.signer.address())
+            .to(to)
+            .input(call.abi_encode().into());
+        let output = self.provider.call(&call_tx).await?;
+        Ok(C::abi_decode_returns(&output, true)?)
+    }
+
+    /// Reserves of every pair along the path, as `(reserveIn, reserveOut)` per hop
+    async fn reserves(&self) -> Result<Vec<(U256, U256)>> {
+        let factory = self
+            .call(self.router_address, UniswapV2Router02::factoryCall {})
+            .await?
+            ._0;
+        let mut reserves = Vec::new();
+        for hop in self.path.windows(2) {
+            let pair = self
+                .call(
+                    factory,
+                    UniswapV2Factory::getPairCall {
+                        tokenA: hop[0],
+                        tokenB: hop[1],
+                    },
+                )
+                .await?
+                .pair;
+            let token0 = self.call(pair, UniswapV2Pair::token0Call {}).await?._0;
+            let pair_reserves = self.call(pair, UniswapV2Pair::getReservesCall {}).await?;
+            let (reserve0, reserve1) = (
+                U256::from(pair_reserves.reserve0),
+                U256::from(pair_reserves.reserve1),
+            );
+            reserves.push(if token0 == hop[0] {
+                (reserve0, reserve1)
+            } else {
+                (reserve1, reserve0)
+            });
+        }
+        Ok(reserves)
+    }
+
+    /// Encode a single swap along the configured path
+    fn encode_swap(&self, amount_in: U256, amount_out_min: U256, deadline: U256) -> Vec<u8> {
+        if self.native_in {
+            UniswapV2Router02::swapExactETHForTokensCall {
+                amountOutMin: amount_out_min,
+                path: self.path.clone(),
+                to: self.signer.address(),
+                deadline,
+            }
+            .abi_encode()
+        } else {
+            UniswapV2Router02::swapExactTokensForTokensCall {
+                amountIn: amount_in,
+                amountOutMin: amount_out_min,
+                path: self.path.clone(),
+                to: self.signer.address(),
+                deadline,
+            }
+            .abi_encode()
+        }
+    }
+}
+
+#[async_trait]
+impl Builder for UniswapV2Builder {
+    async fn build_transactions(
+        &self,
+        start_nonce_override: Option<u64>,
+    ) -> Result<Vec<SignedTransaction>> {
+        if self.path.len() < 2 {
+            return Err(anyhow::anyhow!(
+                "Swap path must contain at least two tokens"
+            ));
+        }
+        let token_in = self.path[0];
+        let starting_nonce = if let Some(start_nonce) = start_nonce_override {
+            start_nonce
+        } else {
+            self.provider
+                .get_transaction_count(self.signer.address())
+                .await?
+        };
+
+        let decimals = if self.native_in {
+            18
+        } else {
+            self.call(token_in, ERC20::decimalsCall {}).await?._0
+        };
+        let amount_in: U256 = parse_units(&self.amount_in.to_string(), decimals)?.into();
+
+        // Every swap moves the price for the next, so each is quoted after the ones before it
+        let amount_out_mins = match self.max_slippage_bps {
+            Some(max_slippage_bps) => {
+                let mut reserves = self.reserves().await?;
+                let keep_bps = U256::from(10_000 - max_slippage_bps.min(10_000));
+                (0..self.max_swaps)
+                    .map(|_| swap_along(&mut reserves, amount_in) * keep_bps / U256::from(10_000))
+                    .collect()
+            }
+            None => vec![U256::ZERO; self.max_swaps as usize],
+        };
+
+        // Deadlines are relative to chain time, which may differ from wall time on a fork
+        let deadline = {
+            let block = self
+                .provider
+                .get_block_by_number(BlockNumberOrTag::Latest, BlockTransactionsKind::Hashes)
+                .await?
+                .ok_or_else(|| anyhow::anyhow!("Latest block not found"))?;
+            U256::from(block.header.timestamp + self.deadline_secs)
+        };
+
+        // Approve the router once if the allowance does not cover this round of swaps
+        let mut approvals = Vec::new();
+        let approval_pending = !self.native_in && self.approval_sent.load(Ordering::SeqCst);
+        if !self.native_in {
+            let allowance = self
+                .call(
+                    token_in,
+                    ERC20::allowanceCall {
+                        owner: self.signer.address(),
+                        spender: self.router_address,
+                    },
+                )
+                .await?
+                ._0;
+            let covered = allowance >= amount_in.saturating_mul(U256::from(self.max_swaps));
+            if !covered && !approval_pending {
+                info!(
+                    "Approving router {} to spend {}",
+                    self.router_address, token_in
+                );
+                let call = ERC20::approveCall {
+                    spender: self.router_address,
+                    amount: U256::MAX,
+                };
+                let sim_tx = TransactionRequest::default()
+                    .from(self.signer.address())
+                    .to(token_in)
+                    .input(call.abi_encode().into());
+                let gas_limit = self.provider.estimate_gas(&sim_tx).await? * 105 / 100;
+                let tx = TxLegacy {
+                    nonce: starting_nonce,
+                    gas_price: 52_000_000_000u128,
+                    gas_limit,
+                    to: TxKind::Call(token_in),
+                    value: U256::ZERO,
+                    input: call.abi_encode().into(),
+                    chain_id: Some(10143),
+                };
+                approvals.push(SignedTransaction::sign_legacy(&self.signer, tx, 0..0)?);
+                self.approval_sent.store(true, Ordering::SeqCst);
+            } else if covered {
+                self.approval_sent.store(false, Ordering::SeqCst);
+            }
+        }
+        let swap_nonce = starting_nonce + approvals.len() as u64;
+        let value = if self.native_in {
+            amount_in
+        } else {
+            U256::ZERO
+        };
+
+        // Swaps can't be simulated before the approval is mined, so fall back to a fixed limit
+        let gas_limit = if !self.approval_sent.load(Ordering::SeqCst) {
+            let sim_tx = TransactionRequest::default()
+                .from(self.signer.address())
+                .to(self.router_address)
+                .value(value)
+                .input(
+                    self.encode_swap(
+                        amount_in,
+                        amount_out_mins.first().copied().unwrap_or_default(),
+                        deadline,
+                    )
+                    .into(),
+                );
+            self.provider.estimate_gas(&sim_tx).await? * 105 / 100
+        } else {
+            100_000 + 60_000 * self.path.len() as u64
+        };
+
+        let swaps = (0..self.max_swaps)
+            .into_par_iter()
+            .map(|i| {
+                let tx = TxLegacy {
+                    nonce: swap_nonce + i,
+                    gas_price: 52_000_000_000u128,
+                    gas_limit,
+                    to: TxKind::Call(self.router_address),
+                    value,
+                    input: self
+                        .encode_swap(amount_in, amount_out_mins[i as usize], deadline)
+                        .into(),
+                    chain_id: Some(10143),
+                };
+                // Swaps have no recipients
+                SignedTransaction::sign_legacy(&self.signer, tx, 0..0)
+            })
+            .collect::<Result<Vec<_>>>()?;
+
+        approvals.extend(swaps);
+        Ok(approvals)
+    }
+}
+
+/// Output of swapping `amount_in` along the path, moving `reserves` the way the swap would
+fn swap_along(reserves: &mut [(U256, U256)], amount_in: U256) -> U256 {
+    let mut amount = amount_in;
+    for (reserve_in, reserve_out) in reserves {
+        // UniswapV2Library.getAmountOut, after the 0.3% fee
+        let amount_with_fee = amount * U256::from(997);
+        let amount_out = (amount_with_fee * *reserve_out)
+            .checked_div(*reserve_in * U256::from(1000) + amount_with_fee)
+            .unwrap_or_default();
+        *reserve_in += amount;
+        *reserve_out -= amount_out;
+        amount = amount_out;
+    }
+    amount
+}
@@ -1,3 +1,8 @@
+use std::ops::Range;
+
+use alloy::primitives::utils::format_ether;
+
+use crate::builders::native::float_to_u256;
 use crate::prelude::*;
 use itertools::Itertools;
 use tokio::task::JoinSet;
@@ -14,8 +19,13 @@ impl Distributor for ContinuousDistributor {
         builder: Box<dyn Builder + Send + Sync>,
         options: DistributionOptions,
     ) -> Result<()> {
-        // Use the first RPC URL to create a provider for nonce management
-        let provider = ProviderBuilder::new().on_http(rpc_urls[0].parse().unwrap());
+        let http_client = Client::new();
+        let pool = Arc::new(RpcPool::new(&rpc_urls, options.rpc_pool.clone()));
+        pool.check_health(&http_client).await;
+        let _health_checks = pool.spawn_health_checks(http_client.clone());
+
+        // Use a healthy HTTP RPC URL to create a provider for nonce management
+        let provider = ProviderBuilder::new().on_http(pool.primary_http()?.parse()?);
         let mut base_nonce = provider
             .get_transaction_count(self.signer.address())
             .await
@@ -26,37 +36,148 @@ impl Distributor for ContinuousDistributor {
             let requests_per_sec = options.target_tps as f64 / options.rpc_batch_size as f64;
             (1000.0 / requests_per_sec) as u64
         };
-        let http_client = Client::new();
 
-        loop {
+        let min_signer_balance = options.stop.min_signer_balance.map(float_to_u256);
+        let mut report = RunReport::default();
+        let mut last_accepted: Option<SignedTransaction> = None;
+        let mut failure = None;
+
+        while report.stop_reason.is_none() {
+            if options.shutdown.is_triggered() {
+                report.stop_reason = Some("received a shutdown signal".to_string());
+                break;
+            }
+
             info!("Signing transactions...");
-            let all_txs = builder.build_transactions(Some(base_nonce)).await?;
+            let mut all_txs = builder.build_transactions(Some(base_nonce)).await?;
+            if all_txs.is_empty() {
+                report.stop_reason = Some("nothing left to send".to_string());
+                break;
+            }
+            // A mixed builder signs with several keys, so every signer of the round is checked
+            if let Some(min_balance) = min_signer_balance {
+                let low_balance = low_balance(&provider, &all_txs, min_balance).await?;
+                if low_balance.is_some() {
+                    report.stop_reason = low_balance;
+                    break;
+                }
+            }
             base_nonce += all_txs.len() as u64;
+            // Sending stops partway through a round once a budget runs out
+            let (within_budget, stop_reason) = report.within_budget(&options.stop, &all_txs);
+            all_txs.truncate(within_budget);
+            report.stop_reason = stop_reason;
 
             info!("Sending batches across {} RPC endpoints...", rpc_urls.len());
             let mut delay_ms = 0u64;
             let mut join_set = JoinSet::new();
-            for (i, chunk) in all_txs
+            for chunk in all_txs
                 .into_iter()
                 .chunks(options.rpc_batch_size)
                 .into_iter()
-                .enumerate()
             {
+                // Batches already scheduled still go out, but no new ones after a shutdown
+                if options.shutdown.is_triggered() {
+                    break;
+                }
+                // Each batch waits for its delay, so one due after the duration limit is not sent
+                let deadline = report.past_deadline(&options.stop, Duration::from_millis(delay_ms));
+                if deadline.is_some() {
+                    report.stop_reason = deadline;
+                    break;
+                }
                 let txs = chunk.collect::<Vec<_>>();
+                report.record_sent(&txs);
                 let client = http_client.clone();
-                let url = &rpc_urls[i % rpc_urls.len()];
                 join_set.spawn(send_transactions_with_delay(
                     txs,
                     client,
-                    url.clone(),
+                    pool.clone(),
                     delay_ms,
                 ));
                 delay_ms += delay_increment_ms;
             }
 
             info!("Awaiting batches to be dispatched");
-            let _ = join_set.join_all().await;
+            let (accepted, failed) = accepted_batches(join_set.join_all().await);
+            if let Some(journal) = &options.journal {
+                journal.record(&accepted, builder.recipients())?;
+            }
+            report.record_accepted(&accepted);
+            if let Some(last) = accepted.into_iter().max_by_key(|tx| tx.nonce) {
+                last_accepted = Some(last);
+            }
+            if let Some(e) = failed {
+                report.stop_reason = Some(format!("a batch could not be sent: {}", e));
+                failure = Some(e);
+                break;
+            }
             info!("All batches dispatched successfully");
         }
+
+        info!(
+            "Stopping: {}",
+            report.stop_reason.as_deref().unwrap_or_default()
+        );
+        let mut confirmation = Ok(());
+        if options.wait_for_confirmation && !options.shutdown.is_triggered() && failure.is_none()
+        {
+            confirmation = match pool.primary() {
+                Ok(rpc_url) => {
+                    confirm_and_verify(
+                        rpc_url,
+                        last_accepted.map(|tx| tx.hash),
+                        builder.as_ref(),
+                        options.confirmation_timeout,
+                    )
+                    .await
+                }
+                Err(e) => Err(e),
+            };
+        }
+        if let Some(journal) = &options.journal {
+            journal.sync()?;
+        }
+        let signers = if report.last_nonces.is_empty() {
+            vec![self.signer.address()]
+        } else {
+            report.last_nonces.keys().copied().collect()
+        };
+        report.record_confirmed_nonces(&pool, signers).await;
+        report.log();
+        match failure {
+            Some(e) => Err(e),
+            None => confirmation,
+        }
+    }
+
+    /// Each round is sent in RPC batches of `rpc_batch_size`
+    fn dispatch_groups(&self, len: usize, options: &DistributionOptions) -> Vec<Range<usize>> {
+        let size = options.rpc_batch_size.max(1);
+        (0..len)
+            .step_by(size)
+            .map(|start| start..(start + size).min(len))
+            .collect()
+    }
+}
+
+/// Why one of the signers of `txs` holds less than `min_balance`, if one does
+async fn low_balance(
+    provider: &dyn Provider,
+    txs: &[SignedTransaction],
+    min_balance: U256,
+) -> Result<Option<String>> {
+    let signers = txs.iter().map(|tx| tx.sender).unique().collect::<Vec<_>>();
+    for signer in signers {
+        let balance = provider.get_balance(signer).await?;
+        if balance < min_balance {
+            return Ok(Some(format!(
+                "signer {} balance {} is below {}",
+                signer,
+                format_ether(balance),
+                format_ether(min_balance)
+            )));
+        }
     }
+    Ok(None)
 }
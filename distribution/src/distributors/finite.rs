@@ -1,3 +1,5 @@
+use std::ops::Range;
+
 use crate::prelude::*;
 use tokio::task::JoinSet;
 
@@ -12,6 +14,26 @@ pub struct FiniteGroupDistributor {
     pub signer: PrivateKeySigner,
 }
 
+/// Transactions dispatched together after the first one, each in its own request
+const GROUP_SIZE: usize = 10;
+
+/// Ranges of `len` transactions that `send_group` dispatches together: the first transaction
+/// alone, then `GROUP_SIZE` at a time
+pub fn groups(len: usize) -> Vec<Range<usize>> {
+    (0..len)
+        .filter(|&i| ends_group(i, len))
+        .scan(0, |start, end| {
+            let group = *start..end + 1;
+            *start = end + 1;
+            Some(group)
+        })
+        .collect()
+}
+
+fn ends_group(i: usize, len: usize) -> bool {
+    i.is_multiple_of(GROUP_SIZE) || i == len - 1
+}
+
 #[async_trait]
 impl Distributor for FiniteGroupDistributor {
     async fn send_transactions(
@@ -20,42 +42,105 @@ impl Distributor for FiniteGroupDistributor {
         builder: Box<dyn Builder + Send + Sync>,
         options: DistributionOptions,
     ) -> Result<()> {
-        let delay_increment_ms = {
-            let requests_per_sec = options.target_tps as f64 / options.rpc_batch_size as f64;
-            (1000.0 / requests_per_sec) as u64
-        };
-        let http_client = Client::new();
-
-        info!("Signing transactions...");
-        let all_txs = builder.build_transactions(None).await?;
-
-        info!("Sending {} transactions across {} RPC endpoints...", all_txs.len(), rpc_urls.len());
-        let mut delay_ms = 0u64;
-        let mut join_set = JoinSet::new();
-        let mut group_number = 1;
-
-        for (i, tx) in all_txs.iter().enumerate() {
-            let client = http_client.clone();
-            let url = &rpc_urls[i % rpc_urls.len()];
-            join_set.spawn(send_transactions_with_delay(
-                vec![tx.clone()],
-                client,
-                url.clone(),
-                delay_ms,
-            ));
-            delay_ms += delay_increment_ms;
-
-            if i % 10 == 0 || i == all_txs.len() - 1 {
-                info!("Waiting for group {} to be dispatched", group_number);
-                let _ = join_set.join_all().await;
-                info!("Group {} dispatched successfully", group_number);
-                delay_ms = 0;
-                join_set = JoinSet::new();
-                group_number += 1;
+        send_group(rpc_urls, builder, options, self.signer.address()).await
+    }
+
+    fn dispatch_groups(&self, len: usize, _options: &DistributionOptions) -> Vec<Range<usize>> {
+        groups(len)
+    }
+}
+
+/// Send everything `builder` builds once, in groups, reporting the confirmed nonce of `sender`
+///
+/// Needs no key of its own, so transactions signed ahead of time can be broadcast with it.
+pub async fn send_group(
+    rpc_urls: Vec<String>,
+    builder: Box<dyn Builder + Send + Sync>,
+    options: DistributionOptions,
+    sender: Address,
+) -> Result<()> {
+    let delay_increment_ms = {
+        let requests_per_sec = options.target_tps as f64 / options.rpc_batch_size as f64;
+        (1000.0 / requests_per_sec) as u64
+    };
+    let http_client = Client::new();
+    let pool = Arc::new(RpcPool::new(&rpc_urls, options.rpc_pool.clone()));
+    pool.check_health(&http_client).await;
+    let _health_checks = pool.spawn_health_checks(http_client.clone());
+
+    info!("Signing transactions...");
+    let all_txs = builder.build_transactions(None).await?;
+
+    info!("Sending {} transactions across {} RPC endpoints...", all_txs.len(), rpc_urls.len());
+    let mut delay_ms = 0u64;
+    let mut join_set = JoinSet::new();
+    let mut group_number = 1;
+    let mut report = RunReport::default();
+    let mut confirmation = Ok(());
+    let mut failure = None;
+
+    for (i, tx) in all_txs.iter().enumerate() {
+        if options.shutdown.is_triggered() {
+            report.stop_reason = Some("received a shutdown signal".to_string());
+            break;
+        }
+        report.record_sent(std::slice::from_ref(tx));
+        let client = http_client.clone();
+        join_set.spawn(send_transactions_with_delay(
+            vec![tx.clone()],
+            client,
+            pool.clone(),
+            delay_ms,
+        ));
+        delay_ms += delay_increment_ms;
+
+        // A shutdown drains the group right away instead of scheduling more
+        if ends_group(i, all_txs.len()) || options.shutdown.is_triggered() {
+            info!("Waiting for group {} to be dispatched", group_number);
+            let (accepted, failed) = accepted_batches(join_set.join_all().await);
+            if let Some(journal) = &options.journal {
+                journal.record(&accepted, builder.recipients())?;
+            }
+            report.record_accepted(&accepted);
+            if let Some(e) = failed {
+                report.stop_reason = Some(format!("a batch could not be sent: {}", e));
+                failure = Some(e);
+                break;
             }
+            info!("Group {} dispatched successfully", group_number);
+            delay_ms = 0;
+            join_set = JoinSet::new();
+            group_number += 1;
         }
+    }
 
+    if let Some(reason) = &report.stop_reason {
+        warn!("Stopped early: {}, skipping confirmation", reason);
+    } else {
         info!("All groups dispatched successfully");
-        Ok(())
+
+        if options.wait_for_confirmation {
+            confirmation = match pool.primary() {
+                Ok(rpc_url) => {
+                    confirm_and_verify(
+                        rpc_url,
+                        all_txs.last().map(|tx| tx.hash),
+                        builder.as_ref(),
+                        options.confirmation_timeout,
+                    )
+                    .await
+                }
+                Err(e) => Err(e),
+            };
+        }
+    }
+    if let Some(journal) = &options.journal {
+        journal.sync()?;
+    }
+    report.record_confirmed_nonces(&pool, [sender]).await;
+    report.log();
+    match failure {
+        Some(e) => Err(e),
+        None => confirmation,
     }
 }
@@ -0,0 +1,129 @@
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::fmt;
+
+use futures::{stream, StreamExt, TryStreamExt};
+
+use crate::addresses::{read_recipients, Recipient};
+use crate::builders::native::float_to_u256;
+use crate::prelude::*;
+
+/// Why a recipient was filtered out
+#[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum FilterReason {
+    /// Listed in this exclusion file
+    Excluded(String),
+    HasCode,
+    BelowMinBalance,
+    AboveMaxBalance,
+}
+
+impl fmt::Display for FilterReason {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            FilterReason::Excluded(file) => write!(f, "listed in {}", file),
+            FilterReason::HasCode => write!(f, "has code"),
+            FilterReason::BelowMinBalance => write!(f, "balance below minimum"),
+            FilterReason::AboveMaxBalance => write!(f, "balance above maximum"),
+        }
+    }
+}
+
+/// Addresses listed in the exclude files of `config`, with the first file listing each
+pub fn excluded(
+    config: &FilterConfig,
+    addresses_config: &AddressesConfig,
+) -> Result<HashMap<Address, FilterReason>> {
+    let mut excluded = HashMap::new();
+    for exclude_file in &config.exclude_files {
+        for recipient in read_recipients(exclude_file, addresses_config)? {
+            excluded
+                .entry(recipient.address)
+                .or_insert_with(|| FilterReason::Excluded(exclude_file.clone()));
+        }
+    }
+    Ok(excluded)
+}
+
+/// Recipients to drop according to `config`, with the first reason that applies to each
+pub async fn filter_recipients(
+    provider: &dyn Provider,
+    addresses: &[Address],
+    config: &FilterConfig,
+    addresses_config: &AddressesConfig,
+) -> Result<HashMap<Address, FilterReason>> {
+    let excluded = excluded(config, addresses_config)?;
+    let mut filtered = addresses
+        .iter()
+        .filter_map(|address| Some((*address, excluded.get(address)?.clone())))
+        .collect::<HashMap<_, _>>();
+
+    let check_balance = config.min_balance.is_some() || config.max_balance.is_some();
+    if !config.skip_contracts && !check_balance {
+        return Ok(filtered);
+    }
+
+    let min_balance = config.min_balance.map(float_to_u256);
+    let max_balance = config.max_balance.map(float_to_u256);
+    let remaining = addresses
+        .iter()
+        .filter(|address| !filtered.contains_key(*address))
+        .copied()
+        .collect::<Vec<_>>();
+    info!("Checking {} recipients on chain", remaining.len());
+    let reasons = stream::iter(remaining)
+        .map(|address| async move {
+            if config.skip_contracts && !provider.get_code_at(address).await?.is_empty() {
+                return Ok::<_, Error>((address, Some(FilterReason::HasCode)));
+            }
+            if !check_balance {
+                return Ok((address, None));
+            }
+            let balance = provider.get_balance(address).await?;
+            let reason = match (min_balance, max_balance) {
+                (Some(min), _) if balance < min => Some(FilterReason::BelowMinBalance),
+                (_, Some(max)) if balance > max => Some(FilterReason::AboveMaxBalance),
+                _ => None,
+            };
+            Ok((address, reason))
+        })
+        .buffered(16)
+        .try_collect::<Vec<_>>()
+        .await?;
+    filtered.extend(
+        reasons
+            .into_iter()
+            .filter_map(|(address, reason)| Some((address, reason?))),
+    );
+    Ok(filtered)
+}
+
+/// Recipients that pass `config`, in order, logging how many each reason dropped
+///
+/// Recipients in `keep` are kept without being checked, so that amounts allocated over the result
+/// still count those already paid by an earlier run.
+pub async fn retain_eligible(
+    provider: &dyn Provider,
+    recipients: Vec<Recipient>,
+    keep: &HashSet<Address>,
+    config: &FilterConfig,
+    addresses_config: &AddressesConfig,
+) -> Result<Vec<Recipient>> {
+    let addresses = recipients
+        .iter()
+        .map(|recipient| recipient.address)
+        .filter(|address| !keep.contains(address))
+        .collect::<Vec<_>>();
+    let filtered = filter_recipients(provider, &addresses, config, addresses_config).await?;
+    let mut summary = BTreeMap::new();
+    for (address, reason) in &filtered {
+        debug!("Filtering out {}: {}", address, reason);
+        *summary.entry(reason).or_insert(0) += 1;
+    }
+    for (reason, count) in summary {
+        info!("Filtered out {} recipients: {}", count, reason);
+    }
+    Ok(recipients
+        .into_iter()
+        .filter(|recipient| !filtered.contains_key(&recipient.address))
+        .collect())
+}
@@ -0,0 +1,156 @@
+use std::collections::HashSet;
+
+use alloy::consensus::Transaction;
+use alloy::rpc::types::{BlockNumberOrTag, BlockTransactionsKind};
+use futures::{stream, StreamExt, TryStreamExt};
+
+use crate::builders::merkle::MerkleDistributor;
+use crate::builders::native::BatchSender;
+use crate::builders::nft::SoulboundMinter;
+use crate::journal::{selector, Journal};
+use crate::prelude::*;
+
+/// The kind of transaction through which a distribution pays its recipients
+///
+/// Payments are only matched within one scope, so recipients of a native transfer are not
+/// skipped by a later NFT mint to the same addresses.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PaymentScope {
+    /// Plain native transfers straight to each recipient
+    Transfer,
+    /// Calls of `selector` on `contract`, such as `batchSend` on a batch sender
+    Call {
+        contract: Address,
+        selector: FixedBytes<4>,
+    },
+}
+
+impl PaymentScope {
+    /// Whether a transaction to `to` calling `selector` pays recipients in this scope
+    pub fn matches(&self, to: Option<Address>, selector: Option<FixedBytes<4>>) -> bool {
+        match *self {
+            Self::Transfer => to.is_some() && selector.is_none(),
+            Self::Call {
+                contract,
+                selector: expected,
+            } => to == Some(contract) && selector == Some(expected),
+        }
+    }
+}
+
+/// Recipients already paid by any of `senders` according to a journal file
+///
+/// The journal only records that an endpoint accepted a transaction, so an entry counts once
+/// its receipt shows it succeeded. Dropped, replaced and reverted transactions leave their
+/// recipients unpaid.
+pub async fn paid_from_journal(
+    provider: &dyn Provider,
+    path: &str,
+    senders: &[Address],
+    scope: PaymentScope,
+) -> Result<HashSet<Address>> {
+    if !Path::new(path).exists() {
+        return Ok(HashSet::new());
+    }
+    let entries = Journal::read(path)?
+        .into_iter()
+        .filter(|entry| senders.contains(&entry.sender) && scope.matches(entry.to, entry.selector))
+        .collect::<Vec<_>>();
+    info!(
+        "Checking receipts of {} journaled transactions",
+        entries.len()
+    );
+
+    let receipts = stream::iter(entries.iter().map(|entry| entry.hash))
+        .map(|hash| async move { provider.get_transaction_receipt(hash).await })
+        .buffered(16)
+        .try_collect::<Vec<_>>()
+        .await?;
+
+    let mut paid = HashSet::new();
+    let mut unconfirmed = 0;
+    for (entry, receipt) in entries.into_iter().zip(receipts) {
+        if receipt.is_some_and(|receipt| receipt.status()) {
+            paid.extend(entry.recipients);
+        } else {
+            unconfirmed += 1;
+        }
+    }
+    if unconfirmed > 0 {
+        warn!(
+            "{} journaled transactions were not mined successfully, their recipients count as unpaid",
+            unconfirmed
+        );
+    }
+    Ok(paid)
+}
+
+/// Recipients paid by any of `senders` through successful transactions in `scope` mined between
+/// `from_block` and `to_block` (inclusive, latest when unset)
+pub async fn paid_on_chain(
+    provider: &dyn Provider,
+    senders: &[Address],
+    scope: PaymentScope,
+    from_block: u64,
+    to_block: Option<u64>,
+) -> Result<HashSet<Address>> {
+    let to_block = match to_block {
+        Some(to_block) => to_block,
+        None => provider.get_block_number().await?,
+    };
+    info!(
+        "Scanning blocks {} to {} for payments from {:?}",
+        from_block, to_block, senders
+    );
+
+    let blocks = stream::iter(from_block..=to_block)
+        .map(|number| async move {
+            provider
+                .get_block_by_number(
+                    BlockNumberOrTag::Number(number),
+                    BlockTransactionsKind::Full,
+                )
+                .await
+        })
+        .buffered(16)
+        .try_collect::<Vec<_>>()
+        .await?;
+
+    let mut paid = HashSet::new();
+    for block in blocks.into_iter().flatten() {
+        for tx in block.transactions.txns() {
+            if !senders.contains(&tx.from) || !scope.matches(tx.to(), selector(tx.input())) {
+                continue;
+            }
+            let recipients = match scope {
+                PaymentScope::Transfer if !tx.value().is_zero() => tx.to().into_iter().collect(),
+                PaymentScope::Transfer => continue,
+                PaymentScope::Call { .. } => match call_recipients(tx.input()) {
+                    Some(recipients) => recipients,
+                    None => continue,
+                },
+            };
+            let receipt = provider
+                .get_transaction_receipt(*tx.inner.tx_hash())
+                .await?;
+            if receipt.is_some_and(|receipt| receipt.status()) {
+                paid.extend(recipients);
+            }
+        }
+    }
+    Ok(paid)
+}
+
+/// Recipients of a `batchSend`, `batchMint` or `claim` call
+fn call_recipients(input: &[u8]) -> Option<Vec<Address>> {
+    if let Ok(call) = BatchSender::batchSendCall::abi_decode(input, true) {
+        return Some(call.recipients);
+    }
+    if let Ok(call) = SoulboundMinter::batchMintCall::abi_decode(input, true) {
+        return Some(call.recipients);
+    }
+    if let Ok(call) = MerkleDistributor::claimCall::abi_decode(input, true) {
+        return Some(vec![call.account]);
+    }
+    None
+}
@@ -0,0 +1,194 @@
+use std::collections::{BTreeMap, HashSet};
+use std::fs::{File, OpenOptions};
+use std::io::{BufReader, Write};
+use std::sync::Mutex;
+
+use alloy::consensus::{Transaction, TxEnvelope};
+use alloy::rlp::Decodable;
+
+use crate::prelude::*;
+
+/// A transaction accepted by an RPC endpoint, as recorded in the journal
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct JournalEntry {
+    pub hash: TxHash,
+    pub nonce: u64,
+    pub sender: Address,
+    pub gas_limit: u64,
+    pub max_fee: u128,
+    pub value: U256,
+    /// Recipients covered by the transaction
+    pub recipients: Vec<Address>,
+    /// Contract called, or the recipient of a plain transfer
+    #[serde(default)]
+    pub to: Option<Address>,
+    /// Function selector of the call, absent for plain transfers
+    #[serde(default)]
+    pub selector: Option<FixedBytes<4>>,
+}
+
+/// Recipients a run kept after its filters, paid ones included, recorded before its entries
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct EligibleRecord {
+    eligible: Vec<Address>,
+}
+
+/// A line of the journal
+enum JournalRecord {
+    Eligible(EligibleRecord),
+    Entry(JournalEntry),
+}
+
+/// Append-only JSONL record of every transaction accepted during a run
+#[derive(Debug)]
+pub struct Journal {
+    file: Mutex<File>,
+}
+
+impl Journal {
+    /// Open the journal for appending, creating it if needed
+    pub fn open(path: &str) -> Result<Self> {
+        let file = OpenOptions::new().create(true).append(true).open(path)?;
+        Ok(Self {
+            file: Mutex::new(file),
+        })
+    }
+
+    /// Record accepted transactions, resolving their recipient indices against `recipients`
+    pub fn record(&self, txs: &[SignedTransaction], recipients: &[Address]) -> Result<()> {
+        let mut buf = Vec::new();
+        for tx in txs {
+            let envelope = TxEnvelope::decode(&mut tx.raw.as_ref())?;
+            let entry = JournalEntry {
+                hash: tx.hash,
+                nonce: tx.nonce,
+                sender: tx.sender,
+                gas_limit: tx.gas_limit,
+                max_fee: tx.max_fee,
+                value: tx.value,
+                recipients: recipients
+                    .get(tx.recipients.clone())
+                    .unwrap_or_default()
+                    .to_vec(),
+                to: envelope.to(),
+                selector: selector(envelope.input()),
+            };
+            serde_json::to_writer(&mut buf, &entry)?;
+            buf.push(b'\n');
+        }
+        let mut file = self.file.lock().unwrap();
+        file.write_all(&buf)?;
+        file.flush()?;
+        Ok(())
+    }
+
+    /// Record the recipients a run kept after its filters, which verification expects to be paid
+    pub fn record_eligible(&self, recipients: &[Address]) -> Result<()> {
+        let mut buf = serde_json::to_vec(&EligibleRecord {
+            eligible: recipients.to_vec(),
+        })?;
+        buf.push(b'\n');
+        let mut file = self.file.lock().unwrap();
+        file.write_all(&buf)?;
+        file.flush()?;
+        Ok(())
+    }
+
+    /// Make sure everything recorded so far is on disk
+    pub fn sync(&self) -> Result<()> {
+        self.file.lock().unwrap().sync_data()?;
+        Ok(())
+    }
+
+    /// Read every entry from a journal file
+    pub fn read(path: &str) -> Result<Vec<JournalEntry>> {
+        Ok(Self::read_records(path)?
+            .into_iter()
+            .filter_map(|record| match record {
+                JournalRecord::Entry(entry) => Some(entry),
+                JournalRecord::Eligible(_) => None,
+            })
+            .collect())
+    }
+
+    /// Recipients the runs of a journal file kept after their filters, if any run recorded them
+    pub fn read_eligible(path: &str) -> Result<Option<HashSet<Address>>> {
+        let mut eligible: Option<HashSet<Address>> = None;
+        for record in Self::read_records(path)? {
+            if let JournalRecord::Eligible(record) = record {
+                eligible
+                    .get_or_insert_with(HashSet::new)
+                    .extend(record.eligible);
+            }
+        }
+        Ok(eligible)
+    }
+
+    fn read_records(path: &str) -> Result<Vec<JournalRecord>> {
+        let file = File::open(path)?;
+        BufReader::new(file)
+            .lines()
+            .filter(|line| !matches!(line, Ok(line) if line.trim().is_empty()))
+            .map(|line| {
+                let line = line?;
+                // A line that is neither is reported with the entry's error, the useful one
+                match serde_json::from_str(&line) {
+                    Ok(entry) => Ok(JournalRecord::Entry(entry)),
+                    Err(e) => match serde_json::from_str(&line) {
+                        Ok(record) => Ok(JournalRecord::Eligible(record)),
+                        Err(_) => Err(e.into()),
+                    },
+                }
+            })
+            .collect()
+    }
+}
+
+/// Totals of a journal, per sender where nonces are concerned
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct JournalSummary {
+    pub transactions: usize,
+    /// Distinct recipients across all transactions
+    pub recipients: usize,
+    pub gas_limit: u64,
+    pub value: U256,
+    pub senders: BTreeMap<Address, NonceRange>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub struct NonceRange {
+    pub first: u64,
+    pub last: u64,
+}
+
+impl JournalSummary {
+    pub fn new(entries: &[JournalEntry]) -> Self {
+        let mut summary = Self::default();
+        let mut recipients = HashSet::new();
+        for entry in entries {
+            summary.transactions += 1;
+            summary.gas_limit += entry.gas_limit;
+            summary.value += entry.value;
+            recipients.extend(entry.recipients.iter().copied());
+            let range = summary.senders.entry(entry.sender).or_insert(NonceRange {
+                first: entry.nonce,
+                last: entry.nonce,
+            });
+            range.first = range.first.min(entry.nonce);
+            range.last = range.last.max(entry.nonce);
+        }
+        summary.recipients = recipients.len();
+        summary
+    }
+}
+
+/// Function selector of `input`, or `None` for a plain transfer without calldata
+pub fn selector(input: &[u8]) -> Option<FixedBytes<4>> {
+    if input.is_empty() {
+        return None;
+    }
+    let mut selector = FixedBytes::ZERO;
+    let len = input.len().min(4);
+    selector[..len].copy_from_slice(&input[..len]);
+    Some(selector)
+}
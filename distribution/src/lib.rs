@@ -1,6 +1,20 @@
+pub mod addresses;
+pub mod allocation;
 pub mod builders;
 pub mod distributors;
+pub mod filters;
+pub mod idempotency;
+pub mod journal;
+pub mod merkle;
+pub mod overrides;
+pub mod plan;
 pub mod prelude;
+pub mod report;
+pub mod rpc_pool;
+pub mod shutdown;
+pub mod validate;
+pub mod verify;
+pub mod ws;
 
 pub use builders::*;
 pub use distributors::*;
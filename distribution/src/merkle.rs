@@ -0,0 +1,142 @@
+use std::fs::File;
+
+use alloy::primitives::{keccak256, B256};
+
+use crate::prelude::*;
+
+/// A single claim and the proof of its inclusion in the tree
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Claim {
+    pub index: u64,
+    pub account: Address,
+    pub amount: U256,
+    pub proof: Vec<B256>,
+}
+
+/// Root and claims of a Merkle airdrop, as written to the proofs file
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ProofsFile {
+    pub root: B256,
+    pub total: U256,
+    pub claims: Vec<Claim>,
+}
+
+impl ProofsFile {
+    pub fn read(path: &str) -> Result<Self> {
+        Ok(serde_json::from_reader(io::BufReader::new(File::open(
+            path,
+        )?))?)
+    }
+
+    pub fn write(&self, path: &str) -> Result<()> {
+        serde_json::to_writer_pretty(File::create(path)?, self)?;
+        Ok(())
+    }
+}
+
+/// Leaf hash matching `keccak256(abi.encodePacked(index, account, amount))` in `MerkleDistributor`
+pub fn leaf(index: u64, account: Address, amount: U256) -> B256 {
+    let mut packed = Vec::with_capacity(84);
+    packed.extend_from_slice(&U256::from(index).to_be_bytes::<32>());
+    packed.extend_from_slice(account.as_slice());
+    packed.extend_from_slice(&amount.to_be_bytes::<32>());
+    keccak256(packed)
+}
+
+/// Hash a pair in sorted order, as OpenZeppelin's `MerkleProof` does
+fn hash_pair(a: B256, b: B256) -> B256 {
+    let (low, high) = if a <= b { (a, b) } else { (b, a) };
+    keccak256([low.as_slice(), high.as_slice()].concat())
+}
+
+/// Check a proof the same way the distributor contract does
+pub fn verify_proof(proof: &[B256], root: B256, leaf: B256) -> bool {
+    proof
+        .iter()
+        .fold(leaf, |hash, sibling| hash_pair(hash, *sibling))
+        == root
+}
+
+/// Merkle tree over claims, with a lone node at the end of a layer carried up unchanged
+pub struct MerkleTree {
+    /// Layers from the leaves up to the root
+    layers: Vec<Vec<B256>>,
+}
+
+impl MerkleTree {
+    pub fn new(leaves: Vec<B256>) -> Result<Self> {
+        if leaves.is_empty() {
+            return Err(anyhow::anyhow!("Cannot build a Merkle tree without leaves"));
+        }
+        let mut layers = vec![leaves];
+        while layers.last().unwrap().len() > 1 {
+            let next = layers
+                .last()
+                .unwrap()
+                .chunks(2)
+                .map(|pair| match pair {
+                    [a, b] => hash_pair(*a, *b),
+                    [a] => *a,
+                    _ => unreachable!(),
+                })
+                .collect();
+            layers.push(next);
+        }
+        Ok(Self { layers })
+    }
+
+    pub fn root(&self) -> B256 {
+        self.layers.last().unwrap()[0]
+    }
+
+    pub fn proof(&self, mut index: usize) -> Vec<B256> {
+        let mut proof = Vec::new();
+        for layer in &self.layers[..self.layers.len() - 1] {
+            if let Some(sibling) = layer.get(index ^ 1) {
+                proof.push(*sibling);
+            }
+            index /= 2;
+        }
+        proof
+    }
+}
+
+/// Build the tree for `amounts[i]` owed to `accounts[i]`, returning every claim with its proof
+pub fn build_proofs(accounts: &[Address], amounts: &[U256]) -> Result<ProofsFile> {
+    assert!(
+        accounts.len() == amounts.len(),
+        "Accounts and amounts must be the same length"
+    );
+    let leaves = accounts
+        .iter()
+        .zip(amounts)
+        .enumerate()
+        .map(|(i, (account, amount))| leaf(i as u64, *account, *amount))
+        .collect();
+    let tree = MerkleTree::new(leaves)?;
+    let claims = accounts
+        .iter()
+        .zip(amounts)
+        .enumerate()
+        .map(|(i, (account, amount))| Claim {
+            index: i as u64,
+            account: *account,
+            amount: *amount,
+            proof: tree.proof(i),
+        })
+        .collect();
+    Ok(ProofsFile {
+        root: tree.root(),
+        total: amounts.iter().sum(),
+        claims,
+    })
+}
+
+/// Read the creation bytecode from a Forge build artifact
+pub fn read_forge_bytecode(path: &str) -> Result<Bytes> {
+    let artifact: Value = serde_json::from_reader(io::BufReader::new(File::open(path)?))?;
+    let bytecode = artifact["bytecode"]["object"]
+        .as_str()
+        .ok_or_else(|| anyhow::anyhow!("No bytecode.object in artifact {}", path))?;
+    Ok(bytecode.parse()?)
+}
@@ -0,0 +1,306 @@
+use serde_yaml::{Mapping, Value as Yaml};
+
+use crate::prelude::*;
+
+/// Prefix of environment variables overriding config fields, as in `RAINMAKER_CORE_TARGET_TPS`
+pub const ENV_PREFIX: &str = "RAINMAKER_";
+
+/// Sections overridable from the environment, with the fields each one accepts
+const FIELDS: &[(&str, &[&str])] = &[
+    (
+        "core",
+        &[
+            "rpc_urls",
+            "target_tps",
+            "rpc_batch_size",
+            "distribution_type",
+            "addresses_file",
+            "scenario_file",
+            "journal_file",
+        ],
+    ),
+    (
+        "token",
+        &[
+            "batch_sender_address",
+            "amount_per_address_low",
+            "amount_per_address_high",
+            "allocation",
+            "batch",
+        ],
+    ),
+    ("nft", &["token_address", "soulbound", "image_url", "batch"]),
+    (
+        "swapper",
+        &[
+            "swapper_address",
+            "max_swaps",
+            "amount",
+            "direction",
+            "max_slippage_bps",
+        ],
+    ),
+    (
+        "contract_call",
+        &[
+            "contract_address",
+            "function_signature",
+            "args",
+            "max_calls",
+            "value",
+        ],
+    ),
+    ("strict_counter", &["counter_address", "num_updates"]),
+    (
+        "merkle",
+        &[
+            "proofs_file",
+            "distributor_address",
+            "artifact_file",
+            "fund",
+            "skip_claimed",
+        ],
+    ),
+    (
+        "uniswap_v2",
+        &[
+            "router_address",
+            "path",
+            "amount_in",
+            "native_in",
+            "max_swaps",
+            "max_slippage_bps",
+            "deadline_secs",
+        ],
+    ),
+    (
+        "idempotency",
+        &["use_journal", "scan_from_block", "scan_to_block"],
+    ),
+    ("addresses", &["format", "strict", "duplicates"]),
+    (
+        "filters",
+        &[
+            "exclude_files",
+            "skip_contracts",
+            "min_balance",
+            "max_balance",
+        ],
+    ),
+    (
+        "rpc_pool",
+        &[
+            "health_check_interval_ms",
+            "max_block_lag",
+            "max_latency_ms",
+            "max_error_rate",
+            "endpoints",
+        ],
+    ),
+    (
+        "stop",
+        &[
+            "max_duration_secs",
+            "max_transactions",
+            "max_gas",
+            "max_value",
+            "min_signer_balance",
+        ],
+    ),
+];
+
+/// Variables sharing the prefix that set something other than a config field
+const OTHER_VARS: &[&str] = &["RAINMAKER_LOG"];
+
+/// Keys whose values are never printed
+const SECRET_KEYS: &[&str] = &["private_key", "secret", "password", "api_key"];
+
+/// A config field replaced on top of the YAML file
+#[derive(Debug, Clone, PartialEq)]
+pub struct Override {
+    /// Dotted path of the field, such as `core.target_tps`
+    pub path: String,
+    /// Raw value, parsed as YAML when applied
+    pub value: String,
+    /// Where the override came from, for error messages
+    pub source: String,
+}
+
+impl Override {
+    /// Parse a `--set core.target_tps=2000` argument
+    pub fn parse_set(arg: &str) -> Result<Self> {
+        let (path, value) = arg
+            .split_once('=')
+            .ok_or_else(|| anyhow::anyhow!("Override {} must look like field=value", arg))?;
+        let path = path.trim().to_string();
+        check_field(&path)?;
+        Ok(Self {
+            path,
+            value: value.to_string(),
+            source: format!("--set {}", arg),
+        })
+    }
+
+    /// Overrides from `RAINMAKER_<SECTION>_<FIELD>` variables, in a stable order
+    ///
+    /// A double underscore separates nested fields, as in `RAINMAKER_TOKEN_BATCH__SIZE` for
+    /// `token.batch.size`; it may also follow the section, as in `RAINMAKER_TOKEN__BATCH__SIZE`.
+    /// Variables naming no section are ignored with a warning.
+    pub fn from_env(vars: impl IntoIterator<Item = (String, String)>) -> Result<Vec<Self>> {
+        let mut overrides = Vec::new();
+        for (name, value) in vars {
+            let Some(rest) = name.strip_prefix(ENV_PREFIX) else {
+                continue;
+            };
+            if OTHER_VARS.contains(&name.as_str()) {
+                continue;
+            }
+            let rest = rest.to_lowercase();
+            // Section names contain underscores too, so the longest one that fits wins
+            let section = FIELDS
+                .iter()
+                .map(|(section, _)| *section)
+                .filter(|section| {
+                    rest.strip_prefix(section)
+                        .is_some_and(|field| field.starts_with('_'))
+                })
+                .max_by_key(|section| section.len());
+            let Some(section) = section else {
+                warn!("Ignoring {}, which names no config section", name);
+                continue;
+            };
+            let fields = rest[section.len()..].trim_start_matches('_');
+            let path = format!("{}.{}", section, fields.replace("__", "."));
+            check_field(&path).map_err(|e| anyhow::anyhow!("{}: {}", name, e))?;
+            overrides.push(Self {
+                path,
+                value,
+                source: name,
+            });
+        }
+        overrides.sort_by(|a, b| a.source.cmp(&b.source));
+        Ok(overrides)
+    }
+
+    fn segments(&self) -> Vec<&str> {
+        self.path.split('.').collect()
+    }
+}
+
+/// Reject unknown fields of the overridable sections, which serde would otherwise ignore
+fn check_field(path: &str) -> Result<()> {
+    let mut segments = path.split('.');
+    let section = segments.next().unwrap_or_default();
+    let field = segments.next();
+    if section.is_empty() || path.split('.').any(str::is_empty) {
+        return Err(anyhow::anyhow!("Invalid field {}", path));
+    }
+    if let (Some((_, fields)), Some(field)) =
+        (FIELDS.iter().find(|(known, _)| *known == section), field)
+    {
+        if !fields.contains(&field) {
+            return Err(anyhow::anyhow!(
+                "Unknown field {}.{}, expected one of {}",
+                section,
+                field,
+                fields.join(", ")
+            ));
+        }
+    }
+    Ok(())
+}
+
+/// Set every override in `config`, later overrides winning over earlier ones
+pub fn apply(config: &mut Yaml, overrides: &[Override]) -> Result<()> {
+    for o in overrides {
+        let segments = o.segments();
+        let (field, parents) = segments.split_last().unwrap();
+        let mut node = &mut *config;
+        for parent in parents {
+            if node.is_null() {
+                *node = Yaml::Mapping(Mapping::new());
+            }
+            node = node
+                .as_mapping_mut()
+                .ok_or_else(|| anyhow::anyhow!("{}: {} is not a section", o.source, parent))?
+                .entry(Yaml::String(parent.to_string()))
+                .or_insert(Yaml::Null);
+        }
+        if node.is_null() {
+            *node = Yaml::Mapping(Mapping::new());
+        }
+        let mapping = node
+            .as_mapping_mut()
+            .ok_or_else(|| anyhow::anyhow!("{}: cannot set {}", o.source, o.path))?;
+        let current = mapping.get(*field);
+        let value = parse_value(&o.value, current);
+        mapping.insert(Yaml::String(field.to_string()), value);
+    }
+    Ok(())
+}
+
+/// Parse a raw override as YAML, keeping hex addresses as strings and splitting lists on commas
+fn parse_value(raw: &str, current: Option<&Yaml>) -> Yaml {
+    if raw.starts_with("0x") {
+        return Yaml::String(raw.to_string());
+    }
+    let value = serde_yaml::from_str(raw).unwrap_or_else(|_| Yaml::String(raw.to_string()));
+    match (value, current) {
+        (Yaml::String(list), Some(Yaml::Sequence(_))) => Yaml::Sequence(
+            list.split(',')
+                .map(|item| Yaml::String(item.trim().to_string()))
+                .collect(),
+        ),
+        (value, _) => value,
+    }
+}
+
+/// A copy of `config` that is safe to log
+///
+/// Secret-looking keys are masked, and URLs lose any credentials, path and query, which is
+/// where RPC providers put API keys.
+pub fn redacted(config: &Yaml) -> Yaml {
+    match config {
+        Yaml::Mapping(mapping) => Yaml::Mapping(
+            mapping
+                .iter()
+                .map(|(key, value)| {
+                    let secret = key.as_str().is_some_and(|key| {
+                        let key = key.to_lowercase();
+                        SECRET_KEYS.iter().any(|secret| key.contains(secret))
+                    });
+                    let value = if secret {
+                        Yaml::String("***".to_string())
+                    } else {
+                        redacted(value)
+                    };
+                    (key.clone(), value)
+                })
+                .collect(),
+        ),
+        Yaml::Sequence(items) => Yaml::Sequence(items.iter().map(redacted).collect()),
+        Yaml::String(value) => Yaml::String(redact_url(value)),
+        value => value.clone(),
+    }
+}
+
+fn redact_url(value: &str) -> String {
+    let Ok(mut url) = reqwest::Url::parse(value) else {
+        return value.to_string();
+    };
+    if url.cannot_be_a_base() {
+        return value.to_string();
+    }
+    let hidden = !url.username().is_empty()
+        || url.password().is_some()
+        || url.path() != "/"
+        || url.query().is_some();
+    if !hidden {
+        return value.to_string();
+    }
+    let _ = url.set_username("");
+    let _ = url.set_password(None);
+    url.set_query(None);
+    url.set_path("***");
+    url.to_string()
+}
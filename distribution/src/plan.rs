@@ -0,0 +1,102 @@
+use std::ops::Range;
+
+use crate::prelude::*;
+
+/// What a run is about to send, printed as one JSON line on stdout for pipelines
+#[derive(Debug, Clone, Serialize)]
+pub struct PlanSummary {
+    pub distribution_type: String,
+    pub signer: Address,
+    pub recipients: usize,
+    /// Sum of all amounts, in wei as a decimal string
+    pub total: String,
+    pub rpc_urls: Vec<String>,
+    pub rpc_batch_size: usize,
+    pub target_tps: u64,
+    /// What the built transactions add up to, when they were built ahead of sending
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub transactions: Option<TransactionPlan>,
+}
+
+impl PlanSummary {
+    pub fn to_json(&self) -> String {
+        serde_json::to_string(self).expect("plan summary is always serializable")
+    }
+}
+
+/// Totals a plan must match to be confirmed without a prompt
+#[derive(Debug, Clone, Default)]
+pub struct Expectations {
+    pub total: Option<U256>,
+    pub count: Option<usize>,
+}
+
+impl Expectations {
+    pub fn is_empty(&self) -> bool {
+        self.total.is_none() && self.count.is_none()
+    }
+
+    /// Every way `recipients` and `total` differ from what was expected
+    pub fn mismatches(&self, recipients: usize, total: U256) -> Vec<String> {
+        let mut mismatches = Vec::new();
+        if let Some(count) = self.count.filter(|&count| count != recipients) {
+            mismatches.push(format!(
+                "expected {} recipients, plan has {}",
+                count, recipients
+            ));
+        }
+        if let Some(expected) = self.total.filter(|&expected| expected != total) {
+            mismatches.push(format!(
+                "expected a total of {} wei, plan has {}",
+                expected, total
+            ));
+        }
+        mismatches
+    }
+}
+
+/// Totals of built transactions, grouped as the distributor would dispatch them
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct TransactionPlan {
+    pub count: usize,
+    pub gas_limit: u64,
+    /// Native value sent, in wei as a decimal string
+    pub value: String,
+    /// Most the transactions can cost in fees, in wei as a decimal string
+    pub max_fee_cost: String,
+    pub batches: Vec<BatchPlan>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct BatchPlan {
+    pub transactions: usize,
+    pub first_nonce: u64,
+    pub last_nonce: u64,
+    pub gas_limit: u64,
+}
+
+impl TransactionPlan {
+    /// `groups` are ranges of `txs` sent together, from `Distributor::dispatch_groups`
+    pub fn new(txs: &[SignedTransaction], groups: &[Range<usize>]) -> Self {
+        let max_fee_cost = txs
+            .iter()
+            .map(|tx| U256::from(tx.gas_limit) * U256::from(tx.max_fee))
+            .sum::<U256>();
+        Self {
+            count: txs.len(),
+            gas_limit: txs.iter().map(|tx| tx.gas_limit).sum(),
+            value: txs.iter().map(|tx| tx.value).sum::<U256>().to_string(),
+            max_fee_cost: max_fee_cost.to_string(),
+            batches: groups
+                .iter()
+                .map(|group| &txs[group.clone()])
+                .map(|batch| BatchPlan {
+                    transactions: batch.len(),
+                    first_nonce: batch.iter().map(|tx| tx.nonce).min().unwrap_or_default(),
+                    last_nonce: batch.iter().map(|tx| tx.nonce).max().unwrap_or_default(),
+                    gas_limit: batch.iter().map(|tx| tx.gas_limit).sum(),
+                })
+                .collect(),
+        }
+    }
+}
@@ -1,6 +1,12 @@
 use std::fs::File;
 use std::io::Read;
+use std::ops::Range;
 use std::str::FromStr;
+pub use std::sync::Arc;
+
+use alloy::primitives::utils::parse_ether;
+
+use crate::builders::native::float_to_u256;
 
 pub use alloy::consensus::SignableTransaction;
 pub use alloy::rpc::types::TransactionRequest;
@@ -8,18 +14,21 @@ pub use alloy::sol;
 pub use alloy::{
     consensus::TxLegacy,
     network::TxSignerSync,
-    primitives::{Address, FixedBytes, TxKind, U256},
+    primitives::{Address, Bytes, FixedBytes, TxHash, TxKind, U256},
     providers::{Provider, ProviderBuilder},
     signers::local::PrivateKeySigner,
     sol_types::{SolCall, SolType},
 };
-use alloy::{hex::FromHex, primitives::TxHash};
 pub use rand::Rng;
 pub use serde::{Deserialize, Serialize};
 pub use std::io::{self, BufRead};
 pub use std::path::Path;
-use tracing::warn;
 
+pub use crate::journal::{Journal, JournalSummary};
+pub use crate::report::RunReport;
+pub use crate::rpc_pool::RpcPool;
+pub use crate::shutdown::Shutdown;
+pub use crate::ws::{is_ws_url, WsClient};
 pub use anyhow::{Error, Result};
 pub use async_trait::async_trait;
 pub use dotenv::dotenv;
@@ -28,7 +37,7 @@ pub use reqwest::Client;
 pub use serde_json::Value;
 pub use std::time::Duration;
 pub use tokio::time::sleep;
-pub use tracing::{debug, error, info};
+pub use tracing::{debug, error, info, warn};
 
 #[derive(Debug, Clone, Copy)]
 pub enum DistributionType {
@@ -36,6 +45,12 @@ pub enum DistributionType {
     NativeBatch,
     NFTMint,
     Swapper,
+    ContractCall,
+    StrictCounter,
+    UniswapV2,
+    Merkle,
+    MerkleClaim,
+    Scenario,
 }
 
 impl FromStr for DistributionType {
@@ -51,6 +66,18 @@ impl FromStr for DistributionType {
             }
             "swapper" | "Swapper" => DistributionType::Swapper,
             "nft-mint" | "nft_mint" | "nftmint" | "NFTMint" => DistributionType::NFTMint,
+            "contract-call" | "contract_call" | "contractcall" | "ContractCall" => {
+                DistributionType::ContractCall
+            }
+            "strict-counter" | "strict_counter" | "strictcounter" | "StrictCounter" => {
+                DistributionType::StrictCounter
+            }
+            "uniswap-v2" | "uniswap_v2" | "uniswapv2" | "UniswapV2" => DistributionType::UniswapV2,
+            "merkle" | "Merkle" => DistributionType::Merkle,
+            "merkle-claim" | "merkle_claim" | "merkleclaim" | "MerkleClaim" => {
+                DistributionType::MerkleClaim
+            }
+            "scenario" | "Scenario" => DistributionType::Scenario,
             x => return Err(anyhow::anyhow!("Unknown distribution type: {}", x)),
         };
         Ok(dist_type)
@@ -62,8 +89,8 @@ impl<'de> Deserialize<'de> for DistributionType {
     where
         D: serde::Deserializer<'de>,
     {
-        let s: &str = Deserialize::deserialize(deserializer)?;
-        DistributionType::from_str(s).map_err(serde::de::Error::custom)
+        let s: String = Deserialize::deserialize(deserializer)?;
+        DistributionType::from_str(&s).map_err(serde::de::Error::custom)
     }
 }
 
@@ -74,13 +101,105 @@ pub struct CoreConfig {
     pub rpc_batch_size: usize,
     pub distribution_type: DistributionType,
     pub addresses_file: Option<String>,
+    /// Mixed workload definition, required for the `scenario` distribution type
+    pub scenario_file: Option<String>,
+    /// JSONL journal of every transaction accepted by the RPC endpoints
+    pub journal_file: Option<String>,
 }
 
 #[derive(Debug, Deserialize)]
 pub struct TokenConfig {
     pub batch_sender_address: Address,
+    #[serde(default)]
     pub amount_per_address_low: f64,
+    #[serde(default)]
     pub amount_per_address_high: f64,
+    /// Overrides the `amount_per_address_*` range when set
+    pub allocation: Option<AllocationConfig>,
+    /// Recipients per `batchSend` call, 1000 by default
+    #[serde(default)]
+    pub batch: BatchConfig,
+}
+
+impl TokenConfig {
+    /// Allocation strategy, falling back to a fixed or random amount from the configured range
+    pub fn allocation(&self) -> AllocationConfig {
+        self.allocation.clone().unwrap_or_else(|| {
+            let low = float_to_u256(self.amount_per_address_low);
+            let high = float_to_u256(self.amount_per_address_high);
+            if low == high {
+                AllocationConfig::Fixed { amount: low }
+            } else {
+                AllocationConfig::Random {
+                    low,
+                    high,
+                    seed: None,
+                }
+            }
+        })
+    }
+}
+
+/// How amounts are assigned to recipients without one in the addresses file
+///
+/// Amounts are in ether, and may be quoted to avoid floating point rounding.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(tag = "strategy", rename_all = "snake_case")]
+pub enum AllocationConfig {
+    Fixed {
+        #[serde(deserialize_with = "deserialize_ether")]
+        amount: U256,
+    },
+    /// Uniformly random in `low..=high`, reproducible when seeded
+    Random {
+        #[serde(deserialize_with = "deserialize_ether")]
+        low: U256,
+        #[serde(deserialize_with = "deserialize_ether")]
+        high: U256,
+        seed: Option<u64>,
+    },
+    /// Split `total` evenly
+    Even {
+        #[serde(deserialize_with = "deserialize_ether")]
+        total: U256,
+    },
+    /// Split `total` in proportion to the `weight` column
+    ProRata {
+        #[serde(deserialize_with = "deserialize_ether")]
+        total: U256,
+    },
+    /// Split `total` pro-rata, giving no one more than `cap` and sharing the excess among the rest
+    CappedProRata {
+        #[serde(deserialize_with = "deserialize_ether")]
+        total: U256,
+        #[serde(deserialize_with = "deserialize_ether")]
+        cap: U256,
+    },
+    /// Pay each recipient the amount of the highest tier its weight reaches
+    Tiered { tiers: Vec<TierConfig> },
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct TierConfig {
+    pub min_weight: f64,
+    #[serde(deserialize_with = "deserialize_ether")]
+    pub amount: U256,
+}
+
+/// Parse an ether amount given as a number or a decimal string into wei
+fn deserialize_ether<'de, D>(deserializer: D) -> Result<U256, D::Error>
+where
+    D: serde::Deserializer<'de>,
+{
+    let amount = match Value::deserialize(deserializer)? {
+        Value::String(amount) => amount,
+        Value::Number(amount) => match amount.as_u64() {
+            Some(amount) => amount.to_string(),
+            None => amount.as_f64().unwrap_or_default().to_string(),
+        },
+        amount => return Err(serde::de::Error::custom(format!("invalid amount {}", amount))),
+    };
+    parse_ether(&amount).map_err(serde::de::Error::custom)
 }
 
 #[derive(Debug, Deserialize)]
@@ -88,21 +207,344 @@ pub struct NFTConfig {
     pub token_address: Address,
     pub soulbound: bool,
     pub image_url: String,
+    /// Recipients per `batchMint` call, 500 by default
+    #[serde(default)]
+    pub batch: BatchConfig,
+}
+
+/// How many recipients each call of a batch contract covers, and how their gas is estimated
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+pub struct BatchConfig {
+    /// Recipients per call, and the most any call gets when packing by gas
+    pub size: Option<usize>,
+    /// Pack as many recipients per call as fit under this gas limit
+    pub target_gas: Option<u64>,
+    /// Pack as many recipients per call as fit under this share of the latest block's gas limit
+    pub block_gas_fraction: Option<f64>,
+    /// `eth_estimateGas` calls in flight at once, spread over every HTTP RPC endpoint
+    pub concurrency: usize,
+    /// Estimate calls with the same number of recipients only once
+    pub cache: bool,
+    /// Estimate one representative call and scale it to the others by recipient count
+    pub extrapolate: bool,
+}
+
+impl Default for BatchConfig {
+    fn default() -> Self {
+        Self {
+            size: None,
+            target_gas: None,
+            block_gas_fraction: None,
+            concurrency: 8,
+            cache: false,
+            extrapolate: false,
+        }
+    }
 }
 
 #[derive(Debug, Deserialize)]
 pub struct SwapperConfig {
     pub swapper_address: Address,
     pub max_swaps: u64,
+    #[serde(default)]
+    pub amount: SwapAmountConfig,
+    #[serde(default)]
+    pub direction: SwapDirectionConfig,
+    /// Maximum price impact per swap in basis points, checked against the pair reserves
+    pub max_slippage_bps: Option<u64>,
+}
+
+/// Distribution of `amountIn` across swaps, in raw token units
+#[derive(Debug, Clone, Deserialize)]
+#[serde(tag = "distribution", rename_all = "snake_case")]
+pub enum SwapAmountConfig {
+    Fixed { value: u64 },
+    Uniform { low: u64, high: u64 },
+    /// `mu` and `sigma` are the mean and standard deviation of the underlying normal
+    LogNormal { mu: f64, sigma: f64 },
+}
+
+impl Default for SwapAmountConfig {
+    fn default() -> Self {
+        SwapAmountConfig::Fixed { value: 100 }
+    }
+}
+
+/// How the `aToB` flag is chosen for each swap
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(tag = "strategy", rename_all = "snake_case")]
+pub enum SwapDirectionConfig {
+    #[default]
+    Alternating,
+    Random,
+    /// Swap A to B with the given probability
+    Biased { a_to_b_probability: f64 },
 }
 
-// Unified configuration for all distribution types
 #[derive(Debug, Deserialize)]
-pub struct UnifiedConfig {
-    pub core: CoreConfig,
+pub struct UniswapV2Config {
+    pub router_address: Address,
+    pub path: Vec<Address>,
+    pub amount_in: f64,
+    #[serde(default)]
+    pub native_in: bool,
+    pub max_swaps: u64,
+    pub max_slippage_bps: Option<u64>,
+    #[serde(default = "default_deadline_secs")]
+    pub deadline_secs: u64,
+}
+
+fn default_deadline_secs() -> u64 {
+    300
+}
+
+#[derive(Debug, Deserialize)]
+pub struct StrictCounterConfig {
+    pub counter_address: Address,
+    pub num_updates: u64,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct MerkleConfig {
+    /// Where the root and per-address proofs are written, or read from when claiming
+    pub proofs_file: String,
+    /// Existing distributor to set the new root on, deployed from `artifact_file` when unset
+    ///
+    /// Required when claiming.
+    pub distributor_address: Option<Address>,
+    /// Forge build artifact of `src/MerkleDistributor.sol`
+    #[serde(default = "default_merkle_artifact_file")]
+    pub artifact_file: String,
+    /// Send the total of all claims to the distributor
+    #[serde(default = "default_fund")]
+    pub fund: bool,
+    /// Skip claims the distributor already marks as claimed when claiming on behalf of recipients
+    #[serde(default = "default_skip_claimed")]
+    pub skip_claimed: bool,
+}
+
+fn default_merkle_artifact_file() -> String {
+    "../out/MerkleDistributor.sol/MerkleDistributor.json".to_string()
+}
+
+fn default_fund() -> bool {
+    true
+}
+
+fn default_skip_claimed() -> bool {
+    true
+}
+
+/// Source of a single contract call argument
+#[derive(Debug, Clone, Deserialize)]
+#[serde(tag = "source", rename_all = "snake_case")]
+pub enum ArgSource {
+    /// Column of the addresses file (0 is the address itself)
+    Column { index: usize },
+    /// Incrementing counter, one step per call
+    Counter {
+        start: u64,
+        #[serde(default = "default_counter_step")]
+        step: u64,
+    },
+    /// Uniformly random integer in `low..=high`
+    Random { low: u64, high: u64 },
+    /// Fixed value, coerced to the parameter type
+    Constant { value: String },
+}
+
+fn default_counter_step() -> u64 {
+    1
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ContractCallConfig {
+    pub contract_address: Address,
+    pub function_signature: String,
+    #[serde(default)]
+    pub args: Vec<ArgSource>,
+    #[serde(default)]
+    pub max_calls: u64,
+    #[serde(default)]
+    pub value: f64,
+}
+
+// Builder-specific settings, shared by the main config and scenario workloads
+#[derive(Debug, Default, Deserialize)]
+pub struct BuilderSections {
     pub token: Option<TokenConfig>,
     pub nft: Option<NFTConfig>,
     pub swapper: Option<SwapperConfig>,
+    pub contract_call: Option<ContractCallConfig>,
+    pub strict_counter: Option<StrictCounterConfig>,
+    pub merkle: Option<MerkleConfig>,
+    pub uniswap_v2: Option<UniswapV2Config>,
+}
+
+// Skips recipients that were already paid by the signer
+#[derive(Debug, Deserialize)]
+pub struct IdempotencyConfig {
+    /// Treat recipients in `core.journal_file` as paid
+    #[serde(default = "default_use_journal")]
+    pub use_journal: bool,
+    /// First block to scan for payments from the signer, scanning is disabled when unset
+    pub scan_from_block: Option<u64>,
+    /// Last block to scan, defaults to the latest block
+    pub scan_to_block: Option<u64>,
+}
+
+fn default_use_journal() -> bool {
+    true
+}
+
+// Limits after which a continuous run stops, drains and reports
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct StopConfig {
+    /// Wall-clock time to keep sending for
+    pub max_duration_secs: Option<u64>,
+    /// Transactions to send in total
+    pub max_transactions: Option<u64>,
+    /// Gas to spend in total, counted by gas limit
+    pub max_gas: Option<u64>,
+    /// Native value to send in total, in ether
+    pub max_value: Option<f64>,
+    /// Signer balance, in ether, below which no more rounds are started
+    pub min_signer_balance: Option<f64>,
+}
+
+// Health checks and weighted routing across `core.rpc_urls`
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+pub struct RpcPoolConfig {
+    /// How often every endpoint is probed with `eth_blockNumber`
+    pub health_check_interval_ms: u64,
+    /// Blocks an endpoint may trail the highest reported block before it is excluded
+    pub max_block_lag: u64,
+    /// Probe latency above which an endpoint is excluded
+    pub max_latency_ms: u64,
+    /// Recent share of failed probes and sends above which an endpoint is excluded
+    pub max_error_rate: f64,
+    /// Per-URL routing settings, endpoints not listed get a weight of 1 and no cap
+    pub endpoints: Vec<RpcEndpointConfig>,
+}
+
+impl Default for RpcPoolConfig {
+    fn default() -> Self {
+        Self {
+            health_check_interval_ms: 5000,
+            max_block_lag: 5,
+            max_latency_ms: 2000,
+            max_error_rate: 0.5,
+            endpoints: vec![],
+        }
+    }
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct RpcEndpointConfig {
+    pub url: String,
+    /// Relative share of batches routed to this endpoint
+    #[serde(default = "default_rpc_weight")]
+    pub weight: f64,
+    /// Transactions per second this endpoint is sent at most
+    pub max_tps: Option<u64>,
+}
+
+fn default_rpc_weight() -> f64 {
+    1.0
+}
+
+// Drops recipients before any transactions are built
+#[derive(Debug, Default, Deserialize)]
+pub struct FilterConfig {
+    /// Addresses files listing addresses that must never be paid
+    #[serde(default)]
+    pub exclude_files: Vec<String>,
+    /// Skip addresses with deployed code
+    #[serde(default)]
+    pub skip_contracts: bool,
+    /// Skip addresses whose current balance, in ether, is below this
+    pub min_balance: Option<f64>,
+    /// Skip addresses whose current balance, in ether, is above this
+    pub max_balance: Option<f64>,
+}
+
+/// Layout of an addresses file
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum AddressesFormat {
+    /// Comma-separated, optionally with a header row
+    Csv,
+    /// A JSON array of recipient objects
+    Json,
+    /// One JSON recipient object per line
+    Jsonl,
+}
+
+/// What to do when an address appears more than once in an addresses file
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum DuplicatePolicy {
+    /// Keep the first occurrence
+    #[default]
+    Skip,
+    /// Keep the first occurrence, adding up the amounts of all occurrences
+    Sum,
+    /// Reject the file
+    Error,
+}
+
+// How addresses files are parsed
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct AddressesConfig {
+    /// Inferred from the file extension when unset, defaulting to CSV
+    pub format: Option<AddressesFormat>,
+    /// Reject the file on any invalid row instead of skipping it
+    #[serde(default)]
+    pub strict: bool,
+    #[serde(default)]
+    pub duplicates: DuplicatePolicy,
+}
+
+// Unified configuration for all distribution types
+#[derive(Debug, Deserialize)]
+pub struct UnifiedConfig {
+    pub core: CoreConfig,
+    pub idempotency: Option<IdempotencyConfig>,
+    #[serde(default)]
+    pub addresses: AddressesConfig,
+    pub filters: Option<FilterConfig>,
+    #[serde(default)]
+    pub rpc_pool: RpcPoolConfig,
+    #[serde(default)]
+    pub stop: StopConfig,
+    #[serde(flatten)]
+    pub sections: BuilderSections,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct WorkloadConfig {
+    pub name: String,
+    /// Relative share of the dispatch stream
+    pub weight: f64,
+    pub distribution_type: DistributionType,
+    pub addresses_file: Option<String>,
+    /// Environment variables holding the private keys of this workload's signers
+    #[serde(default = "default_signer_keys")]
+    pub signer_keys: Vec<String>,
+    #[serde(flatten)]
+    pub sections: BuilderSections,
+}
+
+fn default_signer_keys() -> Vec<String> {
+    vec!["PRIVATE_KEY".to_string()]
+}
+
+// Weighted workloads interleaved into a single dispatch stream
+#[derive(Debug, Deserialize)]
+pub struct ScenarioConfig {
+    pub workloads: Vec<WorkloadConfig>,
 }
 
 #[derive(Debug, Serialize)]
@@ -113,32 +555,36 @@ pub struct JsonRpcRequest {
     pub params: Vec<String>,
 }
 
-pub fn read_config_file<T>(filename: &str) -> T
+pub fn read_config_file<T>(filename: &str) -> Result<T>
 where
     T: for<'a> Deserialize<'a>,
 {
-    let mut file = File::open(format!("{}", filename)).unwrap();
+    let mut file = File::open(filename)
+        .map_err(|e| anyhow::anyhow!("Failed to open {}: {}", filename, e))?;
     let mut contents = String::new();
-    file.read_to_string(&mut contents).unwrap();
+    file.read_to_string(&mut contents)?;
     // Parse the YAML into our Config struct
-    let config: T = serde_yaml::from_str(&contents).unwrap();
-    config
+    let config: T = serde_yaml::from_str(&contents)
+        .map_err(|e| anyhow::anyhow!("Failed to parse {}: {}", filename, e))?;
+    Ok(config)
 }
 
 /// Poll for transaction receipt until it completes or times out using Alloy provider
+///
+/// WebSocket endpoints are checked on every new block instead of polled.
 pub async fn wait_for_transaction(
     rpc_url: String,
-    tx_hash: String,
+    hash: TxHash,
     timeout_seconds: u64,
 ) -> Result<Option<u64>> {
-    info!("Waiting for transaction {} to be mined...", tx_hash);
+    if is_ws_url(&rpc_url) {
+        return crate::ws::wait_for_transaction(&rpc_url, hash, timeout_seconds).await;
+    }
+    info!("Waiting for transaction {} to be mined...", hash);
 
     // Create an Alloy provider for just this polling operation
     let provider = ProviderBuilder::new().on_http(rpc_url.parse().unwrap());
 
-    // Convert tx_hash string to B256 type
-    let hash = TxHash::from_hex(tx_hash).unwrap();
-
     let poll_interval = Duration::from_millis(100);
     let timeout = Duration::from_secs(timeout_seconds);
     let start_time = std::time::Instant::now();
@@ -176,63 +622,206 @@ pub async fn wait_for_transaction(
     Ok(None)
 }
 
+/// Wait for the last transaction of a run to be mined, then check the builder's final state
+///
+/// A timeout is an error of its own, so a slow chain is never reported as a failed check.
+pub async fn confirm_and_verify(
+    rpc_url: String,
+    last: Option<TxHash>,
+    builder: &(dyn Builder + Send + Sync),
+    timeout_seconds: u64,
+) -> Result<()> {
+    if let Some(hash) = last {
+        if wait_for_transaction(rpc_url, hash, timeout_seconds)
+            .await?
+            .is_none()
+        {
+            return Err(anyhow::anyhow!(
+                "Transaction {} not confirmed within {} seconds, skipping verification",
+                hash,
+                timeout_seconds
+            ));
+        }
+    }
+    builder.verify().await
+}
+
+/// Attempts at sending a batch before giving up on it
+const MAX_SEND_ATTEMPTS: u32 = 6;
+
+/// Wait before the first retry of a batch, doubled after every further failure
+const SEND_RETRY_DELAY: Duration = Duration::from_millis(100);
+
+/// Send a batch of transactions, retrying with backoff until an endpoint from the pool returns a
+/// valid RPC response. Returns the transactions the endpoint accepted, or an error when the pool
+/// has no endpoint to send to or every attempt failed.
 pub async fn send_transactions_with_delay(
-    txs: Vec<String>,
+    txs: Vec<SignedTransaction>,
     http_client: Client,
-    url: String,
+    pool: Arc<RpcPool>,
     delay_ms: u64,
-) {
+) -> Result<Vec<SignedTransaction>> {
     if delay_ms > 0 {
         sleep(Duration::from_millis(delay_ms)).await;
     }
     let requests: Vec<JsonRpcRequest> = txs
-        .into_iter()
+        .iter()
         .enumerate()
         .map(|(i, tx)| JsonRpcRequest {
             jsonrpc: "2.0".to_string(),
             id: i,
             method: "eth_sendRawTransaction".to_string(),
-            params: vec![tx],
+            params: vec![tx.to_hex()],
         })
         .collect();
 
+    let mut retry_delay = SEND_RETRY_DELAY;
+    let mut attempt = 0;
     loop {
-        match http_client
-            .post(&url)
-            .json(&requests)
-            .timeout(Duration::from_secs(3))
-            .send()
-            .await
-        {
-            Ok(response) => {
-                let x = response.text().await.unwrap();
-                match serde_json::from_str::<Vec<Value>>(&x) {
-                    Ok(body) => {
-                        // If receive a valid RPC response, don't retry
-                        for obj in body {
-                            if obj.get("error").is_some() {
-                                error!("URL {} RPC error: {:?}", url, obj);
-                            }
+        attempt += 1;
+        let url = pool.select(txs.len()).await?;
+        match send_batch(&http_client, &pool, &url, &requests).await {
+            Ok(body) => {
+                // If receive a valid RPC response, don't retry
+                pool.record(&url, true);
+                let mut accepted = vec![false; txs.len()];
+                for obj in body {
+                    if obj.get("error").is_some() {
+                        error!("URL {} RPC error: {:?}", url, obj);
+                    } else if let Some(id) = obj.get("id").and_then(Value::as_u64) {
+                        if let Some(flag) = accepted.get_mut(id as usize) {
+                            *flag = true;
                         }
-                        return;
-                    }
-                    Err(e) => {
-                        error!("URL {} Failed to parse response: {} {:?}", url, x, e);
                     }
                 }
+                return Ok(txs
+                    .into_iter()
+                    .zip(accepted)
+                    .filter_map(|(tx, accepted)| accepted.then_some(tx))
+                    .collect());
             }
             Err(e) => {
                 error!("URL {} Batch request failed: {}", url, e);
+                pool.record(&url, false);
+                if attempt == MAX_SEND_ATTEMPTS {
+                    return Err(e.context(format!(
+                        "Batch of {} transactions failed {} times",
+                        txs.len(),
+                        MAX_SEND_ATTEMPTS
+                    )));
+                }
+                sleep(retry_delay).await;
+                retry_delay *= 2;
             }
         }
     }
 }
 
+/// Split the results of `send_transactions_with_delay` into every accepted transaction and the
+/// first error, so batches accepted alongside a failed one are still accounted for
+pub fn accepted_batches(
+    results: Vec<Result<Vec<SignedTransaction>>>,
+) -> (Vec<SignedTransaction>, Option<Error>) {
+    let mut accepted = Vec::new();
+    let mut failure = None;
+    for result in results {
+        match result {
+            Ok(txs) => accepted.extend(txs),
+            Err(e) => {
+                failure.get_or_insert(e);
+            }
+        }
+    }
+    (accepted, failure)
+}
+
+/// Send a JSON-RPC batch over HTTP or WebSocket, returning every response object
+async fn send_batch(
+    http_client: &Client,
+    pool: &RpcPool,
+    url: &str,
+    requests: &[JsonRpcRequest],
+) -> Result<Vec<Value>> {
+    if is_ws_url(url) {
+        let client = pool.ws_client(url).await?;
+        return tokio::time::timeout(Duration::from_secs(3), client.batch(requests)).await?;
+    }
+    let response = http_client
+        .post(url)
+        .json(requests)
+        .timeout(Duration::from_secs(3))
+        .send()
+        .await?;
+    let x = response.text().await?;
+    serde_json::from_str::<Vec<Value>>(&x)
+        .map_err(|e| anyhow::anyhow!("Failed to parse response: {} {:?}", x, e))
+}
+
+/// A signed transaction ready to be broadcast, along with the metadata needed to track it
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SignedTransaction {
+    /// RLP-encoded signed transaction
+    pub raw: Bytes,
+    pub hash: TxHash,
+    pub nonce: u64,
+    pub sender: Address,
+    pub gas_limit: u64,
+    /// Maximum fee per gas (the gas price for legacy transactions)
+    pub max_fee: u128,
+    pub value: U256,
+    /// Indices into the builder's recipient list covered by this transaction
+    pub recipients: Range<usize>,
+}
+
+impl SignedTransaction {
+    /// Sign a legacy transaction covering the given recipient indices
+    pub fn sign_legacy(
+        signer: &PrivateKeySigner,
+        mut tx: TxLegacy,
+        recipients: Range<usize>,
+    ) -> Result<Self> {
+        let signature = signer.sign_transaction_sync(&mut tx)?;
+        let signed_tx = tx.into_signed(signature);
+
+        let mut buf = Vec::new();
+        signed_tx.rlp_encode(&mut buf);
+        let tx = signed_tx.tx();
+        Ok(Self {
+            raw: buf.into(),
+            hash: *signed_tx.hash(),
+            nonce: tx.nonce,
+            sender: signer.address(),
+            gas_limit: tx.gas_limit,
+            max_fee: tx.gas_price,
+            value: tx.value,
+            recipients,
+        })
+    }
+
+    /// Hex encoding of the raw transaction, as expected by `eth_sendRawTransaction`
+    pub fn to_hex(&self) -> String {
+        format!("0x{}", hex::encode(&self.raw))
+    }
+}
+
 /// A trait for building transactions
 #[async_trait]
 pub trait Builder {
     /// Create and sign a transaction
-    async fn build_transactions(&self, start_nonce_override: Option<u64>) -> Result<Vec<String>>;
+    async fn build_transactions(
+        &self,
+        start_nonce_override: Option<u64>,
+    ) -> Result<Vec<SignedTransaction>>;
+
+    /// Recipients that built transactions' recipient indices refer to
+    fn recipients(&self) -> &[Address] {
+        &[]
+    }
+
+    /// Check on-chain state once all built transactions have been mined
+    async fn verify(&self) -> Result<()> {
+        Ok(())
+    }
 }
 
 /// A trait for distributing transactions
@@ -245,6 +834,9 @@ pub trait Distributor {
         builder: Box<dyn Builder + Send + Sync>,
         options: DistributionOptions,
     ) -> Result<()>;
+
+    /// Ranges of `len` transactions built together that go out in the same dispatch, for plans
+    fn dispatch_groups(&self, len: usize, options: &DistributionOptions) -> Vec<Range<usize>>;
 }
 
 /// Options for transaction distribution
@@ -255,6 +847,10 @@ pub struct DistributionOptions {
     pub target_tps: u64,
     pub wait_for_confirmation: bool,
     pub confirmation_timeout: u64,
+    pub journal: Option<Arc<Journal>>,
+    pub rpc_pool: RpcPoolConfig,
+    pub stop: StopConfig,
+    pub shutdown: Shutdown,
 }
 
 impl Default for DistributionOptions {
@@ -265,6 +861,10 @@ impl Default for DistributionOptions {
             wait_for_confirmation: true,
             confirmation_timeout: 120,
             rpc_urls: Vec::new(),
+            journal: None,
+            rpc_pool: RpcPoolConfig::default(),
+            stop: StopConfig::default(),
+            shutdown: Shutdown::default(),
         }
     }
 }
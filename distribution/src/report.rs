@@ -0,0 +1,142 @@
+use std::collections::BTreeMap;
+use std::time::Instant;
+
+use alloy::primitives::utils::format_ether;
+
+use crate::builders::native::float_to_u256;
+use crate::prelude::*;
+
+/// Running totals of a distribution, logged once it finishes
+#[derive(Debug, Clone)]
+pub struct RunReport {
+    started: Instant,
+    /// Transactions handed to an RPC endpoint
+    pub sent: u64,
+    /// Transactions an RPC endpoint accepted
+    pub accepted: u64,
+    /// Sum of the gas limits of sent transactions
+    pub gas: u64,
+    /// Native value of sent transactions
+    pub value: U256,
+    /// Highest nonce among accepted transactions, per signer
+    pub last_nonces: BTreeMap<Address, u64>,
+    /// Nonce of the last transaction confirmed as mined, per signer it was read for
+    pub confirmed_nonces: BTreeMap<Address, Option<u64>>,
+    pub stop_reason: Option<String>,
+}
+
+impl Default for RunReport {
+    fn default() -> Self {
+        Self {
+            started: Instant::now(),
+            sent: 0,
+            accepted: 0,
+            gas: 0,
+            value: U256::ZERO,
+            last_nonces: BTreeMap::new(),
+            confirmed_nonces: BTreeMap::new(),
+            stop_reason: None,
+        }
+    }
+}
+
+impl RunReport {
+    pub fn elapsed(&self) -> Duration {
+        self.started.elapsed()
+    }
+
+    pub fn record_sent(&mut self, txs: &[SignedTransaction]) {
+        for tx in txs {
+            self.sent += 1;
+            self.gas += tx.gas_limit;
+            self.value += tx.value;
+        }
+    }
+
+    pub fn record_accepted(&mut self, txs: &[SignedTransaction]) {
+        self.accepted += txs.len() as u64;
+        for tx in txs {
+            let last = self.last_nonces.entry(tx.sender).or_insert(tx.nonce);
+            *last = (*last).max(tx.nonce);
+        }
+    }
+
+    /// Why a batch sent `delay` from now would go out after the run's duration limit, if it would
+    pub fn past_deadline(&self, stop: &StopConfig, delay: Duration) -> Option<String> {
+        let max_duration_secs = stop.max_duration_secs?;
+        (self.elapsed() + delay >= Duration::from_secs(max_duration_secs))
+            .then(|| format!("ran for {}s", max_duration_secs))
+    }
+
+    /// How many of `txs` fit within every budget, with the reason the rest do not
+    pub fn within_budget(
+        &self,
+        stop: &StopConfig,
+        txs: &[SignedTransaction],
+    ) -> (usize, Option<String>) {
+        if let Some(reason) = self.past_deadline(stop, Duration::ZERO) {
+            return (0, Some(reason));
+        }
+        let max_value = stop.max_value.map(float_to_u256);
+        let (mut sent, mut gas, mut value) = (self.sent, self.gas, self.value);
+        for (i, tx) in txs.iter().enumerate() {
+            sent += 1;
+            gas += tx.gas_limit;
+            value += tx.value;
+            let reason = if stop.max_transactions.is_some_and(|max| sent > max) {
+                format!("sent {} transactions", sent - 1)
+            } else if stop.max_gas.is_some_and(|max| gas > max) {
+                format!("reached the gas budget of {}", stop.max_gas.unwrap())
+            } else if max_value.is_some_and(|max| value > max) {
+                format!(
+                    "reached the value budget of {}",
+                    format_ether(max_value.unwrap())
+                )
+            } else {
+                continue;
+            };
+            return (i, Some(reason));
+        }
+        (txs.len(), None)
+    }
+
+    /// Read the last mined nonce of each of `signers` from the chain
+    pub async fn record_confirmed_nonces(
+        &mut self,
+        pool: &RpcPool,
+        signers: impl IntoIterator<Item = Address>,
+    ) {
+        for signer in signers {
+            let confirmed = async {
+                let provider = ProviderBuilder::new().on_http(pool.primary_http()?.parse()?);
+                Ok::<_, Error>(provider.get_transaction_count(signer).await?)
+            };
+            match confirmed.await {
+                Ok(count) => {
+                    self.confirmed_nonces.insert(signer, count.checked_sub(1));
+                }
+                Err(e) => error!("Failed to read the confirmed nonce of {}: {}", signer, e),
+            }
+        }
+    }
+
+    pub fn log(&self) {
+        info!("Run report:");
+        if let Some(reason) = &self.stop_reason {
+            info!("  Stopped: {}", reason);
+        }
+        info!("  Duration: {:.1}s", self.elapsed().as_secs_f64());
+        info!("  Sent: {} ({} accepted)", self.sent, self.accepted);
+        info!("  Gas limit: {}", self.gas);
+        info!("  Value: {}", format_ether(self.value));
+        for (signer, nonce) in &self.last_nonces {
+            info!("  Last accepted nonce of {}: {}", signer, nonce);
+        }
+        for (signer, nonce) in &self.confirmed_nonces {
+            match nonce {
+                Some(nonce) => info!("  Last confirmed nonce of {}: {}", signer, nonce),
+                None => info!("  Last confirmed nonce of {}: none", signer),
+            }
+        }
+    }
+}
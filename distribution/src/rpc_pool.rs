@@ -0,0 +1,346 @@
+use std::collections::HashMap;
+use std::sync::Mutex;
+use std::time::Instant;
+
+use crate::prelude::*;
+
+/// Weight of each new probe or send outcome in an endpoint's error rate
+const ERROR_RATE_SMOOTHING: f64 = 0.2;
+
+/// How long opening a WebSocket connection may take before the endpoint counts as failed
+const WS_CONNECT_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Health of a single endpoint as last observed
+#[derive(Debug, Clone)]
+pub struct EndpointHealth {
+    pub url: String,
+    pub healthy: bool,
+    /// Latency of the last successful probe
+    pub latency_ms: Option<u64>,
+    /// Block number reported by the last successful probe
+    pub block_number: Option<u64>,
+    /// Exponentially smoothed share of failed probes and sends
+    pub error_rate: f64,
+}
+
+struct Endpoint {
+    health: EndpointHealth,
+    weight: f64,
+    max_tps: Option<u64>,
+    /// Running total for smooth weighted round robin
+    current_weight: f64,
+    /// Start of the current one-second window and transactions routed in it
+    window: (Instant, u64),
+}
+
+impl Endpoint {
+    fn record(&mut self, success: bool) {
+        let failure = if success { 0.0 } else { 1.0 };
+        self.health.error_rate += ERROR_RATE_SMOOTHING * (failure - self.health.error_rate);
+    }
+
+    fn has_capacity(&mut self, tx_count: u64) -> bool {
+        if self.window.0.elapsed() >= Duration::from_secs(1) {
+            self.window = (Instant::now(), 0);
+        }
+        // A batch larger than the cap still goes out, alone in its window
+        match self.max_tps {
+            Some(max_tps) => self.window.1 == 0 || self.window.1 + tx_count <= max_tps,
+            None => true,
+        }
+    }
+}
+
+/// Routes batches across RPC endpoints by weight, skipping unhealthy or capped ones
+pub struct RpcPool {
+    config: RpcPoolConfig,
+    endpoints: Mutex<Vec<Endpoint>>,
+    /// Open connections to WebSocket endpoints, by URL
+    ws_clients: tokio::sync::Mutex<HashMap<String, Arc<WsClient>>>,
+}
+
+/// Stops the background health checks when dropped
+pub struct HealthChecks(tokio::task::JoinHandle<()>);
+
+impl Drop for HealthChecks {
+    fn drop(&mut self) {
+        self.0.abort();
+    }
+}
+
+impl RpcPool {
+    pub fn new(rpc_urls: &[String], config: RpcPoolConfig) -> Self {
+        for endpoint in &config.endpoints {
+            if !rpc_urls.contains(&endpoint.url) {
+                warn!(
+                    "RPC pool settings for {} which is not in rpc_urls",
+                    endpoint.url
+                );
+            }
+        }
+        let endpoints = rpc_urls
+            .iter()
+            .map(|url| {
+                let settings = config
+                    .endpoints
+                    .iter()
+                    .find(|endpoint| &endpoint.url == url);
+                Endpoint {
+                    health: EndpointHealth {
+                        url: url.clone(),
+                        // Endpoints are trusted until the first probe says otherwise
+                        healthy: true,
+                        latency_ms: None,
+                        block_number: None,
+                        error_rate: 0.0,
+                    },
+                    weight: settings.map_or(1.0, |settings| settings.weight),
+                    max_tps: settings.and_then(|settings| settings.max_tps),
+                    current_weight: 0.0,
+                    window: (Instant::now(), 0),
+                }
+            })
+            .collect();
+        Self {
+            config,
+            endpoints: Mutex::new(endpoints),
+            ws_clients: tokio::sync::Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Health of every endpoint, in `rpc_urls` order
+    pub fn health(&self) -> Vec<EndpointHealth> {
+        let endpoints = self.endpoints.lock().unwrap();
+        endpoints
+            .iter()
+            .map(|endpoint| endpoint.health.clone())
+            .collect()
+    }
+
+    /// The first healthy endpoint, for reads such as nonces and receipts
+    pub fn primary(&self) -> Result<String> {
+        let endpoints = self.endpoints.lock().unwrap();
+        endpoints
+            .iter()
+            .find(|endpoint| endpoint.health.healthy)
+            .or(endpoints.first())
+            .map(|endpoint| endpoint.health.url.clone())
+            .ok_or_else(|| anyhow::anyhow!("At least one RPC URL is required"))
+    }
+
+    /// The first healthy HTTP endpoint, for reads through an Alloy provider
+    pub fn primary_http(&self) -> Result<String> {
+        let endpoints = self.endpoints.lock().unwrap();
+        let http = endpoints
+            .iter()
+            .filter(|endpoint| !is_ws_url(&endpoint.health.url))
+            .collect::<Vec<_>>();
+        http.iter()
+            .find(|endpoint| endpoint.health.healthy)
+            .or(http.first())
+            .map(|endpoint| endpoint.health.url.clone())
+            .ok_or_else(|| anyhow::anyhow!("At least one HTTP RPC URL is required"))
+    }
+
+    /// The open connection to a WebSocket endpoint, reconnecting if it was lost
+    ///
+    /// The connection map is not locked while connecting, so a slow endpoint does not hold up
+    /// the others; when two callers race, the connection opened first is kept.
+    pub async fn ws_client(&self, url: &str) -> Result<Arc<WsClient>> {
+        if let Some(client) = self.open_ws_client(url).await {
+            return Ok(client);
+        }
+        let client = tokio::time::timeout(WS_CONNECT_TIMEOUT, WsClient::connect(url))
+            .await
+            .map_err(|_| {
+                anyhow::anyhow!(
+                    "Connecting to {} timed out after {}s",
+                    url,
+                    WS_CONNECT_TIMEOUT.as_secs()
+                )
+            })??;
+        let mut ws_clients = self.ws_clients.lock().await;
+        if let Some(client) = ws_clients.get(url).filter(|client| !client.is_closed()) {
+            return Ok(client.clone());
+        }
+        let client = Arc::new(client);
+        ws_clients.insert(url.to_string(), client.clone());
+        Ok(client)
+    }
+
+    async fn open_ws_client(&self, url: &str) -> Option<Arc<WsClient>> {
+        let ws_clients = self.ws_clients.lock().await;
+        ws_clients
+            .get(url)
+            .filter(|client| !client.is_closed())
+            .cloned()
+    }
+
+    /// Pick the endpoint to send `tx_count` transactions to, waiting while every one is capped
+    ///
+    /// When no endpoint is healthy, all of them are used rather than stalling the run. Fails when
+    /// no endpoint has a weight above zero, as none could ever be picked.
+    pub async fn select(&self, tx_count: usize) -> Result<String> {
+        loop {
+            if let Some(url) = self.try_select(tx_count as u64)? {
+                return Ok(url);
+            }
+            sleep(Duration::from_millis(10)).await;
+        }
+    }
+
+    fn try_select(&self, tx_count: u64) -> Result<Option<String>> {
+        let mut endpoints = self.endpoints.lock().unwrap();
+        if !endpoints.iter().any(|endpoint| endpoint.weight > 0.0) {
+            return Err(anyhow::anyhow!(
+                "No RPC endpoint has a weight above 0 to send transactions to"
+            ));
+        }
+        let any_healthy = endpoints.iter().any(|endpoint| endpoint.health.healthy);
+        let candidates = endpoints
+            .iter_mut()
+            .enumerate()
+            .filter_map(|(i, endpoint)| {
+                let eligible = (endpoint.health.healthy || !any_healthy)
+                    && endpoint.weight > 0.0
+                    && endpoint.has_capacity(tx_count);
+                eligible.then_some(i)
+            })
+            .collect::<Vec<_>>();
+        if candidates.is_empty() {
+            return Ok(None);
+        }
+
+        let total_weight = candidates.iter().map(|i| endpoints[*i].weight).sum::<f64>();
+        for i in &candidates {
+            endpoints[*i].current_weight += endpoints[*i].weight;
+        }
+        let chosen = *candidates
+            .iter()
+            .max_by(|a, b| {
+                endpoints[**a]
+                    .current_weight
+                    .total_cmp(&endpoints[**b].current_weight)
+                    .then(b.cmp(a))
+            })
+            .unwrap();
+        let endpoint = &mut endpoints[chosen];
+        endpoint.current_weight -= total_weight;
+        endpoint.window.1 += tx_count;
+        Ok(Some(endpoint.health.url.clone()))
+    }
+
+    /// Record the outcome of a request sent to `url`, excluding it once it fails too often
+    pub fn record(&self, url: &str, success: bool) {
+        let mut endpoints = self.endpoints.lock().unwrap();
+        let Some(endpoint) = endpoints
+            .iter_mut()
+            .find(|endpoint| endpoint.health.url == url)
+        else {
+            return;
+        };
+        endpoint.record(success);
+        if endpoint.health.healthy && endpoint.health.error_rate > self.config.max_error_rate {
+            warn!(
+                "RPC {} is unhealthy: error rate {:.2}",
+                url, endpoint.health.error_rate
+            );
+            endpoint.health.healthy = false;
+        }
+    }
+
+    /// Probe every endpoint with `eth_blockNumber` and update which ones are healthy
+    pub async fn check_health(&self, http_client: &Client) {
+        let urls = self
+            .health()
+            .into_iter()
+            .map(|health| health.url)
+            .collect::<Vec<_>>();
+        let probes =
+            futures::future::join_all(urls.iter().map(|url| self.probe(http_client, url))).await;
+        let highest_block = probes
+            .iter()
+            .filter_map(|probe| probe.as_ref().ok().map(|(block, _)| *block))
+            .max();
+
+        let mut endpoints = self.endpoints.lock().unwrap();
+        for (endpoint, probe) in endpoints.iter_mut().zip(probes) {
+            endpoint.record(probe.is_ok());
+            let problem = match probe {
+                Ok((block, latency_ms)) => {
+                    endpoint.health.block_number = Some(block);
+                    endpoint.health.latency_ms = Some(latency_ms);
+                    let lag = highest_block.unwrap_or(block).saturating_sub(block);
+                    if lag > self.config.max_block_lag {
+                        Some(format!("{} blocks behind", lag))
+                    } else if latency_ms > self.config.max_latency_ms {
+                        Some(format!("latency {}ms", latency_ms))
+                    } else if endpoint.health.error_rate > self.config.max_error_rate {
+                        Some(format!("error rate {:.2}", endpoint.health.error_rate))
+                    } else {
+                        None
+                    }
+                }
+                Err(e) => Some(e.to_string()),
+            };
+            match problem {
+                Some(problem) => {
+                    if endpoint.health.healthy {
+                        warn!("RPC {} is unhealthy: {}", endpoint.health.url, problem);
+                    }
+                    endpoint.health.healthy = false;
+                }
+                None => {
+                    if !endpoint.health.healthy {
+                        info!("RPC {} is healthy again", endpoint.health.url);
+                    }
+                    endpoint.health.healthy = true;
+                }
+            }
+        }
+    }
+
+    /// Keep probing every endpoint in the background until the returned guard is dropped
+    pub fn spawn_health_checks(self: &Arc<Self>, http_client: Client) -> HealthChecks {
+        let pool = self.clone();
+        let interval = Duration::from_millis(self.config.health_check_interval_ms);
+        HealthChecks(tokio::spawn(async move {
+            loop {
+                sleep(interval).await;
+                pool.check_health(&http_client).await;
+            }
+        }))
+    }
+
+    /// Block number and latency in milliseconds reported by a single `eth_blockNumber` call
+    async fn probe(&self, http_client: &Client, url: &str) -> Result<(u64, u64)> {
+        let start = Instant::now();
+        let block = if is_ws_url(url) {
+            let client = self.ws_client(url).await?;
+            let request = client.request("eth_blockNumber", Value::Array(vec![]));
+            tokio::time::timeout(Duration::from_secs(3), request).await??
+        } else {
+            let request = JsonRpcRequest {
+                jsonrpc: "2.0".to_string(),
+                id: 0,
+                method: "eth_blockNumber".to_string(),
+                params: vec![],
+            };
+            http_client
+                .post(url)
+                .json(&request)
+                .timeout(Duration::from_secs(3))
+                .send()
+                .await?
+                .json::<Value>()
+                .await?["result"]
+                .clone()
+        };
+        let latency_ms = start.elapsed().as_millis() as u64;
+        let block = block
+            .as_str()
+            .and_then(|block| u64::from_str_radix(block.trim_start_matches("0x"), 16).ok())
+            .ok_or_else(|| anyhow::anyhow!("invalid eth_blockNumber result {}", block))?;
+        Ok((block, latency_ms))
+    }
+}
@@ -0,0 +1,49 @@
+use std::sync::atomic::{AtomicBool, Ordering};
+
+use crate::prelude::*;
+
+/// Set once the process is asked to stop, so distributors stop scheduling and drain
+#[derive(Debug, Clone, Default)]
+pub struct Shutdown(Arc<AtomicBool>);
+
+impl Shutdown {
+    pub fn trigger(&self) {
+        self.0.store(true, Ordering::SeqCst);
+    }
+
+    pub fn is_triggered(&self) -> bool {
+        self.0.load(Ordering::SeqCst)
+    }
+
+    /// Trigger on the first SIGINT or SIGTERM, and exit immediately on the second
+    pub fn listen_for_signals(&self) -> Result<()> {
+        #[cfg(unix)]
+        let mut terminate =
+            tokio::signal::unix::signal(tokio::signal::unix::SignalKind::terminate())?;
+        let shutdown = self.clone();
+        tokio::spawn(async move {
+            loop {
+                #[cfg(unix)]
+                let signal = tokio::select! {
+                    _ = tokio::signal::ctrl_c() => "SIGINT",
+                    _ = terminate.recv() => "SIGTERM",
+                };
+                #[cfg(not(unix))]
+                let signal = {
+                    let _ = tokio::signal::ctrl_c().await;
+                    "Ctrl+C"
+                };
+                if shutdown.is_triggered() {
+                    error!("Received {} again, exiting without draining", signal);
+                    std::process::exit(130);
+                }
+                warn!(
+                    "Received {}, finishing in-flight batches (send again to exit now)",
+                    signal
+                );
+                shutdown.trigger();
+            }
+        });
+        Ok(())
+    }
+}
@@ -0,0 +1,521 @@
+use std::collections::HashMap;
+use std::fmt;
+
+use reqwest::Url;
+
+use crate::addresses;
+use crate::allocation;
+use crate::overrides::{self, Override};
+use crate::prelude::*;
+
+/// A problem with a config file, pointing at the offending field
+#[derive(Debug, Clone, PartialEq)]
+pub struct ConfigError {
+    pub file: String,
+    /// 1-based line of the field, when it appears in the file
+    pub line: Option<usize>,
+    pub field: String,
+    pub message: String,
+}
+
+impl fmt::Display for ConfigError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.line {
+            Some(line) => write!(f, "{}:{}: ", self.file, line)?,
+            None => write!(f, "{}: ", self.file)?,
+        }
+        if !self.field.is_empty() {
+            write!(f, "{}: ", self.field)?;
+        }
+        write!(f, "{}", self.message)
+    }
+}
+
+/// A config with its overrides applied, ready to run
+#[derive(Debug)]
+pub struct LoadedConfig {
+    pub config: UnifiedConfig,
+    /// The file merged with its overrides, to be logged through [`overrides::redacted`]
+    pub effective: serde_yaml::Value,
+}
+
+/// Read, parse, override and check a config file, reporting every problem rather than the first
+///
+/// The scenario file of a `scenario` run is checked as well.
+pub fn load_config(
+    filename: &str,
+    overrides: &[Override],
+) -> Result<LoadedConfig, Vec<ConfigError>> {
+    let (config, contents, effective) = parse::<UnifiedConfig>(filename, overrides)?;
+    let mut validator = Validator::new(filename, &contents, overrides);
+    validator.core(&config.core);
+    validator.sections(
+        config.core.distribution_type,
+        (config.core.addresses_file.as_ref(), &config.addresses),
+        &config.sections,
+        "",
+    );
+    validator.rpc_pool(&config.rpc_pool, &config.core.rpc_urls);
+    if let Some(filters) = &config.filters {
+        validator.filters(filters);
+    }
+    let mut errors = validator.errors;
+
+    if let (DistributionType::Scenario, Some(scenario_file)) =
+        (config.core.distribution_type, &config.core.scenario_file)
+    {
+        match parse::<ScenarioConfig>(scenario_file, &[]) {
+            Ok((scenario, contents, _)) => errors.extend(validate_scenario(
+                scenario_file,
+                &contents,
+                &scenario,
+                &config.addresses,
+            )),
+            Err(scenario_errors) => errors.extend(scenario_errors),
+        }
+    }
+
+    if errors.is_empty() {
+        Ok(LoadedConfig { config, effective })
+    } else {
+        Err(errors)
+    }
+}
+
+fn parse<T>(
+    filename: &str,
+    overrides: &[Override],
+) -> Result<(T, String, serde_yaml::Value), Vec<ConfigError>>
+where
+    T: for<'a> Deserialize<'a>,
+{
+    let error = |line, message| {
+        vec![ConfigError {
+            file: filename.to_string(),
+            line,
+            field: String::new(),
+            message,
+        }]
+    };
+    let contents = std::fs::read_to_string(filename)
+        .map_err(|e| error(None, format!("Failed to read: {}", e)))?;
+    let located = |e: serde_yaml::Error| error(e.location().map(|l| l.line()), e.to_string());
+    let mut effective = serde_yaml::from_str(&contents).map_err(located)?;
+    overrides::apply(&mut effective, overrides).map_err(|e| error(None, e.to_string()))?;
+    let config = serde_yaml::from_value(effective.clone()).map_err(|e| {
+        // Only the file itself has line numbers, so prefer its error when it has one too
+        match serde_yaml::from_str::<T>(&contents) {
+            Err(file_error) => located(file_error),
+            Ok(_) => error(None, format!("After applying overrides: {}", e)),
+        }
+    })?;
+    Ok((config, contents, effective))
+}
+
+fn validate_scenario(
+    filename: &str,
+    contents: &str,
+    scenario: &ScenarioConfig,
+    addresses_config: &AddressesConfig,
+) -> Vec<ConfigError> {
+    let mut validator = Validator::new(filename, contents, &[]);
+    if scenario.workloads.is_empty() {
+        validator.error("workloads", "At least one workload must be defined");
+    }
+    // Each workload tracks its signers' nonces on its own, so no two may share a signer
+    let mut signer_workloads = HashMap::new();
+    for (i, workload) in scenario.workloads.iter().enumerate() {
+        let prefix = format!("workloads[{}].", i);
+        if let DistributionType::Scenario = workload.distribution_type {
+            validator.error(
+                &format!("{}distribution_type", prefix),
+                "Scenario workloads cannot be nested",
+            );
+        }
+        if workload.weight <= 0.0 {
+            validator.error(&format!("{}weight", prefix), "Must be greater than 0");
+        }
+        if workload.signer_keys.is_empty() {
+            validator.error(
+                &format!("{}signer_keys", prefix),
+                "At least one signer key must be specified",
+            );
+        }
+        for (j, signer_key) in workload.signer_keys.iter().enumerate() {
+            if let Some(other) = signer_workloads.insert(signer_key, &workload.name) {
+                validator.error(
+                    &format!("{}signer_keys[{}]", prefix, j),
+                    format!(
+                        "Signer key {} is already used by workload {}",
+                        signer_key, other
+                    ),
+                );
+            }
+        }
+        validator.sections(
+            workload.distribution_type,
+            (workload.addresses_file.as_ref(), addresses_config),
+            &workload.sections,
+            &prefix,
+        );
+    }
+    validator.errors
+}
+
+struct Validator<'a> {
+    file: &'a str,
+    contents: &'a str,
+    overrides: &'a [Override],
+    errors: Vec<ConfigError>,
+}
+
+impl<'a> Validator<'a> {
+    fn new(file: &'a str, contents: &'a str, overrides: &'a [Override]) -> Self {
+        Self {
+            file,
+            contents,
+            overrides,
+            errors: Vec::new(),
+        }
+    }
+
+    fn error(&mut self, field: &str, message: impl Into<String>) {
+        let mut message = message.into();
+        // An overridden field is not where the file says, so name the override instead
+        let overridden = self.overrides.iter().rev().find(|o| {
+            field == o.path
+                || field.starts_with(&format!("{}.", o.path))
+                || field.starts_with(&format!("{}[", o.path))
+        });
+        let line = match overridden {
+            Some(o) => {
+                message = format!("{} (set by {})", message, o.source);
+                None
+            }
+            None => locate(self.contents, field),
+        };
+        self.errors.push(ConfigError {
+            file: self.file.to_string(),
+            line,
+            field: field.to_string(),
+            message,
+        });
+    }
+
+    fn existing_file(&mut self, field: &str, path: &str) {
+        if !Path::new(path).is_file() {
+            self.error(field, format!("File {} does not exist", path));
+        }
+    }
+
+    fn core(&mut self, core: &CoreConfig) {
+        if core.rpc_urls.is_empty() {
+            self.error("core.rpc_urls", "At least one RPC URL must be specified");
+        } else if core.rpc_urls.iter().all(|url| is_ws_url(url)) {
+            self.error(
+                "core.rpc_urls",
+                "At least one HTTP RPC URL must be specified for chain reads",
+            );
+        }
+        for (i, url) in core.rpc_urls.iter().enumerate() {
+            if let Err(message) = check_url(url) {
+                self.error(&format!("core.rpc_urls[{}]", i), message);
+            }
+        }
+        if core.target_tps == 0 {
+            self.error("core.target_tps", "Must be greater than 0");
+        }
+        if core.rpc_batch_size == 0 {
+            self.error("core.rpc_batch_size", "Must be greater than 0");
+        }
+        match (core.distribution_type, &core.scenario_file) {
+            (DistributionType::Scenario, None) => self.error(
+                "core.scenario_file",
+                "Scenario file must be specified for distribution type Scenario",
+            ),
+            (DistributionType::Scenario, Some(scenario_file)) => {
+                self.existing_file("core.scenario_file", scenario_file)
+            }
+            _ => {}
+        }
+    }
+
+    fn sections(
+        &mut self,
+        distribution_type: DistributionType,
+        (addresses_file, addresses_config): (Option<&String>, &AddressesConfig),
+        sections: &BuilderSections,
+        prefix: &str,
+    ) {
+        let field = |name: &str| format!("{}{}", prefix, name);
+        let needs_addresses = match distribution_type {
+            DistributionType::NativeDirect
+            | DistributionType::NativeBatch
+            | DistributionType::NFTMint
+            | DistributionType::Merkle => true,
+            DistributionType::ContractCall => sections.contract_call.as_ref().is_some_and(|c| {
+                c.args
+                    .iter()
+                    .any(|arg| matches!(arg, ArgSource::Column { .. }))
+            }),
+            _ => false,
+        };
+        let addresses_field = if prefix.is_empty() {
+            "core.addresses_file".to_string()
+        } else {
+            field("addresses_file")
+        };
+        match addresses_file {
+            None if needs_addresses => self.error(
+                &addresses_field,
+                format!(
+                    "Addresses file must be specified for distribution type {:?}",
+                    distribution_type
+                ),
+            ),
+            Some(addresses_file) if needs_addresses => {
+                self.existing_file(&addresses_field, addresses_file)
+            }
+            _ => {}
+        }
+
+        let required = match distribution_type {
+            DistributionType::NativeBatch => Some(("token", sections.token.is_some())),
+            DistributionType::NFTMint => Some(("nft", sections.nft.is_some())),
+            DistributionType::Swapper => Some(("swapper", sections.swapper.is_some())),
+            DistributionType::UniswapV2 => Some(("uniswap_v2", sections.uniswap_v2.is_some())),
+            DistributionType::ContractCall => {
+                Some(("contract_call", sections.contract_call.is_some()))
+            }
+            DistributionType::StrictCounter => {
+                Some(("strict_counter", sections.strict_counter.is_some()))
+            }
+            DistributionType::Merkle | DistributionType::MerkleClaim => {
+                Some(("merkle", sections.merkle.is_some()))
+            }
+            _ => None,
+        };
+        if let Some((section, false)) = required {
+            self.error(
+                &field(section),
+                format!(
+                    "Section must be set for distribution type {:?}",
+                    distribution_type
+                ),
+            );
+        }
+
+        if let Some(token) = &sections.token {
+            if token.amount_per_address_low > token.amount_per_address_high {
+                self.error(
+                    &field("token.amount_per_address_low"),
+                    format!(
+                        "Low amount {} is above high amount {}",
+                        token.amount_per_address_low, token.amount_per_address_high
+                    ),
+                );
+            }
+            if let Some(AllocationConfig::Random { low, high, .. }) = &token.allocation {
+                if low > high {
+                    self.error(
+                        &field("token.allocation.low"),
+                        "Low amount is above high amount",
+                    );
+                }
+            }
+
+            // Recipients allocated nothing are skipped, so such a run would silently pay no one
+            let allocates = matches!(
+                distribution_type,
+                DistributionType::NativeDirect
+                    | DistributionType::NativeBatch
+                    | DistributionType::Merkle
+            );
+            let unpaid = addresses_file
+                .and_then(|file| addresses::read_recipients(file, addresses_config).ok())
+                .is_some_and(|recipients| {
+                    recipients
+                        .iter()
+                        .any(|recipient| recipient.amount.is_none())
+                });
+            if allocates && unpaid && allocation::pays_nothing(&token.allocation()) {
+                let allocation_field = if token.allocation.is_some() {
+                    "token.allocation"
+                } else {
+                    "token.amount_per_address_high"
+                };
+                self.error(
+                    &field(allocation_field),
+                    "Allocates nothing to the recipients without an amount in the addresses file",
+                );
+            }
+        }
+        if let Some(token) = &sections.token {
+            self.batch(&field("token.batch"), &token.batch);
+        }
+        if let Some(nft) = &sections.nft {
+            self.batch(&field("nft.batch"), &nft.batch);
+            if !nft.soulbound {
+                self.error(
+                    &field("nft.soulbound"),
+                    "Non-soulbound NFT minting is not implemented yet",
+                );
+            }
+        }
+        if let Some(swapper) = &sections.swapper {
+            if let SwapAmountConfig::Uniform { low, high } = swapper.amount {
+                if low > high {
+                    self.error(
+                        &field("swapper.amount.low"),
+                        format!("Low amount {} is above high amount {}", low, high),
+                    );
+                }
+            }
+            if let SwapDirectionConfig::Biased { a_to_b_probability } = swapper.direction {
+                if !(0.0..=1.0).contains(&a_to_b_probability) {
+                    self.error(
+                        &field("swapper.direction.a_to_b_probability"),
+                        "Must be between 0 and 1",
+                    );
+                }
+            }
+        }
+        if let Some(uniswap) = &sections.uniswap_v2 {
+            if uniswap.path.len() < 2 {
+                self.error(
+                    &field("uniswap_v2.path"),
+                    "Path must contain at least two tokens",
+                );
+            }
+        }
+        if let Some(call) = &sections.contract_call {
+            if let Err(e) = alloy::json_abi::Function::parse(&call.function_signature) {
+                self.error(
+                    &field("contract_call.function_signature"),
+                    format!("Invalid function signature: {}", e),
+                );
+            }
+            for (i, arg) in call.args.iter().enumerate() {
+                if let ArgSource::Random { low, high } = arg {
+                    if low > high {
+                        self.error(
+                            &field(&format!("contract_call.args[{}].low", i)),
+                            format!("Low value {} is above high value {}", low, high),
+                        );
+                    }
+                }
+            }
+        }
+        if let (DistributionType::MerkleClaim, Some(merkle)) = (distribution_type, &sections.merkle)
+        {
+            if merkle.distributor_address.is_none() {
+                self.error(
+                    &field("merkle.distributor_address"),
+                    "A distributor address must be set to claim from it",
+                );
+            }
+            self.existing_file(&field("merkle.proofs_file"), &merkle.proofs_file);
+        }
+    }
+
+    fn batch(&mut self, field: &str, batch: &BatchConfig) {
+        if batch.size == Some(0) {
+            self.error(&format!("{}.size", field), "Must be greater than 0");
+        }
+        if batch.concurrency == 0 {
+            self.error(&format!("{}.concurrency", field), "Must be greater than 0");
+        }
+        if batch.target_gas == Some(0) {
+            self.error(&format!("{}.target_gas", field), "Must be greater than 0");
+        }
+        if let Some(fraction) = batch.block_gas_fraction {
+            if batch.target_gas.is_some() {
+                self.error(
+                    &format!("{}.block_gas_fraction", field),
+                    "Cannot be set together with target_gas",
+                );
+            } else if !(fraction > 0.0 && fraction <= 1.0) {
+                self.error(
+                    &format!("{}.block_gas_fraction", field),
+                    "Must be above 0 and at most 1",
+                );
+            }
+        }
+    }
+
+    fn rpc_pool(&mut self, rpc_pool: &RpcPoolConfig, rpc_urls: &[String]) {
+        for (i, endpoint) in rpc_pool.endpoints.iter().enumerate() {
+            if !rpc_urls.contains(&endpoint.url) {
+                self.error(
+                    &format!("rpc_pool.endpoints[{}].url", i),
+                    format!("{} is not one of core.rpc_urls", endpoint.url),
+                );
+            }
+            if endpoint.weight <= 0.0 {
+                self.error(
+                    &format!("rpc_pool.endpoints[{}].weight", i),
+                    "Must be greater than 0",
+                );
+            }
+        }
+    }
+
+    fn filters(&mut self, filters: &FilterConfig) {
+        if let (Some(min), Some(max)) = (filters.min_balance, filters.max_balance) {
+            if min > max {
+                self.error(
+                    "filters.min_balance",
+                    format!("Minimum balance {} is above maximum balance {}", min, max),
+                );
+            }
+        }
+        for (i, exclude_file) in filters.exclude_files.iter().enumerate() {
+            self.existing_file(&format!("filters.exclude_files[{}]", i), exclude_file);
+        }
+    }
+}
+
+fn check_url(url: &str) -> std::result::Result<(), String> {
+    let parsed = Url::parse(url).map_err(|e| format!("Malformed URL {}: {}", url, e))?;
+    match parsed.scheme() {
+        "http" | "https" | "ws" | "wss" => Ok(()),
+        scheme => Err(format!("Unsupported URL scheme {} in {}", scheme, url)),
+    }
+}
+
+/// Line of the key at `field`, or of its deepest enclosing key present in the file
+///
+/// List indices are ignored, so a field inside a list points at the first item's key.
+fn locate(contents: &str, field: &str) -> Option<usize> {
+    let lines = contents.lines().collect::<Vec<_>>();
+    let mut found = None;
+    let mut start = 0;
+    let mut parent_indent = None;
+    for key in field.split('.').map(|key| key.split('[').next().unwrap()) {
+        let mut matched = false;
+        for (i, line) in lines.iter().enumerate().skip(start) {
+            let trimmed = line.trim_start();
+            let indent = line.len() - trimmed.len();
+            let trimmed = trimmed.trim_start_matches("- ");
+            if trimmed.is_empty() || trimmed.starts_with('#') {
+                continue;
+            }
+            // Leaving the parent's block means the key is not there
+            if i > start && parent_indent.is_some_and(|parent| indent <= parent) {
+                break;
+            }
+            if trimmed.starts_with(&format!("{}:", key)) && (parent_indent.is_some() || indent == 0)
+            {
+                found = Some(i + 1);
+                start = i;
+                parent_indent = Some(indent);
+                matched = true;
+                break;
+            }
+        }
+        if !matched {
+            break;
+        }
+    }
+    found
+}
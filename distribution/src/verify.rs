@@ -0,0 +1,209 @@
+use std::collections::HashMap;
+use std::fmt;
+
+use alloy::consensus::Transaction;
+use alloy::sol_types::SolEvent;
+
+use crate::builders::native::BatchSender;
+use crate::builders::nft::SoulboundMinter;
+use crate::journal::JournalEntry;
+use crate::prelude::*;
+
+/// What a recipient should have received, in wei for native amounts
+#[derive(Debug, Clone, Copy)]
+pub enum ExpectedAmount {
+    /// Exactly this amount
+    Exact(U256),
+    /// A random amount drawn from this inclusive range
+    Range(U256, U256),
+    /// A single minted token
+    Token,
+}
+
+/// Something a recipient received from a mined transaction
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum Received {
+    Native { amount: U256 },
+    Token { contract: Address, token_id: U256 },
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct Delivery {
+    pub recipient: Address,
+    pub hash: TxHash,
+    pub received: Received,
+}
+
+/// A mismatch between the addresses file and what was mined
+#[derive(Debug, Clone, Serialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum Discrepancy {
+    /// A reported transaction is unknown to the node
+    NotMined { hash: TxHash },
+    /// A reported transaction was mined but reverted
+    Reverted { hash: TxHash },
+    /// A recipient received nothing
+    Missing { recipient: Address },
+    /// A recipient was paid more than once
+    Duplicate {
+        recipient: Address,
+        hashes: Vec<TxHash>,
+    },
+    /// A recipient received a different amount or asset than intended
+    WrongAmount {
+        recipient: Address,
+        hash: TxHash,
+        expected: String,
+        received: Received,
+    },
+    /// An address outside the addresses file received something
+    Unexpected { recipient: Address, hash: TxHash },
+}
+
+impl fmt::Display for Discrepancy {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Discrepancy::NotMined { hash } => write!(f, "{} was not mined", hash),
+            Discrepancy::Reverted { hash } => write!(f, "{} reverted", hash),
+            Discrepancy::Missing { recipient } => write!(f, "{} received nothing", recipient),
+            Discrepancy::Duplicate { recipient, hashes } => {
+                write!(f, "{} was paid {} times", recipient, hashes.len())
+            }
+            Discrepancy::WrongAmount {
+                recipient,
+                hash,
+                expected,
+                received,
+            } => write!(
+                f,
+                "{} expected {} but received {:?} in {}",
+                recipient, expected, received, hash
+            ),
+            Discrepancy::Unexpected { recipient, hash } => {
+                write!(
+                    f,
+                    "{} is not in the addresses file but was paid in {}",
+                    recipient, hash
+                )
+            }
+        }
+    }
+}
+
+/// Decode what every reported transaction delivered, from its calldata and `Transfer` logs
+pub async fn mined_deliveries(
+    provider: &dyn Provider,
+    entries: &[JournalEntry],
+) -> Result<(Vec<Delivery>, Vec<Discrepancy>)> {
+    let mut deliveries = Vec::new();
+    let mut discrepancies = Vec::new();
+    for entry in entries {
+        let (Some(tx), Some(receipt)) = (
+            provider.get_transaction_by_hash(entry.hash).await?,
+            provider.get_transaction_receipt(entry.hash).await?,
+        ) else {
+            discrepancies.push(Discrepancy::NotMined { hash: entry.hash });
+            continue;
+        };
+        if !receipt.status() {
+            discrepancies.push(Discrepancy::Reverted { hash: entry.hash });
+            continue;
+        }
+
+        let mut deliver = |recipient, received| {
+            deliveries.push(Delivery {
+                recipient,
+                hash: entry.hash,
+                received,
+            })
+        };
+        if let Ok(call) = BatchSender::batchSendCall::abi_decode(tx.input(), true) {
+            for (recipient, amount) in call.recipients.into_iter().zip(call.amounts) {
+                deliver(recipient, Received::Native { amount });
+            }
+        } else if tx.input().is_empty() {
+            if let Some(to) = tx.to() {
+                deliver(to, Received::Native { amount: tx.value() });
+            }
+        } else {
+            // Only mints count, so transfers between holders are ignored
+            for log in receipt.inner.logs() {
+                if let Ok(transfer) = SoulboundMinter::Transfer::decode_log(&log.inner, true) {
+                    if transfer.from == Address::ZERO {
+                        deliver(
+                            transfer.to,
+                            Received::Token {
+                                contract: log.address(),
+                                token_id: transfer.tokenId,
+                            },
+                        );
+                    }
+                }
+            }
+        }
+    }
+    Ok((deliveries, discrepancies))
+}
+
+/// Compare deliveries against what each recipient should have received
+pub fn compare(
+    expected: &[(Address, ExpectedAmount)],
+    deliveries: &[Delivery],
+) -> Vec<Discrepancy> {
+    let mut by_recipient: HashMap<Address, Vec<&Delivery>> = HashMap::new();
+    for delivery in deliveries {
+        by_recipient
+            .entry(delivery.recipient)
+            .or_default()
+            .push(delivery);
+    }
+
+    let mut discrepancies = Vec::new();
+    for (recipient, amount) in expected {
+        let received = by_recipient.remove(recipient).unwrap_or_default();
+        let delivery = match received.as_slice() {
+            [] => {
+                discrepancies.push(Discrepancy::Missing {
+                    recipient: *recipient,
+                });
+                continue;
+            }
+            [delivery] => delivery,
+            _ => {
+                discrepancies.push(Discrepancy::Duplicate {
+                    recipient: *recipient,
+                    hashes: received.iter().map(|delivery| delivery.hash).collect(),
+                });
+                continue;
+            }
+        };
+        let matches = match (amount, delivery.received) {
+            (ExpectedAmount::Exact(value), Received::Native { amount }) => amount == *value,
+            (ExpectedAmount::Range(low, high), Received::Native { amount }) => {
+                amount >= *low && amount <= *high
+            }
+            (ExpectedAmount::Token, Received::Token { .. }) => true,
+            _ => false,
+        };
+        if !matches {
+            discrepancies.push(Discrepancy::WrongAmount {
+                recipient: *recipient,
+                hash: delivery.hash,
+                expected: format!("{:?}", amount),
+                received: delivery.received,
+            });
+        }
+    }
+
+    // Whatever is left was not in the addresses file
+    let mut unexpected = by_recipient.into_values().flatten().collect::<Vec<_>>();
+    unexpected.sort_by_key(|delivery| (delivery.hash, delivery.recipient));
+    for delivery in unexpected {
+        discrepancies.push(Discrepancy::Unexpected {
+            recipient: delivery.recipient,
+            hash: delivery.hash,
+        });
+    }
+    discrepancies
+}
@@ -0,0 +1,250 @@
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Mutex;
+
+use futures::{SinkExt, StreamExt};
+use serde_json::json;
+use tokio::sync::{mpsc, oneshot};
+use tokio_tungstenite::tungstenite::Message;
+
+use crate::prelude::*;
+
+/// Whether `url` is served over WebSocket rather than HTTP
+pub fn is_ws_url(url: &str) -> bool {
+    url.starts_with("ws://") || url.starts_with("wss://")
+}
+
+/// A request waiting for its response
+struct Pending {
+    response: oneshot::Sender<Value>,
+    /// Where notifications go when the request is an `eth_subscribe`
+    subscription: Option<mpsc::UnboundedSender<Value>>,
+}
+
+#[derive(Default)]
+struct Routes {
+    pending: HashMap<u64, Pending>,
+    subscriptions: HashMap<String, mpsc::UnboundedSender<Value>>,
+    closed: bool,
+}
+
+impl Routes {
+    fn dispatch(&mut self, message: Value) {
+        if let Value::Array(messages) = message {
+            for message in messages {
+                self.dispatch(message);
+            }
+            return;
+        }
+        if message["method"] == "eth_subscription" {
+            let params = &message["params"];
+            if let Some(subscription) = params["subscription"]
+                .as_str()
+                .and_then(|id| self.subscriptions.get(id))
+            {
+                let _ = subscription.send(params["result"].clone());
+            }
+            return;
+        }
+        let Some(pending) = message["id"]
+            .as_u64()
+            .and_then(|id| self.pending.remove(&id))
+        else {
+            return;
+        };
+        // Register before answering, so no notification arrives for an unknown subscription
+        if let (Some(subscription), Some(id)) = (pending.subscription, message["result"].as_str()) {
+            self.subscriptions.insert(id.to_string(), subscription);
+        }
+        let _ = pending.response.send(message);
+    }
+}
+
+/// A persistent JSON-RPC connection over WebSocket
+///
+/// Requests are pipelined: each is written as soon as it is made and matched to its response by
+/// id, so any number can be in flight on the one connection.
+pub struct WsClient {
+    pub url: String,
+    next_id: AtomicU64,
+    outgoing: mpsc::UnboundedSender<Message>,
+    routes: Arc<Mutex<Routes>>,
+    tasks: [tokio::task::JoinHandle<()>; 2],
+}
+
+impl Drop for WsClient {
+    fn drop(&mut self) {
+        for task in &self.tasks {
+            task.abort();
+        }
+    }
+}
+
+impl WsClient {
+    pub async fn connect(url: &str) -> Result<Self> {
+        let (stream, _) = tokio_tungstenite::connect_async(url).await?;
+        let (mut sink, mut source) = stream.split();
+        let (outgoing, mut queued) = mpsc::unbounded_channel::<Message>();
+        let routes = Arc::new(Mutex::new(Routes::default()));
+
+        let writer = tokio::spawn(async move {
+            while let Some(message) = queued.recv().await {
+                if sink.send(message).await.is_err() {
+                    break;
+                }
+            }
+        });
+        let reader_routes = routes.clone();
+        let reader_url = url.to_string();
+        let reader = tokio::spawn(async move {
+            while let Some(message) = source.next().await {
+                let text = match message {
+                    Ok(Message::Text(text)) => text,
+                    Ok(Message::Close(_)) => break,
+                    Ok(_) => continue,
+                    Err(e) => {
+                        error!("URL {} WebSocket error: {}", reader_url, e);
+                        break;
+                    }
+                };
+                match serde_json::from_str::<Value>(&text) {
+                    Ok(message) => reader_routes.lock().unwrap().dispatch(message),
+                    Err(e) => error!(
+                        "URL {} Failed to parse message: {} {:?}",
+                        reader_url, text, e
+                    ),
+                }
+            }
+            // Dropping every sender fails waiting requests and ends subscriptions
+            let mut routes = reader_routes.lock().unwrap();
+            routes.closed = true;
+            routes.pending.clear();
+            routes.subscriptions.clear();
+        });
+
+        Ok(Self {
+            url: url.to_string(),
+            next_id: AtomicU64::new(1),
+            outgoing,
+            routes,
+            tasks: [writer, reader],
+        })
+    }
+
+    /// Whether the connection has been lost, after which every request fails
+    pub fn is_closed(&self) -> bool {
+        self.routes.lock().unwrap().closed
+    }
+
+    /// Write a request under a fresh id, returning its response object
+    async fn send(
+        &self,
+        mut request: Value,
+        subscription: Option<mpsc::UnboundedSender<Value>>,
+    ) -> Result<Value> {
+        let id = self.next_id.fetch_add(1, Ordering::Relaxed);
+        request["id"] = json!(id);
+        let (response, receiver) = oneshot::channel();
+        {
+            let mut routes = self.routes.lock().unwrap();
+            if routes.closed {
+                return Err(anyhow::anyhow!("WebSocket {} is closed", self.url));
+            }
+            routes.pending.insert(
+                id,
+                Pending {
+                    response,
+                    subscription,
+                },
+            );
+        }
+        self.outgoing
+            .send(Message::Text(request.to_string().into()))
+            .map_err(|_| anyhow::anyhow!("WebSocket {} is closed", self.url))?;
+        receiver
+            .await
+            .map_err(|_| anyhow::anyhow!("WebSocket {} closed before responding", self.url))
+    }
+
+    /// Send every request at once, returning their response objects with the original ids
+    pub async fn batch(&self, requests: &[JsonRpcRequest]) -> Result<Vec<Value>> {
+        let responses = futures::future::try_join_all(requests.iter().map(|request| async move {
+            let mut response = self.send(serde_json::to_value(request)?, None).await?;
+            response["id"] = json!(request.id);
+            Ok::<_, Error>(response)
+        }))
+        .await?;
+        Ok(responses)
+    }
+
+    /// Call a single method, returning its result
+    pub async fn request(&self, method: &str, params: Value) -> Result<Value> {
+        let request = json!({ "jsonrpc": "2.0", "method": method, "params": params });
+        let response = self.send(request, None).await?;
+        if let Some(error) = response.get("error") {
+            return Err(anyhow::anyhow!("{} failed: {}", method, error));
+        }
+        Ok(response["result"].clone())
+    }
+
+    /// Start an `eth_subscribe` subscription, returning the stream of its notifications
+    pub async fn subscribe(&self, params: Value) -> Result<mpsc::UnboundedReceiver<Value>> {
+        let (subscription, notifications) = mpsc::unbounded_channel();
+        let request = json!({ "jsonrpc": "2.0", "method": "eth_subscribe", "params": params });
+        let response = self.send(request, Some(subscription)).await?;
+        if let Some(error) = response.get("error") {
+            return Err(anyhow::anyhow!("eth_subscribe failed: {}", error));
+        }
+        Ok(notifications)
+    }
+
+    /// Headers of new blocks as they are produced
+    pub async fn subscribe_new_heads(&self) -> Result<mpsc::UnboundedReceiver<Value>> {
+        self.subscribe(json!(["newHeads"])).await
+    }
+
+    /// Hashes of transactions as they enter the node's pool
+    pub async fn subscribe_pending_transactions(&self) -> Result<mpsc::UnboundedReceiver<Value>> {
+        self.subscribe(json!(["newPendingTransactions"])).await
+    }
+}
+
+/// Wait for a receipt, checking again on every new block rather than polling
+pub async fn wait_for_transaction(
+    rpc_url: &str,
+    hash: TxHash,
+    timeout_seconds: u64,
+) -> Result<Option<u64>> {
+    info!("Waiting for transaction {} to be mined...", hash);
+    let client = WsClient::connect(rpc_url).await?;
+    let mut new_heads = client.subscribe_new_heads().await?;
+    let deadline = tokio::time::Instant::now() + Duration::from_secs(timeout_seconds);
+
+    loop {
+        let receipt = client
+            .request("eth_getTransactionReceipt", json!([hash]))
+            .await?;
+        if !receipt.is_null() {
+            if receipt["status"] == "0x1" {
+                info!("Transaction successfully mined!");
+            } else {
+                warn!(
+                    "Transaction mined but failed! Status: {:?}",
+                    receipt["status"]
+                );
+            }
+            let block_number = receipt["blockNumber"]
+                .as_str()
+                .and_then(|block| u64::from_str_radix(block.trim_start_matches("0x"), 16).ok());
+            return Ok(block_number);
+        }
+        match tokio::time::timeout_at(deadline, new_heads.recv()).await {
+            Ok(Some(_)) => {}
+            Ok(None) => return Err(anyhow::anyhow!("WebSocket {} closed", rpc_url)),
+            Err(_) => {
+                warn!("Timeout reached while waiting for transaction");
+                return Ok(None);
+            }
+        }
+    }
+}
@@ -0,0 +1,200 @@
+use distribution::addresses::{read_recipients, read_rows};
+use distribution::prelude::*;
+
+const A: &str = "0x4Bf3A122B5C47532c2E88F45A762ff62a6ae3957";
+const B: &str = "0xDC941b68bB711765F2d22a00012b03F7b6002840";
+const C: &str = "0xAA39d9f5A2F5507d8a6eaC3AF3B033176F37DafA";
+
+/// Write an addresses file to a temporary path
+fn addresses_file(name: &str, contents: &str) -> String {
+    let path = std::env::temp_dir().join(format!("{}-{}", std::process::id(), name));
+    std::fs::write(&path, contents).unwrap();
+    path.to_str().unwrap().to_string()
+}
+
+fn address(address: &str) -> Address {
+    address.parse().unwrap()
+}
+
+fn strict(duplicates: DuplicatePolicy) -> AddressesConfig {
+    AddressesConfig {
+        strict: true,
+        duplicates,
+        ..Default::default()
+    }
+}
+
+#[test]
+fn reads_simple_and_headerless_csv() {
+    let path = addresses_file("simple.txt", &format!("{}\n\n{},0.5\n", A, B));
+    let recipients = read_recipients(&path, &AddressesConfig::default()).unwrap();
+    assert_eq!(recipients.len(), 2);
+    assert_eq!(recipients[0].address, address(A));
+    assert_eq!(recipients[0].amount, None);
+    assert_eq!(recipients[1].address, address(B));
+    assert_eq!(
+        recipients[1].amount,
+        Some(U256::from(500_000_000_000_000_000u128))
+    );
+}
+
+#[test]
+fn reads_named_csv_columns_in_any_order() {
+    let path = addresses_file(
+        "named.csv",
+        &format!(
+            "label,amount_wei,Address,token_id,uri\n\
+             \"Team, ops\",123456789012345678901,{},7,ipfs://a\n\
+             ,1,{},,\n",
+            A, B
+        ),
+    );
+    let recipients = read_recipients(&path, &AddressesConfig::default()).unwrap();
+    assert_eq!(recipients.len(), 2);
+    assert_eq!(recipients[0].address, address(A));
+    assert_eq!(
+        recipients[0].amount,
+        Some("123456789012345678901".parse().unwrap())
+    );
+    assert_eq!(recipients[0].label.as_deref(), Some("Team, ops"));
+    assert_eq!(recipients[0].token_id, Some(U256::from(7)));
+    assert_eq!(recipients[0].uri.as_deref(), Some("ipfs://a"));
+    assert_eq!(recipients[1].amount, Some(U256::from(1)));
+    assert_eq!(recipients[1].label, None);
+}
+
+#[test]
+fn reads_json_and_jsonl() {
+    let json = addresses_file(
+        "recipients.json",
+        &format!(
+            r#"[{{"address": "{}", "amount": 0.25}}, {{"address": "{}", "amount_wei": "10"}}]"#,
+            A, B
+        ),
+    );
+    let jsonl = addresses_file(
+        "recipients.jsonl",
+        &format!(
+            "{{\"address\": \"{}\", \"amount\": \"0.25\"}}\n\n\
+             {{\"address\": \"{}\", \"amount_wei\": 10}}\n",
+            A, B
+        ),
+    );
+    let from_json = read_recipients(&json, &AddressesConfig::default()).unwrap();
+    let from_jsonl = read_recipients(&jsonl, &AddressesConfig::default()).unwrap();
+    assert_eq!(from_json, from_jsonl);
+    assert_eq!(
+        from_json[0].amount,
+        Some(U256::from(250_000_000_000_000_000u128))
+    );
+    assert_eq!(from_json[1].amount, Some(U256::from(10)));
+}
+
+#[test]
+fn lenient_mode_skips_invalid_rows() {
+    let path = addresses_file(
+        "lenient.csv",
+        &format!("{},abc\n0x1234\n{},1\n{},1\n", A, B, B),
+    );
+    let recipients = read_recipients(&path, &AddressesConfig::default()).unwrap();
+    assert_eq!(recipients.len(), 1);
+    assert_eq!(recipients[0].address, address(B));
+}
+
+#[test]
+fn strict_mode_reports_every_invalid_row_with_its_line() {
+    let path = addresses_file(
+        "strict.csv",
+        &format!(
+            "address,amount,amount_wei\n{},abc,\n0x1234,,\n{},1,5\n{},1,\n",
+            A, B, C
+        ),
+    );
+    let error = read_recipients(&path, &strict(DuplicatePolicy::Skip))
+        .unwrap_err()
+        .to_string();
+    assert!(error.contains("line 2: invalid amount abc"), "{}", error);
+    assert!(
+        error.contains("line 3: invalid address 0x1234"),
+        "{}",
+        error
+    );
+    assert!(
+        error.contains("line 4: both amount and amount_wei are set"),
+        "{}",
+        error
+    );
+    assert!(!error.contains("line 5"), "{}", error);
+}
+
+#[test]
+fn duplicate_policies() {
+    let path = addresses_file("duplicates.csv", &format!("{},1\n{},2\n{},0.5\n", A, B, A));
+
+    let skipped = read_recipients(&path, &strict(DuplicatePolicy::Skip)).unwrap();
+    assert_eq!(skipped.len(), 2);
+    assert_eq!(
+        skipped[0].amount,
+        Some(U256::from(1_000_000_000_000_000_000u128))
+    );
+
+    let summed = read_recipients(&path, &strict(DuplicatePolicy::Sum)).unwrap();
+    assert_eq!(summed.len(), 2);
+    assert_eq!(
+        summed[0].amount,
+        Some(U256::from(1_500_000_000_000_000_000u128))
+    );
+
+    let error = read_recipients(&path, &strict(DuplicatePolicy::Error))
+        .unwrap_err()
+        .to_string();
+    assert!(
+        error.contains(&format!(
+            "line 3: duplicate address {} (first on line 1)",
+            A
+        )),
+        "{}",
+        error
+    );
+}
+
+#[test]
+fn reads_rows_for_contract_call_columns() {
+    let csv = addresses_file(
+        "rows.csv",
+        &format!(
+            "label,address,note\n\"Team, ops\",{},first\nbad,0x1234,x\n\n,{},\"a, b\"\n",
+            A, B
+        ),
+    );
+    let rows = read_rows(&csv, &AddressesConfig::default()).unwrap();
+    assert_eq!(
+        rows,
+        vec![
+            vec![A.to_string(), "Team, ops".to_string(), "first".to_string()],
+            vec![B.to_string(), String::new(), "a, b".to_string()],
+        ]
+    );
+    let error = read_rows(&csv, &strict(DuplicatePolicy::Skip))
+        .unwrap_err()
+        .to_string();
+    assert!(
+        error.contains("line 3: invalid address 0x1234"),
+        "{}",
+        error
+    );
+
+    let headerless = addresses_file("rows.txt", &format!("{},1,extra\n", C));
+    let rows = read_rows(&headerless, &AddressesConfig::default()).unwrap();
+    assert_eq!(
+        rows,
+        vec![vec![C.to_string(), "1".to_string(), "extra".to_string()]]
+    );
+
+    let jsonl = addresses_file(
+        "rows.jsonl",
+        &format!("{{\"amount\": 2, \"address\": \"{}\"}}\n", A),
+    );
+    let rows = read_rows(&jsonl, &AddressesConfig::default()).unwrap();
+    assert_eq!(rows, vec![vec![A.to_string(), "2".to_string()]]);
+}
@@ -0,0 +1,106 @@
+mod common;
+
+use common::verify::test_recipients;
+use distribution::addresses::Recipient;
+use distribution::allocation::allocate;
+use distribution::prelude::*;
+
+const ETH: u128 = 1_000_000_000_000_000_000;
+
+fn weighted(weights: &[f64]) -> Vec<Recipient> {
+    test_recipients(weights.len())
+        .into_iter()
+        .zip(weights)
+        .map(|(address, weight)| Recipient {
+            address,
+            amount: None,
+            uri: None,
+            token_id: None,
+            label: None,
+            weight: Some(*weight),
+        })
+        .collect()
+}
+
+fn strategy(yaml: &str) -> AllocationConfig {
+    serde_yaml::from_str(yaml).unwrap()
+}
+
+#[test]
+fn even_split_adds_up_to_the_exact_budget() {
+    let amounts = allocate(&strategy("strategy: even\ntotal: 1"), &weighted(&[0.0; 7])).unwrap();
+    assert_eq!(amounts.iter().sum::<U256>(), U256::from(ETH));
+    let min = amounts.iter().min().unwrap();
+    let max = amounts.iter().max().unwrap();
+    assert!(*max - *min <= U256::from(1));
+}
+
+#[test]
+fn pro_rata_split_follows_weights_exactly() {
+    let budget = strategy("strategy: pro_rata\ntotal: \"100.000000000000000001\"");
+    let amounts = allocate(&budget, &weighted(&[1.0, 2.0, 3.0, 0.5])).unwrap();
+    assert_eq!(amounts.iter().sum::<U256>(), U256::from(100 * ETH + 1));
+    assert!(amounts[1] > amounts[0] && amounts[2] > amounts[1]);
+    // Shares are exact up to the single wei of rounding each may receive
+    assert!((amounts[3] * U256::from(2)).abs_diff(amounts[0]) <= U256::from(2));
+
+    let missing_weight = Recipient {
+        weight: None,
+        ..weighted(&[1.0])[0].clone()
+    };
+    assert!(allocate(&budget, &[missing_weight]).is_err());
+}
+
+#[test]
+fn capped_pro_rata_redistributes_the_excess() {
+    let capped = strategy("strategy: capped_pro_rata\ntotal: 10\ncap: 4");
+    let amounts = allocate(&capped, &weighted(&[1.0, 1.0, 8.0])).unwrap();
+    assert_eq!(
+        amounts,
+        vec![
+            U256::from(3 * ETH),
+            U256::from(3 * ETH),
+            U256::from(4 * ETH)
+        ]
+    );
+
+    // Capping one recipient can push another over the cap
+    let amounts = allocate(&capped, &weighted(&[1.0, 3.0, 6.0, 0.0])).unwrap();
+    assert_eq!(amounts.iter().sum::<U256>(), U256::from(10 * ETH));
+    assert!(amounts.iter().all(|amount| *amount <= U256::from(4 * ETH)));
+    assert_eq!(amounts[2], U256::from(4 * ETH));
+
+    let too_small = strategy("strategy: capped_pro_rata\ntotal: 10\ncap: 3");
+    assert!(allocate(&too_small, &weighted(&[1.0, 1.0, 1.0])).is_err());
+}
+
+#[test]
+fn tiers_pay_the_highest_tier_reached() {
+    let tiered = strategy(
+        "strategy: tiered\ntiers:\n  - { min_weight: 10, amount: 1 }\n  - { min_weight: 100, amount: 5 }\n",
+    );
+    let amounts = allocate(&tiered, &weighted(&[5.0, 10.0, 99.9, 100.0])).unwrap();
+    assert_eq!(
+        amounts,
+        vec![
+            U256::ZERO,
+            U256::from(ETH),
+            U256::from(ETH),
+            U256::from(5 * ETH)
+        ]
+    );
+}
+
+#[test]
+fn seeded_random_allocations_are_reproducible() {
+    let seeded = strategy("strategy: random\nlow: 0.1\nhigh: 0.2\nseed: 42");
+    let recipients = weighted(&[0.0; 50]);
+    let amounts = allocate(&seeded, &recipients).unwrap();
+    assert_eq!(amounts, allocate(&seeded, &recipients).unwrap());
+    assert!(amounts
+        .iter()
+        .all(|amount| *amount >= U256::from(ETH / 10) && *amount <= U256::from(ETH / 5)));
+
+    let reseeded = strategy("strategy: random\nlow: 0.1\nhigh: 0.2\nseed: 43");
+    assert_ne!(amounts, allocate(&reseeded, &recipients).unwrap());
+}
@@ -0,0 +1,812 @@
+mod common;
+
+use std::sync::Mutex;
+
+use alloy::dyn_abi::DynSolValue;
+use alloy::dyn_abi::JsonAbiExt;
+use alloy::json_abi::Function;
+use alloy::sol_types::SolValue;
+use common::mock_rpc::MockRpc;
+use common::verify::{test_recipients, verify_transactions, Expected};
+use distribution::builders::batching::plan_batches;
+use distribution::builders::contract_call::ContractCallBuilder;
+use distribution::builders::mixed::{MixedBuilder, WeightedBuilder};
+use distribution::builders::native::{
+    BatchSender, NativeBatchSenderBuilder, NativeTransferBuilder,
+};
+use distribution::builders::nft::{SoulboundMinter, SoulboundNFTMintBuilder};
+use distribution::builders::strict_counter::{StrictCounter, StrictCounterBuilder};
+use distribution::builders::swapper::{Swapper, SwapperBuilder, UniswapV2Pair};
+use distribution::builders::uniswap_v2::{
+    UniswapV2Builder, UniswapV2Factory, UniswapV2Router02, ERC20,
+};
+use distribution::prelude::*;
+
+const ETH: u128 = 1_000_000_000_000_000_000;
+
+fn contract() -> Address {
+    Address::with_last_byte(0xcc)
+}
+
+#[tokio::test]
+async fn native_transfer_builder_pays_each_recipient_directly() {
+    let mock = MockRpc::start().await;
+    let signer = PrivateKeySigner::random();
+    mock.set_nonce(signer.address(), 4);
+    let recipients = test_recipients(20);
+
+    let builder = NativeTransferBuilder {
+        signer: signer.clone(),
+        provider: mock.provider(),
+        recipients: recipients.clone(),
+        amounts: vec![U256::from(ETH / 2); 10]
+            .into_iter()
+            .chain(vec![U256::from(5 * ETH / 4); 10])
+            .collect(),
+    };
+    let txs = builder.build_transactions(None).await.unwrap();
+
+    let expected = Expected::new(signer.address(), 4)
+        .total_value(U256::from(175 * ETH / 10))
+        .recipients(20);
+    let decoded = verify_transactions(&txs, &expected);
+    for (i, tx) in decoded.iter().enumerate() {
+        assert_eq!(tx.to, Some(recipients[i]));
+        assert_eq!(tx.gas_limit, 21_000);
+        assert!(tx.input.is_empty());
+    }
+}
+
+#[tokio::test]
+async fn native_transfer_builder_honors_nonce_override() {
+    let mock = MockRpc::start().await;
+    let signer = PrivateKeySigner::random();
+
+    let builder = NativeTransferBuilder {
+        signer: signer.clone(),
+        provider: mock.provider(),
+        recipients: test_recipients(3),
+        amounts: vec![U256::from(ETH); 3],
+    };
+    let txs = builder.build_transactions(Some(42)).await.unwrap();
+
+    let expected = Expected::new(signer.address(), 42)
+        .total_value(U256::from(3 * ETH))
+        .recipients(3);
+    verify_transactions(&txs, &expected);
+}
+
+#[tokio::test]
+async fn native_batch_builder_encodes_batch_send_calls() {
+    let mock = MockRpc::start().await;
+    let signer = PrivateKeySigner::random();
+    mock.set_gas_estimate(1_000_000);
+    let recipients = test_recipients(2_500);
+
+    let builder = NativeBatchSenderBuilder {
+        signer: signer.clone(),
+        provider: mock.provider(),
+        contract_address: contract(),
+        recipients: recipients.clone(),
+        amounts: vec![U256::from(ETH / 4); 2_500],
+        batch: BatchConfig::default(),
+        estimate_providers: vec![],
+    };
+    let txs = builder.build_transactions(None).await.unwrap();
+    assert_eq!(txs.len(), 3);
+
+    let expected = Expected::new(signer.address(), 0)
+        .total_value(U256::from(625 * ETH))
+        .recipients(2_500);
+    let decoded = verify_transactions(&txs, &expected);
+
+    let mut decoded_recipients = Vec::new();
+    for tx in &decoded {
+        assert_eq!(tx.to, Some(contract()));
+        assert_eq!(tx.gas_limit, 1_100_000);
+        let call = BatchSender::batchSendCall::abi_decode(&tx.input, true).unwrap();
+        assert_eq!(call.recipients.len(), call.amounts.len());
+        assert_eq!(call.amounts.iter().sum::<U256>(), tx.value);
+        assert!(call
+            .amounts
+            .iter()
+            .all(|amount| *amount == U256::from(ETH / 4)));
+        decoded_recipients.extend(call.recipients);
+    }
+    assert_eq!(decoded_recipients, recipients);
+}
+
+#[tokio::test]
+async fn soulbound_mint_builder_encodes_batch_mint_calls() {
+    let mock = MockRpc::start().await;
+    let signer = PrivateKeySigner::random();
+    mock.set_nonce(signer.address(), 9);
+    let recipients = test_recipients(1_200);
+
+    let builder = SoulboundNFTMintBuilder {
+        signer: signer.clone(),
+        provider: mock.provider(),
+        contract_address: contract(),
+        recipients: recipients.clone(),
+        image_url: "https://example.com/nft.json".to_string(),
+        batch: BatchConfig::default(),
+        estimate_providers: vec![],
+    };
+    let txs = builder.build_transactions(None).await.unwrap();
+    assert_eq!(txs.len(), 3);
+
+    let expected = Expected::new(signer.address(), 9).recipients(1_200);
+    let decoded = verify_transactions(&txs, &expected);
+
+    let mut decoded_recipients = Vec::new();
+    for tx in &decoded {
+        let call = SoulboundMinter::batchMintCall::abi_decode(&tx.input, true).unwrap();
+        assert_eq!(call.uri, "https://example.com/nft.json");
+        decoded_recipients.extend(call.recipients);
+    }
+    assert_eq!(decoded_recipients, recipients);
+}
+
+#[tokio::test]
+async fn native_batch_builder_uses_the_configured_batch_size() {
+    let mock = MockRpc::start().await;
+    let signer = PrivateKeySigner::random();
+    let builder = NativeBatchSenderBuilder {
+        signer: signer.clone(),
+        provider: mock.provider(),
+        contract_address: contract(),
+        recipients: test_recipients(1_000),
+        amounts: vec![U256::from(ETH / 4); 1_000],
+        batch: BatchConfig {
+            size: Some(300),
+            ..Default::default()
+        },
+        estimate_providers: vec![],
+    };
+    let txs = builder.build_transactions(None).await.unwrap();
+    let sizes = txs.iter().map(|tx| tx.recipients.len()).collect::<Vec<_>>();
+    assert_eq!(sizes, vec![300, 300, 300, 100]);
+    let decoded = verify_transactions(
+        &txs,
+        &Expected::new(signer.address(), 0)
+            .total_value(U256::from(250 * ETH))
+            .recipients(1_000),
+    );
+
+    // Each transaction's range names exactly the recipients its calldata pays
+    for (tx, decoded) in txs.iter().zip(&decoded) {
+        let call = BatchSender::batchSendCall::abi_decode(&decoded.input, true).unwrap();
+        assert_eq!(call.recipients, builder.recipients[tx.recipients.clone()]);
+    }
+}
+
+#[tokio::test]
+async fn soulbound_mint_builder_packs_batches_under_a_block_gas_fraction() {
+    let mock = MockRpc::start().await;
+    let signer = PrivateKeySigner::random();
+    mock.set_gas_estimate(21_000);
+    mock.set_gas_per_input_byte(100);
+    let recipients = test_recipients(1_200);
+
+    // 1% of the mock's 30M block gas limit leaves room for 77 recipients per call
+    let builder = SoulboundNFTMintBuilder {
+        signer: signer.clone(),
+        provider: mock.provider(),
+        contract_address: contract(),
+        recipients: recipients.clone(),
+        image_url: "https://example.com/nft.json".to_string(),
+        batch: BatchConfig {
+            block_gas_fraction: Some(0.01),
+            ..Default::default()
+        },
+        estimate_providers: vec![],
+    };
+    let txs = builder.build_transactions(None).await.unwrap();
+    assert_eq!(txs.len(), 16);
+    assert!(txs.iter().all(|tx| tx.gas_limit <= 300_000));
+    assert!(txs[..15].iter().all(|tx| tx.recipients.len() == 77));
+
+    let decoded = verify_transactions(&txs, &Expected::new(signer.address(), 0).recipients(1_200));
+    let decoded_recipients = decoded
+        .iter()
+        .flat_map(|tx| {
+            SoulboundMinter::batchMintCall::abi_decode(&tx.input, true)
+                .unwrap()
+                .recipients
+        })
+        .collect::<Vec<_>>();
+    assert_eq!(decoded_recipients, recipients);
+}
+
+#[tokio::test]
+async fn batch_planning_shrinks_batches_the_model_overestimates() {
+    let mock = MockRpc::start().await;
+    // Costs jump past 40 recipients, which a linear model from two samples cannot see
+    let estimate = |recipients: std::ops::Range<usize>| async move {
+        let n = recipients.len() as u64;
+        Ok(1_000 + 100 * n + if n > 40 { 5_000 } else { 0 })
+    };
+    let config = BatchConfig {
+        size: Some(200),
+        target_gas: Some(20_000),
+        ..Default::default()
+    };
+    let batches = plan_batches(&*mock.provider(), &config, 1000, 300, estimate)
+        .await
+        .unwrap();
+    let planned = batches
+        .iter()
+        .map(|batch| (batch.recipients.clone(), batch.gas_limit))
+        .collect::<Vec<_>>();
+    assert_eq!(
+        planned,
+        vec![(0..140, 20_000), (140..280, 20_000), (280..300, 3_000)]
+    );
+
+    // Without a target every batch has the configured size
+    let config = BatchConfig {
+        size: Some(200),
+        ..Default::default()
+    };
+    let batches = plan_batches(&*mock.provider(), &config, 1000, 300, estimate)
+        .await
+        .unwrap();
+    assert_eq!(batches.len(), 2);
+    assert_eq!(batches[1].recipients, 200..300);
+}
+
+#[tokio::test]
+async fn native_batch_builder_spreads_gas_estimates_over_every_endpoint() {
+    let (mock, other) = (MockRpc::start().await, MockRpc::start().await);
+    let signer = PrivateKeySigner::random();
+    let builder = NativeBatchSenderBuilder {
+        signer: signer.clone(),
+        provider: mock.provider(),
+        contract_address: contract(),
+        recipients: test_recipients(1_000),
+        amounts: vec![U256::from(ETH / 4); 1_000],
+        batch: BatchConfig {
+            size: Some(100),
+            concurrency: 4,
+            ..Default::default()
+        },
+        estimate_providers: vec![mock.provider(), other.provider()],
+    };
+    let before = mock.requests();
+    let txs = builder.build_transactions(None).await.unwrap();
+    assert_eq!(txs.len(), 10);
+    assert_eq!(other.requests(), 5);
+    assert!(mock.requests() - before >= 5);
+    verify_transactions(
+        &txs,
+        &Expected::new(signer.address(), 0)
+            .total_value(U256::from(250 * ETH))
+            .recipients(1_000),
+    );
+}
+
+#[tokio::test]
+async fn native_batch_builder_retries_estimates_on_another_endpoint() {
+    let mock = MockRpc::start().await;
+    let signer = PrivateKeySigner::random();
+    let unreachable: Box<dyn Provider> =
+        Box::new(ProviderBuilder::new().on_http("http://127.0.0.1:1".parse().unwrap()));
+    let builder = NativeBatchSenderBuilder {
+        signer: signer.clone(),
+        provider: mock.provider(),
+        contract_address: contract(),
+        recipients: test_recipients(400),
+        amounts: vec![U256::from(ETH / 4); 400],
+        batch: BatchConfig {
+            size: Some(100),
+            concurrency: 2,
+            ..Default::default()
+        },
+        estimate_providers: vec![unreachable, mock.provider()],
+    };
+    let txs = builder.build_transactions(None).await.unwrap();
+    assert_eq!(txs.len(), 4);
+    verify_transactions(
+        &txs,
+        &Expected::new(signer.address(), 0)
+            .total_value(U256::from(100 * ETH))
+            .recipients(400),
+    );
+}
+
+#[tokio::test]
+async fn batch_planning_caches_and_extrapolates_estimates() {
+    let mock = MockRpc::start().await;
+    let calls = Mutex::new(Vec::new());
+    let estimate = |recipients: std::ops::Range<usize>| {
+        calls.lock().unwrap().push(recipients.clone());
+        async move { Ok(21_000 + 1_000 * recipients.len() as u64) }
+    };
+    let gas_limits = |batches: Vec<distribution::builders::batching::PlannedBatch>| {
+        batches
+            .into_iter()
+            .map(|batch| (batch.recipients, batch.gas_limit))
+            .collect::<Vec<_>>()
+    };
+
+    // Batches of the same size are estimated once
+    let config = BatchConfig {
+        size: Some(100),
+        cache: true,
+        ..Default::default()
+    };
+    let batches = plan_batches(&*mock.provider(), &config, 1000, 450, estimate)
+        .await
+        .unwrap();
+    assert_eq!(batches.len(), 5);
+    assert_eq!(batches[3].gas_limit, 121_000);
+    assert_eq!(batches[4].gas_limit, 71_000);
+    assert_eq!(
+        std::mem::take(&mut *calls.lock().unwrap()),
+        vec![0..100, 400..450]
+    );
+
+    // One representative batch is estimated and scaled to the rest
+    let config = BatchConfig {
+        size: Some(100),
+        extrapolate: true,
+        ..Default::default()
+    };
+    let batches = plan_batches(&*mock.provider(), &config, 1000, 450, estimate)
+        .await
+        .unwrap();
+    assert_eq!(
+        gas_limits(batches),
+        vec![
+            (0..100, 121_000),
+            (100..200, 121_000),
+            (200..300, 121_000),
+            (300..400, 121_000),
+            (400..450, 71_000)
+        ]
+    );
+    assert_eq!(std::mem::take(&mut *calls.lock().unwrap()), vec![0..100]);
+
+    // Packing under a target needs only the two calibration estimates
+    let config = BatchConfig {
+        size: Some(100),
+        target_gas: Some(50_000),
+        extrapolate: true,
+        ..Default::default()
+    };
+    let batches = plan_batches(&*mock.provider(), &config, 1000, 60, estimate)
+        .await
+        .unwrap();
+    assert_eq!(
+        gas_limits(batches),
+        vec![(0..29, 50_000), (29..58, 50_000), (58..60, 23_000)]
+    );
+    assert_eq!(*calls.lock().unwrap(), vec![0..1, 0..60]);
+}
+
+fn swapper_builder(mock: &MockRpc, signer: &PrivateKeySigner) -> SwapperBuilder {
+    SwapperBuilder {
+        signer: signer.clone(),
+        provider: mock.provider(),
+        contract_address: contract(),
+        max_swaps: 50,
+        amount: SwapAmountConfig::default(),
+        direction: SwapDirectionConfig::default(),
+        max_slippage_bps: None,
+    }
+}
+
+#[tokio::test]
+async fn swapper_builder_alternates_fixed_swaps_by_default() {
+    let mock = MockRpc::start().await;
+    let signer = PrivateKeySigner::random();
+
+    let txs = swapper_builder(&mock, &signer)
+        .build_transactions(None)
+        .await
+        .unwrap();
+
+    let decoded = verify_transactions(&txs, &Expected::new(signer.address(), 0));
+    assert_eq!(decoded.len(), 50);
+    for (i, tx) in decoded.iter().enumerate() {
+        let call = Swapper::swapCall::abi_decode(&tx.input, true).unwrap();
+        assert_eq!(call.amountIn, U256::from(100));
+        assert_eq!(call.aToB, i % 2 == 0);
+    }
+}
+
+#[tokio::test]
+async fn swapper_builder_samples_and_clamps_amounts() {
+    let mock = MockRpc::start().await;
+    let signer = PrivateKeySigner::random();
+    let (token_a, token_b, pair) = (
+        Address::with_last_byte(0xa),
+        Address::with_last_byte(0xb),
+        Address::with_last_byte(0xd),
+    );
+    mock.set_call_result::<Swapper::getTokensCall>(contract(), (token_a, token_b).abi_encode());
+    mock.set_call_result::<Swapper::getPairCall>(contract(), pair.abi_encode());
+    // token0 is B, so reserve0 belongs to B
+    mock.set_call_result::<UniswapV2Pair::token0Call>(pair, token_b.abi_encode());
+    mock.set_call_result::<UniswapV2Pair::getReservesCall>(
+        pair,
+        (U256::from(1_000_000), U256::from(100_000), 0u32).abi_encode(),
+    );
+
+    let mut builder = swapper_builder(&mock, &signer);
+    builder.amount = SwapAmountConfig::Uniform {
+        low: 500,
+        high: 5_000,
+    };
+    builder.direction = SwapDirectionConfig::Biased {
+        a_to_b_probability: 1.0,
+    };
+    // 1% of the 100_000 token A reserve is ~1010 tokens in
+    builder.max_slippage_bps = Some(100);
+    let txs = builder.build_transactions(None).await.unwrap();
+
+    let decoded = verify_transactions(&txs, &Expected::new(signer.address(), 0));
+    for tx in &decoded {
+        let call = Swapper::swapCall::abi_decode(&tx.input, true).unwrap();
+        assert!(call.aToB);
+        assert!(call.amountIn >= U256::from(500) && call.amountIn <= U256::from(1_010));
+    }
+}
+
+#[tokio::test]
+async fn contract_call_builder_renders_argument_templates() {
+    let mock = MockRpc::start().await;
+    let signer = PrivateKeySigner::random();
+    let recipients = test_recipients(4);
+    let function = Function::parse("transfer(address to, uint256 amount, uint256 id)").unwrap();
+
+    let builder = ContractCallBuilder {
+        signer: signer.clone(),
+        provider: mock.provider(),
+        contract_address: contract(),
+        function: function.clone(),
+        args: vec![
+            ArgSource::Column { index: 0 },
+            ArgSource::Column { index: 1 },
+            ArgSource::Counter { start: 10, step: 5 },
+        ],
+        rows: recipients
+            .iter()
+            .enumerate()
+            .map(|(i, address)| vec![address.to_string(), (i * 100).to_string()])
+            .collect(),
+        recipients: recipients.clone(),
+        max_calls: 0,
+        value: 0.0,
+    };
+    let txs = builder.build_transactions(None).await.unwrap();
+
+    let expected = Expected::new(signer.address(), 0).recipients(4);
+    let decoded = verify_transactions(&txs, &expected);
+    for (i, tx) in decoded.iter().enumerate() {
+        assert_eq!(&tx.input[..4], function.selector().as_slice());
+        let args = function.abi_decode_input(&tx.input[4..], true).unwrap();
+        assert_eq!(
+            args,
+            vec![
+                DynSolValue::Address(recipients[i]),
+                DynSolValue::Uint(U256::from(i * 100), 256),
+                DynSolValue::Uint(U256::from(10 + 5 * i), 256),
+            ]
+        );
+    }
+}
+
+#[tokio::test]
+async fn contract_call_builder_estimates_every_call() {
+    let mock = MockRpc::start().await;
+    mock.set_gas_estimate(21_000);
+    mock.set_gas_per_input_byte(16);
+    let signer = PrivateKeySigner::random();
+    let function = Function::parse("note(string text)").unwrap();
+
+    // Longer notes need more calldata, and so more gas, than the first one
+    let builder = ContractCallBuilder {
+        signer: signer.clone(),
+        provider: mock.provider(),
+        contract_address: contract(),
+        function: function.clone(),
+        args: vec![ArgSource::Column { index: 0 }],
+        rows: vec![
+            vec!["a".to_string()],
+            vec!["b".repeat(100)],
+            vec!["c".repeat(40)],
+        ],
+        recipients: vec![],
+        max_calls: 0,
+        value: 0.0,
+    };
+    let txs = builder.build_transactions(None).await.unwrap();
+    let decoded = verify_transactions(&txs, &Expected::new(signer.address(), 0).recipients(3));
+    for (tx, decoded) in txs.iter().zip(&decoded) {
+        let estimate = 21_000 + 16 * decoded.input.len() as u64;
+        assert_eq!(tx.gas_limit, estimate * 105 / 100);
+    }
+    assert!(txs[1].gas_limit > txs[2].gas_limit && txs[2].gas_limit > txs[0].gas_limit);
+}
+
+#[tokio::test]
+async fn contract_call_builder_draws_random_arguments_within_bounds() {
+    let mock = MockRpc::start().await;
+    let signer = PrivateKeySigner::random();
+    let function = Function::parse("update(uint256 value, string label)").unwrap();
+
+    let builder = ContractCallBuilder {
+        signer: signer.clone(),
+        provider: mock.provider(),
+        contract_address: contract(),
+        function: function.clone(),
+        args: vec![
+            ArgSource::Random {
+                low: 100,
+                high: 200,
+            },
+            ArgSource::Constant {
+                value: "load".to_string(),
+            },
+        ],
+        rows: vec![],
+        recipients: vec![],
+        max_calls: 50,
+        value: 0.0,
+    };
+    let txs = builder.build_transactions(None).await.unwrap();
+    assert_eq!(txs.len(), 50);
+
+    // Calls without rows cover no recipients
+    assert!(txs.iter().all(|tx| tx.recipients.is_empty()));
+    let decoded = verify_transactions(&txs, &Expected::new(signer.address(), 0));
+    for tx in &decoded {
+        let args = function.abi_decode_input(&tx.input[4..], true).unwrap();
+        let DynSolValue::Uint(value, 256) = args[0] else {
+            panic!("unexpected argument {:?}", args[0]);
+        };
+        assert!(value >= U256::from(100) && value <= U256::from(200));
+        assert_eq!(args[1], DynSolValue::String("load".to_string()));
+    }
+}
+
+#[tokio::test]
+async fn contract_call_builder_rejects_mismatched_templates() {
+    let mock = MockRpc::start().await;
+    let builder = ContractCallBuilder {
+        signer: PrivateKeySigner::random(),
+        provider: mock.provider(),
+        contract_address: contract(),
+        function: Function::parse("update(uint256)").unwrap(),
+        args: vec![],
+        rows: vec![],
+        recipients: vec![],
+        max_calls: 1,
+        value: 0.0,
+    };
+    assert!(builder.build_transactions(None).await.is_err());
+}
+
+#[tokio::test]
+async fn strict_counter_builder_continues_from_current_value() {
+    let mock = MockRpc::start().await;
+    let signer = PrivateKeySigner::random();
+    mock.set_call_result::<StrictCounter::numberCall>(contract(), U256::from(41).abi_encode());
+
+    let builder = StrictCounterBuilder {
+        signer: signer.clone(),
+        provider: mock.provider(),
+        contract_address: contract(),
+        num_updates: 10,
+        expected: Mutex::new(None),
+    };
+    let txs = builder.build_transactions(None).await.unwrap();
+
+    let decoded = verify_transactions(&txs, &Expected::new(signer.address(), 0));
+    for (i, tx) in decoded.iter().enumerate() {
+        let call = StrictCounter::updateCall::abi_decode(&tx.input, true).unwrap();
+        assert_eq!(call.newNumber, U256::from(42 + i));
+    }
+
+    // Nothing has been applied yet, so the ordering check must fail
+    assert!(builder.verify().await.is_err());
+    mock.set_call_result::<StrictCounter::numberCall>(contract(), U256::from(51).abi_encode());
+    builder.verify().await.unwrap();
+}
+
+#[tokio::test]
+async fn uniswap_v2_builder_approves_then_swaps_along_path() {
+    let mock = MockRpc::start().await;
+    let signer = PrivateKeySigner::random();
+    let router = Address::with_last_byte(0xee);
+    let path = vec![Address::with_last_byte(0xa), Address::with_last_byte(0xb)];
+    mock.set_block_timestamp(1_800_000_000);
+    mock.set_call_result::<ERC20::decimalsCall>(path[0], U256::from(6).abi_encode());
+    mock.set_call_result::<ERC20::allowanceCall>(path[0], U256::ZERO.abi_encode());
+    let (factory, pair) = (Address::with_last_byte(0xf0), Address::with_last_byte(0xf1));
+    mock.set_call_result::<UniswapV2Router02::factoryCall>(router, factory.abi_encode());
+    mock.set_call_result::<UniswapV2Factory::getPairCall>(factory, pair.abi_encode());
+    mock.set_call_result::<UniswapV2Pair::token0Call>(pair, path[0].abi_encode());
+    mock.set_call_result::<UniswapV2Pair::getReservesCall>(
+        pair,
+        (U256::from(1_000_000_000), U256::from(400_000_000), 0u32).abi_encode(),
+    );
+
+    let builder = UniswapV2Builder {
+        signer: signer.clone(),
+        provider: mock.provider(),
+        router_address: router,
+        path: path.clone(),
+        amount_in: 2.5,
+        native_in: false,
+        max_swaps: 5,
+        max_slippage_bps: Some(50),
+        deadline_secs: 60,
+        approval_sent: Default::default(),
+    };
+    let txs = builder.build_transactions(None).await.unwrap();
+
+    let decoded = verify_transactions(&txs, &Expected::new(signer.address(), 0));
+    assert_eq!(decoded.len(), 6);
+    let approval = ERC20::approveCall::abi_decode(&decoded[0].input, true).unwrap();
+    assert_eq!(decoded[0].to, Some(path[0]));
+    assert_eq!(approval.spender, router);
+    assert_eq!(approval.amount, U256::MAX);
+
+    // Each swap is quoted against the reserves left by the swaps before it
+    let expected_mins = [989_548, 984_632, 979_752, 974_909, 970_103];
+    for (tx, expected_min) in decoded[1..].iter().zip(expected_mins) {
+        assert_eq!(tx.to, Some(router));
+        let call =
+            UniswapV2Router02::swapExactTokensForTokensCall::abi_decode(&tx.input, true).unwrap();
+        assert_eq!(call.amountIn, U256::from(2_500_000));
+        assert_eq!(call.amountOutMin, U256::from(expected_min));
+        assert_eq!(call.path, path);
+        assert_eq!(call.to, signer.address());
+        assert_eq!(call.deadline, U256::from(1_800_000_060u64));
+    }
+
+    // The approval is not mined yet, so the next round must not send another
+    let txs = builder.build_transactions(Some(6)).await.unwrap();
+    let decoded = verify_transactions(&txs, &Expected::new(signer.address(), 6));
+    assert_eq!(decoded.len(), 5);
+    assert!(decoded.iter().all(|tx| tx.to == Some(router)));
+}
+
+#[tokio::test]
+async fn uniswap_v2_builder_sends_value_for_native_swaps() {
+    let mock = MockRpc::start().await;
+    let signer = PrivateKeySigner::random();
+
+    let builder = UniswapV2Builder {
+        signer: signer.clone(),
+        provider: mock.provider(),
+        router_address: Address::with_last_byte(0xee),
+        path: vec![Address::with_last_byte(0xa), Address::with_last_byte(0xb)],
+        amount_in: 0.5,
+        native_in: true,
+        max_swaps: 4,
+        max_slippage_bps: None,
+        deadline_secs: 60,
+        approval_sent: Default::default(),
+    };
+    let txs = builder.build_transactions(None).await.unwrap();
+
+    let expected = Expected::new(signer.address(), 0).total_value(U256::from(2 * ETH));
+    for tx in verify_transactions(&txs, &expected) {
+        let call =
+            UniswapV2Router02::swapExactETHForTokensCall::abi_decode(&tx.input, true).unwrap();
+        assert_eq!(call.amountOutMin, U256::ZERO);
+    }
+}
+
+#[tokio::test]
+async fn mixed_builder_interleaves_by_weight() {
+    let mock = MockRpc::start().await;
+    let (heavy, light) = (PrivateKeySigner::random(), PrivateKeySigner::random());
+    mock.set_nonce(light.address(), 100);
+    let transfers = |signer: &PrivateKeySigner| -> Box<dyn Builder + Send + Sync> {
+        Box::new(NativeTransferBuilder {
+            signer: signer.clone(),
+            provider: mock.provider(),
+            recipients: test_recipients(4),
+            amounts: vec![U256::from(ETH); 4],
+        })
+    };
+
+    let builder = MixedBuilder::new(
+        vec![
+            WeightedBuilder {
+                name: "heavy".to_string(),
+                weight: 3.0,
+                builder: transfers(&heavy),
+                finite: false,
+            },
+            WeightedBuilder {
+                name: "light".to_string(),
+                weight: 1.0,
+                builder: transfers(&light),
+                finite: false,
+            },
+        ],
+        8,
+    );
+    let first = builder.build_transactions(None).await.unwrap();
+    let second = builder.build_transactions(None).await.unwrap();
+
+    let rounds = first.iter().chain(&second).collect::<Vec<_>>();
+    let heavy_txs = rounds
+        .iter()
+        .filter(|tx| tx.sender == heavy.address())
+        .map(|tx| (*tx).clone())
+        .collect::<Vec<_>>();
+    let light_txs = rounds
+        .iter()
+        .filter(|tx| tx.sender == light.address())
+        .map(|tx| (*tx).clone())
+        .collect::<Vec<_>>();
+    assert_eq!((heavy_txs.len(), light_txs.len()), (12, 4));
+
+    // Each signer's nonces stay contiguous across refills of its builder
+    verify_transactions(
+        &heavy_txs,
+        &Expected::new(heavy.address(), 0).total_value(U256::from(12 * ETH)),
+    );
+    let decoded = verify_transactions(
+        &light_txs,
+        &Expected::new(light.address(), 100).total_value(U256::from(4 * ETH)),
+    );
+
+    // Ranges refer to every workload's recipients back to back, the light ones coming second
+    for (tx, decoded) in light_txs.iter().zip(&decoded) {
+        assert_eq!(tx.recipients.len(), 1);
+        assert!(tx.recipients.start >= 4);
+        assert_eq!(decoded.to, Some(builder.recipients()[tx.recipients.start]));
+    }
+}
+
+#[tokio::test]
+async fn mixed_builder_verifies_every_workload() {
+    let mock = MockRpc::start().await;
+    let signer = PrivateKeySigner::random();
+    mock.set_call_result::<StrictCounter::numberCall>(contract(), U256::from(7).abi_encode());
+
+    let builder = MixedBuilder::new(
+        vec![
+            WeightedBuilder {
+                name: "transfers".to_string(),
+                weight: 1.0,
+                builder: Box::new(NativeTransferBuilder {
+                    signer: PrivateKeySigner::random(),
+                    provider: mock.provider(),
+                    recipients: test_recipients(2),
+                    amounts: vec![U256::from(ETH); 2],
+                }),
+                finite: true,
+            },
+            WeightedBuilder {
+                name: "counter".to_string(),
+                weight: 1.0,
+                builder: Box::new(StrictCounterBuilder {
+                    signer: signer.clone(),
+                    provider: mock.provider(),
+                    contract_address: contract(),
+                    num_updates: 3,
+                    expected: Mutex::new(None),
+                }),
+                finite: true,
+            },
+        ],
+        10,
+    );
+    // Finite workloads are built once, so the counter is not rebuilt from a stale value
+    let txs = builder.build_transactions(None).await.unwrap();
+    assert_eq!(txs.len(), 5);
+    assert!(builder.build_transactions(None).await.unwrap().is_empty());
+
+    let err = builder.verify().await.unwrap_err();
+    assert!(err.to_string().contains("counter"));
+    mock.set_call_result::<StrictCounter::numberCall>(contract(), U256::from(10).abi_encode());
+    builder.verify().await.unwrap();
+}
@@ -0,0 +1,524 @@
+//! In-process mock JSON-RPC server for exercising builders and distributors without a node
+
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::convert::Infallible;
+use std::net::TcpListener;
+use std::sync::{Arc, Mutex};
+use std::time::Instant;
+
+use alloy::consensus::TxEnvelope;
+use alloy::rlp::Decodable;
+use distribution::prelude::*;
+use futures::{SinkExt, StreamExt};
+use hyper::service::{make_service_fn, service_fn};
+use hyper::{Body, Request, Response, Server, StatusCode};
+use serde_json::json;
+use tokio::sync::mpsc;
+use tokio_tungstenite::tungstenite::Message;
+
+use super::verify::{decode_raw, DecodedTransaction};
+
+/// Fault injected into the next HTTP request that submits transactions
+#[derive(Debug, Clone)]
+pub enum Fault {
+    /// Close the connection without responding
+    Drop,
+    /// Respond normally after a delay
+    Delay(Duration),
+    /// Respond to every call in the request with a JSON-RPC error
+    Error { code: i64, message: String },
+    /// Respond with HTTP 429 and a plain-text body
+    RateLimited,
+}
+
+struct MockState {
+    chain_id: u64,
+    gas_estimate: u64,
+    /// Added to `gas_estimate` for every byte of calldata
+    gas_per_input_byte: u64,
+    nonces: HashMap<Address, u64>,
+    balances: HashMap<Address, U256>,
+    code: HashMap<Address, Bytes>,
+    /// `eth_call` outputs keyed by target and function selector
+    call_results: HashMap<(Address, [u8; 4]), Bytes>,
+    /// `eth_call` outputs for exact calldata, taking precedence over `call_results`
+    exact_call_results: HashMap<(Address, Bytes), Bytes>,
+    block_timestamp: u64,
+    block_number: u64,
+    faults: VecDeque<Fault>,
+    /// Maximum submission requests per second before responding with 429
+    rate_limit: Option<usize>,
+    window: (Instant, usize),
+    requests: usize,
+    sent: Vec<DecodedTransaction>,
+    /// Logs emitted by accepted transactions
+    logs: HashMap<TxHash, Vec<alloy::primitives::Log>>,
+    /// Accepted transactions whose receipts report a revert
+    reverted: HashSet<TxHash>,
+    /// Whether accepted transactions get receipts, as if mined right away
+    mining: bool,
+    /// WebSocket subscriptions by id, with their kind and connection
+    subscriptions: Vec<(String, String, mpsc::UnboundedSender<String>)>,
+}
+
+impl MockState {
+    fn notify(&self, kind: &str, result: Value) {
+        for (id, _, connection) in self.subscriptions.iter().filter(|(_, k, _)| k == kind) {
+            let notification = json!({
+                "jsonrpc": "2.0",
+                "method": "eth_subscription",
+                "params": { "subscription": id, "result": result },
+            });
+            let _ = connection.send(notification.to_string());
+        }
+    }
+}
+
+#[derive(Clone)]
+pub struct MockRpc {
+    pub url: String,
+    /// The same server over WebSocket
+    pub ws_url: String,
+    state: Arc<Mutex<MockState>>,
+}
+
+impl MockRpc {
+    /// Start a mock server on a random local port
+    pub async fn start() -> Self {
+        let state = Arc::new(Mutex::new(MockState {
+            chain_id: 10143,
+            gas_estimate: 100_000,
+            gas_per_input_byte: 0,
+            nonces: HashMap::new(),
+            balances: HashMap::new(),
+            code: HashMap::new(),
+            call_results: HashMap::new(),
+            exact_call_results: HashMap::new(),
+            block_timestamp: 1_700_000_000,
+            block_number: 1,
+            faults: VecDeque::new(),
+            rate_limit: None,
+            window: (Instant::now(), 0),
+            requests: 0,
+            sent: Vec::new(),
+            logs: HashMap::new(),
+            reverted: HashSet::new(),
+            mining: true,
+            subscriptions: Vec::new(),
+        }));
+
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = format!("http://{}", listener.local_addr().unwrap());
+        let service_state = state.clone();
+        let make_svc = make_service_fn(move |_| {
+            let state = service_state.clone();
+            async move { Ok::<_, Infallible>(service_fn(move |req| handle_request(state.clone(), req))) }
+        });
+        let server = Server::from_tcp(listener).unwrap().serve(make_svc);
+        tokio::spawn(server);
+
+        let ws_listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let ws_url = format!("ws://{}", ws_listener.local_addr().unwrap());
+        let ws_state = state.clone();
+        tokio::spawn(async move {
+            while let Ok((stream, _)) = ws_listener.accept().await {
+                tokio::spawn(handle_ws(ws_state.clone(), stream));
+            }
+        });
+
+        Self { url, ws_url, state }
+    }
+
+    pub fn provider(&self) -> Box<dyn Provider> {
+        Box::new(ProviderBuilder::new().on_http(self.url.parse().unwrap()))
+    }
+
+    pub fn set_nonce(&self, address: Address, nonce: u64) {
+        self.state.lock().unwrap().nonces.insert(address, nonce);
+    }
+
+    pub fn set_balance(&self, address: Address, balance: U256) {
+        self.state.lock().unwrap().balances.insert(address, balance);
+    }
+
+    pub fn set_code(&self, address: Address, code: Bytes) {
+        self.state.lock().unwrap().code.insert(address, code);
+    }
+
+    pub fn set_call_result<C: SolCall>(&self, to: Address, output: Vec<u8>) {
+        self.state
+            .lock()
+            .unwrap()
+            .call_results
+            .insert((to, C::SELECTOR), output.into());
+    }
+
+    pub fn set_exact_call_result<C: SolCall>(&self, to: Address, call: &C, output: Vec<u8>) {
+        self.state
+            .lock()
+            .unwrap()
+            .exact_call_results
+            .insert((to, call.abi_encode().into()), output.into());
+    }
+
+    pub fn set_block_timestamp(&self, timestamp: u64) {
+        self.state.lock().unwrap().block_timestamp = timestamp;
+    }
+
+    pub fn set_block_number(&self, block_number: u64) {
+        self.state.lock().unwrap().block_number = block_number;
+    }
+
+    /// Mine an empty block, notifying `newHeads` subscribers
+    pub fn publish_new_head(&self) {
+        let mut state = self.state.lock().unwrap();
+        state.block_number += 1;
+        let head = json!({ "number": format!("{:#x}", state.block_number) });
+        state.notify("newHeads", head);
+    }
+
+    pub fn set_gas_estimate(&self, gas: u64) {
+        self.state.lock().unwrap().gas_estimate = gas;
+    }
+
+    pub fn set_gas_per_input_byte(&self, gas: u64) {
+        self.state.lock().unwrap().gas_per_input_byte = gas;
+    }
+
+    pub fn set_rate_limit(&self, requests_per_sec: usize) {
+        self.state.lock().unwrap().rate_limit = Some(requests_per_sec);
+    }
+
+    pub fn push_fault(&self, fault: Fault) {
+        self.state.lock().unwrap().faults.push_back(fault);
+    }
+
+    /// Attach a log to the receipt of an accepted transaction
+    pub fn push_log(&self, hash: TxHash, log: alloy::primitives::Log) {
+        self.state
+            .lock()
+            .unwrap()
+            .logs
+            .entry(hash)
+            .or_default()
+            .push(log);
+    }
+
+    /// Make the receipt of an accepted transaction report a revert
+    pub fn revert(&self, hash: TxHash) {
+        self.state.lock().unwrap().reverted.insert(hash);
+    }
+
+    /// Stop or resume mining, leaving accepted transactions without receipts meanwhile
+    pub fn set_mining(&self, mining: bool) {
+        self.state.lock().unwrap().mining = mining;
+    }
+
+    /// Number of HTTP requests received, including faulted ones
+    pub fn requests(&self) -> usize {
+        self.state.lock().unwrap().requests
+    }
+
+    /// Transactions accepted so far, in arrival order
+    pub fn sent(&self) -> Vec<DecodedTransaction> {
+        self.state.lock().unwrap().sent.clone()
+    }
+}
+
+async fn handle_request(
+    state: Arc<Mutex<MockState>>,
+    req: Request<Body>,
+) -> Result<Response<Body>, std::io::Error> {
+    let body = hyper::body::to_bytes(req.into_body())
+        .await
+        .map_err(std::io::Error::other)?;
+
+    let request: Value = serde_json::from_slice(&body).map_err(std::io::Error::other)?;
+    // Faults only target submissions, so builders can still read chain state
+    let is_submission = request.to_string().contains("eth_sendRawTransaction");
+
+    let fault = {
+        let mut state = state.lock().unwrap();
+        state.requests += 1;
+        let rate_limited = match state.rate_limit {
+            _ if !is_submission => false,
+            Some(limit) => {
+                if state.window.0.elapsed() >= Duration::from_secs(1) {
+                    state.window = (Instant::now(), 0);
+                }
+                state.window.1 += 1;
+                state.window.1 > limit
+            }
+            None => false,
+        };
+        if rate_limited {
+            Some(Fault::RateLimited)
+        } else if is_submission {
+            state.faults.pop_front()
+        } else {
+            None
+        }
+    };
+
+    let error = match fault {
+        Some(Fault::Drop) => {
+            return Err(std::io::Error::other("dropped by mock"));
+        }
+        Some(Fault::Delay(delay)) => {
+            sleep(delay).await;
+            None
+        }
+        Some(Fault::Error { code, message }) => Some((code, message)),
+        Some(Fault::RateLimited) => {
+            return Ok(Response::builder()
+                .status(StatusCode::TOO_MANY_REQUESTS)
+                .body(Body::from("rate limited"))
+                .unwrap());
+        }
+        None => None,
+    };
+
+    let respond = |call: &Value| match &error {
+        Some((code, message)) => json!({
+            "jsonrpc": "2.0",
+            "id": call["id"],
+            "error": { "code": code, "message": message },
+        }),
+        None => handle_call(&state, call),
+    };
+    let response = match &request {
+        Value::Array(calls) => Value::Array(calls.iter().map(respond).collect()),
+        call => respond(call),
+    };
+
+    Ok(Response::builder()
+        .header("content-type", "application/json")
+        .body(Body::from(response.to_string()))
+        .unwrap())
+}
+
+async fn handle_ws(state: Arc<Mutex<MockState>>, stream: tokio::net::TcpStream) {
+    let Ok(ws) = tokio_tungstenite::accept_async(stream).await else {
+        return;
+    };
+    let (mut sink, mut source) = ws.split();
+    let (connection, mut outgoing) = mpsc::unbounded_channel::<String>();
+    tokio::spawn(async move {
+        while let Some(text) = outgoing.recv().await {
+            if sink.send(Message::Text(text.into())).await.is_err() {
+                break;
+            }
+        }
+    });
+
+    while let Some(Ok(message)) = source.next().await {
+        let Message::Text(text) = message else {
+            continue;
+        };
+        let request: Value = serde_json::from_str(&text).unwrap();
+        state.lock().unwrap().requests += 1;
+        let respond = |call: &Value| {
+            if call["method"] != "eth_subscribe" {
+                return handle_call(&state, call);
+            }
+            let mut state = state.lock().unwrap();
+            let id = format!("{:#x}", state.subscriptions.len() + 1);
+            let kind = call["params"][0].as_str().unwrap_or_default().to_string();
+            state
+                .subscriptions
+                .push((id.clone(), kind, connection.clone()));
+            json!({ "jsonrpc": "2.0", "id": call["id"], "result": id })
+        };
+        let response = match &request {
+            Value::Array(calls) => Value::Array(calls.iter().map(respond).collect()),
+            call => respond(call),
+        };
+        let _ = connection.send(response.to_string());
+    }
+}
+
+fn handle_call(state: &Mutex<MockState>, call: &Value) -> Value {
+    let mut state = state.lock().unwrap();
+    let params = call["params"].as_array().cloned().unwrap_or_default();
+    let address_param = || params[0].as_str().unwrap().parse::<Address>().unwrap();
+
+    let result = match call["method"].as_str().unwrap_or_default() {
+        "eth_chainId" => Ok(json!(format!("{:#x}", state.chain_id))),
+        "eth_getTransactionCount" => {
+            let address = address_param();
+            let configured = state.nonces.get(&address).copied().unwrap_or_default();
+            // Accepted transactions are treated as mined immediately
+            let accepted = state
+                .sent
+                .iter()
+                .filter(|tx| tx.from == address)
+                .map(|tx| tx.nonce + 1)
+                .max()
+                .unwrap_or_default();
+            Ok(json!(format!("{:#x}", configured.max(accepted))))
+        }
+        "eth_estimateGas" => {
+            let input = params[0]["input"]
+                .as_str()
+                .or(params[0]["data"].as_str())
+                .unwrap_or("0x");
+            let input_len = (input.len() as u64 - 2) / 2;
+            let gas = state.gas_estimate + state.gas_per_input_byte * input_len;
+            Ok(json!(format!("{:#x}", gas)))
+        }
+        "eth_getBalance" => {
+            let address = address_param();
+            let balance = state.balances.get(&address).copied().unwrap_or_default();
+            Ok(json!(balance))
+        }
+        "eth_getCode" => {
+            let address = address_param();
+            let code = state.code.get(&address).cloned().unwrap_or_default();
+            Ok(json!(code))
+        }
+        "eth_call" => {
+            let to = params[0]["to"]
+                .as_str()
+                .unwrap()
+                .parse::<Address>()
+                .unwrap();
+            let input = params[0]["input"]
+                .as_str()
+                .or(params[0]["data"].as_str())
+                .unwrap()
+                .parse::<Bytes>()
+                .unwrap();
+            let selector: [u8; 4] = input[..4].try_into().unwrap();
+            match state
+                .exact_call_results
+                .get(&(to, input))
+                .or(state.call_results.get(&(to, selector)))
+            {
+                Some(output) => Ok(json!(output)),
+                None => Err((3, "execution reverted".to_string())),
+            }
+        }
+        "eth_blockNumber" => Ok(json!(format!("{:#x}", state.block_number))),
+        "eth_getBlockByNumber" => Ok(block(state.block_timestamp)),
+        "eth_sendRawTransaction" => {
+            let raw = params[0].as_str().unwrap().parse::<Bytes>().unwrap();
+            match decode_raw(raw) {
+                Ok(tx) => {
+                    let hash = tx.hash;
+                    state.sent.push(tx);
+                    state.notify("newPendingTransactions", json!(hash));
+                    Ok(json!(hash))
+                }
+                Err(e) => Err((-32000, e.to_string())),
+            }
+        }
+        "eth_getTransactionReceipt" => {
+            let hash = params[0].as_str().unwrap().parse::<TxHash>().unwrap();
+            let position = state.sent.iter().position(|tx| tx.hash == hash);
+            Ok(match position.filter(|_| state.mining) {
+                Some(index) => {
+                    let tx = &state.sent[index];
+                    let logs = state.logs.get(&hash).cloned().unwrap_or_default();
+                    receipt(tx, index as u64, &logs, !state.reverted.contains(&hash))
+                }
+                None => Value::Null,
+            })
+        }
+        "eth_getTransactionByHash" => {
+            let hash = params[0].as_str().unwrap().parse::<TxHash>().unwrap();
+            let position = state.sent.iter().position(|tx| tx.hash == hash);
+            Ok(match position {
+                Some(index) => transaction(&state.sent[index], index as u64),
+                None => Value::Null,
+            })
+        }
+        method => Err((-32601, format!("Method {} not supported by mock", method))),
+    };
+
+    match result {
+        Ok(result) => json!({ "jsonrpc": "2.0", "id": call["id"], "result": result }),
+        Err((code, message)) => json!({
+            "jsonrpc": "2.0",
+            "id": call["id"],
+            "error": { "code": code, "message": message },
+        }),
+    }
+}
+
+/// Latest block, with only the timestamp being meaningful
+fn block(timestamp: u64) -> Value {
+    let zero_hash = TxHash::ZERO;
+    json!({
+        "hash": TxHash::with_last_byte(1),
+        "parentHash": zero_hash,
+        "sha3Uncles": zero_hash,
+        "miner": Address::ZERO,
+        "stateRoot": zero_hash,
+        "transactionsRoot": zero_hash,
+        "receiptsRoot": zero_hash,
+        "logsBloom": format!("0x{}", "00".repeat(256)),
+        "difficulty": "0x0",
+        "number": "0x1",
+        "gasLimit": "0x1c9c380",
+        "gasUsed": "0x0",
+        "timestamp": format!("{:#x}", timestamp),
+        "extraData": "0x",
+        "mixHash": zero_hash,
+        "nonce": "0x0000000000000000",
+        "baseFeePerGas": "0x0",
+        "uncles": [],
+        "transactions": [],
+    })
+}
+
+/// Transaction mined alone in the block numbered after its arrival index
+fn transaction(tx: &DecodedTransaction, index: u64) -> Value {
+    let envelope = TxEnvelope::decode(&mut tx.raw.as_ref()).unwrap();
+    json!(alloy::rpc::types::Transaction {
+        inner: envelope,
+        block_hash: Some(TxHash::with_last_byte(1)),
+        block_number: Some(index + 1),
+        transaction_index: Some(0),
+        effective_gas_price: tx.gas_price,
+        from: tx.from,
+    })
+}
+
+/// Receipt for a transaction mined alone in the block numbered after its arrival index
+fn receipt(
+    tx: &DecodedTransaction,
+    index: u64,
+    logs: &[alloy::primitives::Log],
+    success: bool,
+) -> Value {
+    let logs = logs
+        .iter()
+        .enumerate()
+        .map(|(log_index, log)| alloy::rpc::types::Log {
+            inner: log.clone(),
+            block_hash: Some(TxHash::with_last_byte(1)),
+            block_number: Some(index + 1),
+            block_timestamp: None,
+            transaction_hash: Some(tx.hash),
+            transaction_index: Some(0),
+            log_index: Some(log_index as u64),
+            removed: false,
+        })
+        .collect::<Vec<_>>();
+    json!({
+        "type": "0x0",
+        "status": if success { "0x1" } else { "0x0" },
+        "cumulativeGasUsed": "0x5208",
+        "logs": logs,
+        "logsBloom": format!("0x{}", "00".repeat(256)),
+        "transactionHash": tx.hash,
+        "transactionIndex": "0x0",
+        "blockHash": TxHash::with_last_byte(1),
+        "blockNumber": format!("{:#x}", index + 1),
+        "gasUsed": "0x5208",
+        "effectiveGasPrice": "0xc1b710800",
+        "from": tx.from,
+        "to": tx.to,
+        "contractAddress": null,
+    })
+}
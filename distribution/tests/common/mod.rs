@@ -0,0 +1,4 @@
+#![allow(dead_code)]
+
+pub mod mock_rpc;
+pub mod verify;
@@ -0,0 +1,145 @@
+//! Decoding and invariant checks for the output of a `Builder`
+
+use alloy::consensus::{Transaction, TxEnvelope};
+use alloy::rlp::Decodable;
+use distribution::prelude::*;
+
+/// A signed transaction decoded back from its raw encoding
+#[derive(Debug, Clone)]
+pub struct DecodedTransaction {
+    pub raw: Bytes,
+    pub hash: TxHash,
+    /// Signer recovered from the signature
+    pub from: Address,
+    pub nonce: u64,
+    pub to: Option<Address>,
+    pub value: U256,
+    pub input: Bytes,
+    pub chain_id: Option<u64>,
+    pub gas_limit: u64,
+    pub gas_price: Option<u128>,
+}
+
+pub fn decode_raw(raw: Bytes) -> Result<DecodedTransaction> {
+    let envelope = TxEnvelope::decode(&mut raw.as_ref())?;
+    Ok(DecodedTransaction {
+        hash: *envelope.tx_hash(),
+        from: envelope.recover_signer()?,
+        nonce: envelope.nonce(),
+        to: envelope.to(),
+        value: envelope.value(),
+        input: envelope.input().clone(),
+        chain_id: envelope.chain_id(),
+        gas_limit: envelope.gas_limit(),
+        gas_price: envelope.gas_price(),
+        raw,
+    })
+}
+
+/// Decode the `0x`-hex encoding sent over JSON-RPC
+pub fn decode_hex(tx: &str) -> Result<DecodedTransaction> {
+    decode_raw(tx.parse::<Bytes>()?)
+}
+
+/// Invariants every builder's output must satisfy
+#[derive(Debug, Clone)]
+pub struct Expected {
+    pub signer: Address,
+    pub start_nonce: u64,
+    pub chain_id: u64,
+    /// Sum of native value across all transactions
+    pub total_value: U256,
+    /// Number of recipients that must each be covered by exactly one transaction
+    pub recipients: Option<usize>,
+}
+
+impl Expected {
+    pub fn new(signer: Address, start_nonce: u64) -> Self {
+        Self {
+            signer,
+            start_nonce,
+            chain_id: 10143,
+            total_value: U256::ZERO,
+            recipients: None,
+        }
+    }
+
+    pub fn total_value(mut self, total_value: U256) -> Self {
+        self.total_value = total_value;
+        self
+    }
+
+    pub fn recipients(mut self, recipients: usize) -> Self {
+        self.recipients = Some(recipients);
+        self
+    }
+}
+
+/// Decode every transaction and assert the builder invariants, returning them in nonce order
+pub fn verify_transactions(
+    txs: &[SignedTransaction],
+    expected: &Expected,
+) -> Vec<DecodedTransaction> {
+    let decoded = txs
+        .iter()
+        .map(|tx| decode_hex(&tx.to_hex()).expect("builder output must decode"))
+        .collect::<Vec<_>>();
+
+    for (i, (tx, decoded)) in txs.iter().zip(&decoded).enumerate() {
+        assert_eq!(decoded.from, expected.signer, "tx {} signer", i);
+        assert_eq!(
+            decoded.chain_id,
+            Some(expected.chain_id),
+            "tx {} chain id",
+            i
+        );
+        assert_eq!(
+            decoded.nonce,
+            expected.start_nonce + i as u64,
+            "tx {} nonce",
+            i
+        );
+
+        // The typed metadata must agree with what was actually signed
+        assert_eq!(tx.hash, decoded.hash, "tx {} hash", i);
+        assert_eq!(tx.nonce, decoded.nonce, "tx {} nonce metadata", i);
+        assert_eq!(tx.sender, decoded.from, "tx {} sender metadata", i);
+        assert_eq!(tx.value, decoded.value, "tx {} value metadata", i);
+        assert_eq!(
+            tx.gas_limit, decoded.gas_limit,
+            "tx {} gas limit metadata",
+            i
+        );
+        assert_eq!(
+            Some(tx.max_fee),
+            decoded.gas_price,
+            "tx {} max fee metadata",
+            i
+        );
+    }
+
+    let total_value = decoded.iter().map(|tx| tx.value).sum::<U256>();
+    assert_eq!(total_value, expected.total_value, "total value");
+
+    if let Some(recipients) = expected.recipients {
+        let mut covered = vec![0usize; recipients];
+        for tx in txs {
+            for index in tx.recipients.clone() {
+                assert!(index < recipients, "recipient {} out of range", index);
+                covered[index] += 1;
+            }
+        }
+        for (index, count) in covered.iter().enumerate() {
+            assert_eq!(*count, 1, "recipient {} covered {} times", index, count);
+        }
+    }
+
+    decoded
+}
+
+/// Distinct addresses for use as recipients
+pub fn test_recipients(count: usize) -> Vec<Address> {
+    (0..count)
+        .map(|i| Address::from_word(U256::from(i + 1).into()))
+        .collect()
+}
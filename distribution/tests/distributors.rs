@@ -0,0 +1,722 @@
+mod common;
+
+use std::collections::HashMap;
+
+use common::mock_rpc::{Fault, MockRpc};
+use distribution::builders::mixed::{split_recipients, MixedBuilder, WeightedBuilder};
+use distribution::builders::native::NativeTransferBuilder;
+use distribution::builders::presigned::PresignedBuilder;
+use distribution::distributors::continuous::ContinuousDistributor;
+use distribution::distributors::finite::{send_group, FiniteGroupDistributor};
+use distribution::idempotency::{paid_from_journal, PaymentScope};
+use distribution::journal::NonceRange;
+use distribution::prelude::*;
+
+fn transfer_builder(
+    mock: &MockRpc,
+    signer: &PrivateKeySigner,
+    count: usize,
+) -> NativeTransferBuilder {
+    NativeTransferBuilder {
+        signer: signer.clone(),
+        provider: mock.provider(),
+        recipients: (0..count)
+            .map(|i| Address::with_last_byte(i as u8 + 1))
+            .collect(),
+        amounts: vec![U256::from(1_000_000_000_000_000u128); count],
+    }
+}
+
+fn test_options(mock: &MockRpc, rpc_batch_size: usize) -> DistributionOptions {
+    DistributionOptions {
+        rpc_urls: vec![mock.url.clone()],
+        rpc_batch_size,
+        target_tps: 10_000,
+        confirmation_timeout: 5,
+        ..Default::default()
+    }
+}
+
+/// Transfers whose final-state check always fails, counting how often it ran
+struct FailingCheck {
+    transfers: NativeTransferBuilder,
+    checks: Arc<std::sync::atomic::AtomicUsize>,
+}
+
+#[async_trait]
+impl Builder for FailingCheck {
+    async fn build_transactions(
+        &self,
+        start_nonce_override: Option<u64>,
+    ) -> Result<Vec<SignedTransaction>> {
+        self.transfers
+            .build_transactions(start_nonce_override)
+            .await
+    }
+
+    async fn verify(&self) -> Result<()> {
+        self.checks
+            .fetch_add(1, std::sync::atomic::Ordering::SeqCst);
+        Err(anyhow::anyhow!("final state check failed"))
+    }
+}
+
+fn sorted_nonces(mock: &MockRpc) -> Vec<u64> {
+    let mut nonces = mock.sent().iter().map(|tx| tx.nonce).collect::<Vec<_>>();
+    nonces.sort();
+    nonces
+}
+
+#[tokio::test]
+async fn finite_distributor_sends_every_transaction_once() {
+    let mock = MockRpc::start().await;
+    let signer = PrivateKeySigner::random();
+    mock.set_nonce(signer.address(), 3);
+
+    let distributor = FiniteGroupDistributor {
+        signer: signer.clone(),
+    };
+    distributor
+        .send_transactions(
+            vec![mock.url.clone()],
+            Box::new(transfer_builder(&mock, &signer, 25)),
+            test_options(&mock, 1),
+        )
+        .await
+        .unwrap();
+
+    let sent = mock.sent();
+    assert_eq!(sent.len(), 25);
+    assert!(sent.iter().all(|tx| tx.from == signer.address()));
+    assert!(sent.iter().all(|tx| tx.chain_id == Some(10143)));
+    assert_eq!(sorted_nonces(&mock), (3..28).collect::<Vec<_>>());
+}
+
+#[tokio::test]
+async fn finite_distributor_retries_dropped_and_rate_limited_batches() {
+    let mock = MockRpc::start().await;
+    let signer = PrivateKeySigner::random();
+    mock.push_fault(Fault::Drop);
+    mock.push_fault(Fault::RateLimited);
+    mock.push_fault(Fault::Delay(Duration::from_millis(200)));
+    mock.push_fault(Fault::Drop);
+
+    let distributor = FiniteGroupDistributor {
+        signer: signer.clone(),
+    };
+    distributor
+        .send_transactions(
+            vec![mock.url.clone()],
+            Box::new(transfer_builder(&mock, &signer, 12)),
+            test_options(&mock, 1),
+        )
+        .await
+        .unwrap();
+
+    assert_eq!(sorted_nonces(&mock), (0..12).collect::<Vec<_>>());
+}
+
+#[tokio::test]
+async fn finite_distributor_survives_rate_limits() {
+    let mock = MockRpc::start().await;
+    let signer = PrivateKeySigner::random();
+    mock.set_rate_limit(4);
+
+    let distributor = FiniteGroupDistributor {
+        signer: signer.clone(),
+    };
+    distributor
+        .send_transactions(
+            vec![mock.url.clone()],
+            Box::new(transfer_builder(&mock, &signer, 8)),
+            test_options(&mock, 1),
+        )
+        .await
+        .unwrap();
+
+    assert_eq!(sorted_nonces(&mock), (0..8).collect::<Vec<_>>());
+}
+
+#[tokio::test]
+async fn finite_distributor_does_not_retry_rpc_errors() {
+    let mock = MockRpc::start().await;
+    let signer = PrivateKeySigner::random();
+    // The first group holds only the first transaction
+    mock.push_fault(Fault::Error {
+        code: -32000,
+        message: "nonce too low".to_string(),
+    });
+
+    let distributor = FiniteGroupDistributor {
+        signer: signer.clone(),
+    };
+    distributor
+        .send_transactions(
+            vec![mock.url.clone()],
+            Box::new(transfer_builder(&mock, &signer, 12)),
+            test_options(&mock, 1),
+        )
+        .await
+        .unwrap();
+
+    assert_eq!(sorted_nonces(&mock), (1..12).collect::<Vec<_>>());
+}
+
+#[tokio::test]
+async fn finite_distributor_journals_only_accepted_transactions() {
+    let mock = MockRpc::start().await;
+    let signer = PrivateKeySigner::random();
+    mock.push_fault(Fault::Error {
+        code: -32000,
+        message: "nonce too low".to_string(),
+    });
+    let path = std::env::temp_dir().join(format!("journal-{}.jsonl", signer.address()));
+    let path = path.to_str().unwrap().to_string();
+
+    let builder = transfer_builder(&mock, &signer, 12);
+    let recipients = builder.recipients.clone();
+    let mut options = test_options(&mock, 1);
+    options.journal = Some(Arc::new(Journal::open(&path).unwrap()));
+    let distributor = FiniteGroupDistributor {
+        signer: signer.clone(),
+    };
+    distributor
+        .send_transactions(vec![mock.url.clone()], Box::new(builder), options)
+        .await
+        .unwrap();
+
+    let entries = Journal::read(&path).unwrap();
+    assert_eq!(entries.len(), 11);
+    assert!(entries.iter().all(|entry| entry.recipients.len() == 1));
+    assert!(entries
+        .iter()
+        .all(|entry| entry.to == Some(entry.recipients[0]) && entry.selector.is_none()));
+
+    // A reverted transfer leaves its recipient unpaid
+    mock.revert(entries[0].hash);
+    let provider = mock.provider();
+    let paid_from = |senders: Vec<Address>, scope: PaymentScope| {
+        let (provider, path) = (&provider, &path);
+        async move {
+            paid_from_journal(provider.as_ref(), path, &senders, scope)
+                .await
+                .unwrap()
+        }
+    };
+    let paid = paid_from(vec![signer.address()], PaymentScope::Transfer).await;
+    assert!(!paid.contains(&recipients[0]));
+    assert!(!paid.contains(&entries[0].recipients[0]));
+    assert_eq!(paid.len(), 10);
+    assert!(paid_from(vec![Address::ZERO], PaymentScope::Transfer)
+        .await
+        .is_empty());
+
+    // Transfers don't count as payments of another distribution to the same recipients
+    let mint = PaymentScope::Call {
+        contract: Address::with_last_byte(0xcc),
+        selector: FixedBytes::ZERO,
+    };
+    assert!(paid_from(vec![signer.address()], mint).await.is_empty());
+
+    // Nor do transactions that were accepted but never mined
+    mock.set_mining(false);
+    assert!(paid_from(vec![signer.address()], PaymentScope::Transfer)
+        .await
+        .is_empty());
+    std::fs::remove_file(&path).unwrap();
+}
+
+#[tokio::test]
+async fn continuous_distributor_keeps_nonces_contiguous_across_rounds() {
+    let mock = MockRpc::start().await;
+    let signer = PrivateKeySigner::random();
+    mock.set_nonce(signer.address(), 7);
+
+    let builder = Box::new(transfer_builder(&mock, &signer, 5));
+    let options = test_options(&mock, 2);
+    let rpc_urls = vec![mock.url.clone(), mock.url.clone()];
+    let distributor = ContinuousDistributor {
+        signer: signer.clone(),
+    };
+    let handle = tokio::spawn(async move {
+        distributor
+            .send_transactions(rpc_urls, builder, options)
+            .await
+    });
+
+    tokio::time::timeout(Duration::from_secs(10), async {
+        while mock.sent().len() < 15 {
+            sleep(Duration::from_millis(20)).await;
+        }
+    })
+    .await
+    .expect("distributor did not send three rounds");
+    handle.abort();
+
+    let nonces = sorted_nonces(&mock);
+    assert_eq!(nonces[..15], (7..22).collect::<Vec<_>>());
+    // Batches of two transactions are sent per request
+    assert!(mock.requests() < nonces.len() + 5);
+}
+
+#[tokio::test]
+async fn continuous_distributor_stops_partway_through_a_round_at_its_budget() {
+    let mock = MockRpc::start().await;
+    let signer = PrivateKeySigner::random();
+
+    let mut options = test_options(&mock, 2);
+    options.stop.max_transactions = Some(12);
+    let distributor = ContinuousDistributor {
+        signer: signer.clone(),
+    };
+    tokio::time::timeout(
+        Duration::from_secs(10),
+        distributor.send_transactions(
+            vec![mock.url.clone()],
+            Box::new(transfer_builder(&mock, &signer, 5)),
+            options,
+        ),
+    )
+    .await
+    .expect("distributor did not stop")
+    .unwrap();
+
+    assert_eq!(sorted_nonces(&mock), (0..12).collect::<Vec<_>>());
+}
+
+#[tokio::test]
+async fn continuous_distributor_stops_at_gas_and_value_budgets() {
+    // Each transfer has a gas limit of 21000 and sends 0.001 ether
+    let budgets = [
+        StopConfig {
+            max_gas: Some(21_000 * 7),
+            ..Default::default()
+        },
+        StopConfig {
+            max_value: Some(0.0075),
+            ..Default::default()
+        },
+    ];
+    for stop in budgets {
+        let mock = MockRpc::start().await;
+        let signer = PrivateKeySigner::random();
+        let mut options = test_options(&mock, 2);
+        options.stop = stop;
+        let distributor = ContinuousDistributor {
+            signer: signer.clone(),
+        };
+        tokio::time::timeout(
+            Duration::from_secs(10),
+            distributor.send_transactions(
+                vec![mock.url.clone()],
+                Box::new(transfer_builder(&mock, &signer, 5)),
+                options,
+            ),
+        )
+        .await
+        .expect("distributor did not stop")
+        .unwrap();
+
+        assert_eq!(sorted_nonces(&mock), (0..7).collect::<Vec<_>>());
+    }
+}
+
+#[tokio::test]
+async fn continuous_distributor_stops_mid_round_at_its_duration() {
+    let mock = MockRpc::start().await;
+    let signer = PrivateKeySigner::random();
+
+    // One transaction every 500ms, so only the first two are due within a second
+    let mut options = test_options(&mock, 1);
+    options.target_tps = 2;
+    options.stop.max_duration_secs = Some(1);
+    let distributor = ContinuousDistributor {
+        signer: signer.clone(),
+    };
+    distributor
+        .send_transactions(
+            vec![mock.url.clone()],
+            Box::new(transfer_builder(&mock, &signer, 5)),
+            options,
+        )
+        .await
+        .unwrap();
+
+    assert_eq!(sorted_nonces(&mock), vec![0, 1]);
+}
+
+#[tokio::test]
+async fn continuous_distributor_stops_at_duration_and_balance_floor() {
+    let mock = MockRpc::start().await;
+    let signer = PrivateKeySigner::random();
+    mock.set_balance(signer.address(), U256::from(2_000_000_000_000_000_000u128));
+
+    let mut options = test_options(&mock, 5);
+    options.stop.max_duration_secs = Some(1);
+    options.stop.min_signer_balance = Some(1.0);
+    let distributor = ContinuousDistributor {
+        signer: signer.clone(),
+    };
+    distributor
+        .send_transactions(
+            vec![mock.url.clone()],
+            Box::new(transfer_builder(&mock, &signer, 5)),
+            options.clone(),
+        )
+        .await
+        .unwrap();
+    assert!(!mock.sent().is_empty());
+
+    // Nothing is sent once the signer is below the floor
+    let sent = mock.sent().len();
+    mock.set_balance(signer.address(), U256::from(500_000_000_000_000_000u128));
+    distributor
+        .send_transactions(
+            vec![mock.url.clone()],
+            Box::new(transfer_builder(&mock, &signer, 5)),
+            options,
+        )
+        .await
+        .unwrap();
+    assert_eq!(mock.sent().len(), sent);
+}
+
+#[tokio::test]
+async fn continuous_distributor_drains_and_journals_on_shutdown() {
+    let mock = MockRpc::start().await;
+    let signer = PrivateKeySigner::random();
+    let path = std::env::temp_dir().join(format!("shutdown-{}.jsonl", signer.address()));
+    let path = path.to_str().unwrap().to_string();
+
+    let mut options = test_options(&mock, 2);
+    options.journal = Some(Arc::new(Journal::open(&path).unwrap()));
+    let shutdown = options.shutdown.clone();
+    let distributor = ContinuousDistributor {
+        signer: signer.clone(),
+    };
+    let builder = Box::new(transfer_builder(&mock, &signer, 5));
+    let handle = tokio::spawn(async move {
+        distributor
+            .send_transactions(vec![mock.url.clone()], builder, options)
+            .await
+    });
+
+    tokio::time::timeout(Duration::from_secs(10), async {
+        while Journal::read(&path).unwrap().len() < 10 {
+            sleep(Duration::from_millis(20)).await;
+        }
+    })
+    .await
+    .expect("distributor did not send two rounds");
+    shutdown.trigger();
+    tokio::time::timeout(Duration::from_secs(10), handle)
+        .await
+        .expect("distributor did not stop")
+        .unwrap()
+        .unwrap();
+
+    // Every accepted transaction is journaled and nonces have no gaps
+    let mut nonces = Journal::read(&path)
+        .unwrap()
+        .iter()
+        .map(|entry| entry.nonce)
+        .collect::<Vec<_>>();
+    nonces.sort();
+    assert_eq!(nonces, (0..nonces.len() as u64).collect::<Vec<_>>());
+}
+
+#[tokio::test]
+async fn finite_distributor_sends_nothing_after_shutdown() {
+    let mock = MockRpc::start().await;
+    let signer = PrivateKeySigner::random();
+
+    let options = test_options(&mock, 1);
+    options.shutdown.trigger();
+    FiniteGroupDistributor {
+        signer: signer.clone(),
+    }
+    .send_transactions(
+        vec![mock.url.clone()],
+        Box::new(transfer_builder(&mock, &signer, 5)),
+        options,
+    )
+    .await
+    .unwrap();
+    assert!(mock.sent().is_empty());
+}
+
+#[tokio::test]
+async fn presigned_transactions_broadcast_without_the_key() {
+    let mock = MockRpc::start().await;
+    let signer = PrivateKeySigner::random();
+    let builder = transfer_builder(&mock, &signer, 6);
+    let presigned = PresignedBuilder {
+        recipients: builder.recipients.clone(),
+        transactions: builder.build_transactions(None).await.unwrap(),
+    };
+    let path = std::env::temp_dir().join(format!("signed-{}.json", signer.address()));
+    let path = path.to_str().unwrap();
+    presigned.write(path).unwrap();
+
+    let journal_path = format!("{}.journal", path);
+    let mut options = test_options(&mock, 2);
+    options.journal = Some(Arc::new(Journal::open(&journal_path).unwrap()));
+    let presigned = PresignedBuilder::read(path).unwrap();
+    let sender = presigned.sender().unwrap().unwrap();
+    assert_eq!(sender, signer.address());
+    send_group(vec![mock.url.clone()], Box::new(presigned), options, sender)
+        .await
+        .unwrap();
+
+    assert_eq!(sorted_nonces(&mock), (0..6).collect::<Vec<_>>());
+    let summary = JournalSummary::new(&Journal::read(&journal_path).unwrap());
+    assert_eq!(summary.transactions, 6);
+    assert_eq!(summary.recipients, 6);
+    assert_eq!(summary.value, U256::from(6_000_000_000_000_000u128));
+    assert_eq!(
+        summary.senders[&signer.address()],
+        NonceRange { first: 0, last: 5 }
+    );
+
+    // Nonces are signed in, so the transactions cannot be moved to another one
+    let presigned = PresignedBuilder::read(path).unwrap();
+    assert!(presigned.build_transactions(Some(1)).await.is_err());
+}
+
+#[tokio::test]
+async fn finite_distributor_reports_unconfirmed_runs_without_verifying() {
+    let mock = MockRpc::start().await;
+    let signer = PrivateKeySigner::random();
+    mock.set_mining(false);
+
+    let checks = Arc::new(std::sync::atomic::AtomicUsize::new(0));
+    let builder = FailingCheck {
+        transfers: transfer_builder(&mock, &signer, 5),
+        checks: checks.clone(),
+    };
+    let mut options = test_options(&mock, 1);
+    options.confirmation_timeout = 1;
+    let error = FiniteGroupDistributor {
+        signer: signer.clone(),
+    }
+    .send_transactions(vec![mock.url.clone()], Box::new(builder), options)
+    .await
+    .unwrap_err();
+    assert!(error.to_string().contains("not confirmed within 1 seconds"));
+    assert_eq!(checks.load(std::sync::atomic::Ordering::SeqCst), 0);
+    assert_eq!(mock.sent().len(), 5);
+}
+
+#[tokio::test]
+async fn mixed_workloads_pay_each_recipient_once_across_rounds() {
+    let mock = MockRpc::start().await;
+    let signers = (0..3)
+        .map(|_| PrivateKeySigner::random())
+        .collect::<Vec<_>>();
+    let recipients = (0..10)
+        .map(|i| Address::with_last_byte(i + 1))
+        .collect::<Vec<_>>();
+    let amounts = (1..=10).map(U256::from).collect::<Vec<_>>();
+
+    // Two signers share the recipients of a finite workload, and a third keeps sending
+    let mut builders = split_recipients(&recipients, &amounts, 2)
+        .into_iter()
+        .zip(&signers)
+        .map(|((recipients, amounts), signer)| WeightedBuilder {
+            name: signer.address().to_string(),
+            weight: 1.0,
+            builder: Box::new(NativeTransferBuilder {
+                signer: signer.clone(),
+                provider: mock.provider(),
+                recipients,
+                amounts,
+            }),
+            finite: true,
+        })
+        .collect::<Vec<_>>();
+    let filler = Address::with_last_byte(0xff);
+    builders.push(WeightedBuilder {
+        name: "filler".to_string(),
+        weight: 1.0,
+        builder: Box::new(NativeTransferBuilder {
+            signer: signers[2].clone(),
+            provider: mock.provider(),
+            recipients: vec![filler],
+            amounts: vec![U256::from(1)],
+        }),
+        finite: false,
+    });
+
+    let mut options = test_options(&mock, 1);
+    options.stop.max_transactions = Some(30);
+    ContinuousDistributor {
+        signer: signers[2].clone(),
+    }
+    .send_transactions(
+        vec![mock.url.clone()],
+        Box::new(MixedBuilder::new(builders, 4)),
+        options,
+    )
+    .await
+    .unwrap();
+
+    let sent = mock.sent();
+    assert_eq!(sent.len(), 30);
+    let mut received = HashMap::new();
+    for tx in &sent {
+        *received.entry(tx.to.unwrap()).or_insert(U256::ZERO) += tx.value;
+    }
+    for (recipient, amount) in recipients.iter().zip(&amounts) {
+        assert_eq!(received[recipient], *amount, "{} paid wrongly", recipient);
+    }
+    assert_eq!(received[&filler], U256::from(20));
+}
+
+#[tokio::test]
+async fn mixed_workloads_stop_once_every_finite_workload_is_exhausted() {
+    let mock = MockRpc::start().await;
+    let signers = (0..2)
+        .map(|_| PrivateKeySigner::random())
+        .collect::<Vec<_>>();
+    let recipients = (0..7)
+        .map(|i| Address::with_last_byte(i + 1))
+        .collect::<Vec<_>>();
+    let amounts = vec![U256::from(5); 7];
+    let builders = split_recipients(&recipients, &amounts, 2)
+        .into_iter()
+        .zip(&signers)
+        .map(|((recipients, amounts), signer)| WeightedBuilder {
+            name: signer.address().to_string(),
+            weight: 1.0,
+            builder: Box::new(NativeTransferBuilder {
+                signer: signer.clone(),
+                provider: mock.provider(),
+                recipients,
+                amounts,
+            }),
+            finite: true,
+        })
+        .collect::<Vec<_>>();
+
+    ContinuousDistributor {
+        signer: signers[0].clone(),
+    }
+    .send_transactions(
+        vec![mock.url.clone()],
+        Box::new(MixedBuilder::new(builders, 3)),
+        test_options(&mock, 1),
+    )
+    .await
+    .unwrap();
+
+    let mut paid = mock
+        .sent()
+        .iter()
+        .map(|tx| tx.to.unwrap())
+        .collect::<Vec<_>>();
+    paid.sort();
+    assert_eq!(paid, recipients);
+}
+
+#[tokio::test]
+async fn mixed_workloads_stop_once_any_signer_is_below_the_balance_floor() {
+    let mock = MockRpc::start().await;
+    let signers = (0..2)
+        .map(|_| PrivateKeySigner::random())
+        .collect::<Vec<_>>();
+    mock.set_balance(
+        signers[0].address(),
+        U256::from(2_000_000_000_000_000_000u128),
+    );
+    mock.set_balance(
+        signers[1].address(),
+        U256::from(500_000_000_000_000_000u128),
+    );
+    let builders = signers
+        .iter()
+        .map(|signer| WeightedBuilder {
+            name: signer.address().to_string(),
+            weight: 1.0,
+            builder: Box::new(transfer_builder(&mock, signer, 2)),
+            finite: false,
+        })
+        .collect::<Vec<_>>();
+
+    let mut options = test_options(&mock, 1);
+    options.stop.max_transactions = Some(10);
+    options.stop.min_signer_balance = Some(1.0);
+    // The distributor's own signer is funded, but the second workload signer is not
+    ContinuousDistributor {
+        signer: signers[0].clone(),
+    }
+    .send_transactions(
+        vec![mock.url.clone()],
+        Box::new(MixedBuilder::new(builders, 4)),
+        options,
+    )
+    .await
+    .unwrap();
+
+    assert!(mock.sent().is_empty());
+}
+
+#[tokio::test]
+async fn finite_distributor_gives_up_on_a_batch_that_keeps_failing() {
+    let mock = MockRpc::start().await;
+    let signer = PrivateKeySigner::random();
+    for _ in 0..10 {
+        mock.push_fault(Fault::Drop);
+    }
+
+    let distributor = FiniteGroupDistributor {
+        signer: signer.clone(),
+    };
+    let err = distributor
+        .send_transactions(
+            vec![mock.url.clone()],
+            Box::new(transfer_builder(&mock, &signer, 3)),
+            test_options(&mock, 1),
+        )
+        .await
+        .unwrap_err();
+
+    assert!(err.to_string().contains("failed 6 times"), "{:#}", err);
+    assert!(mock.sent().is_empty());
+}
+
+#[tokio::test]
+async fn continuous_distributor_journals_batches_accepted_before_one_fails() {
+    let mock = MockRpc::start().await;
+    let signer = PrivateKeySigner::random();
+    let path = std::env::temp_dir().join(format!("failed-{}.jsonl", signer.address()));
+    let path = path.to_str().unwrap().to_string();
+    // The first batch goes through, then every attempt at the second one is dropped
+    mock.push_fault(Fault::Delay(Duration::ZERO));
+    for _ in 0..10 {
+        mock.push_fault(Fault::Drop);
+    }
+
+    let mut options = test_options(&mock, 2);
+    // One batch per second, so the first is done before the second is sent
+    options.target_tps = 2;
+    options.journal = Some(Arc::new(Journal::open(&path).unwrap()));
+    let result = ContinuousDistributor {
+        signer: signer.clone(),
+    }
+    .send_transactions(
+        vec![mock.url.clone()],
+        Box::new(transfer_builder(&mock, &signer, 4)),
+        options,
+    )
+    .await;
+    assert!(result.is_err());
+
+    let mut nonces = Journal::read(&path)
+        .unwrap()
+        .iter()
+        .map(|entry| entry.nonce)
+        .collect::<Vec<_>>();
+    std::fs::remove_file(&path).unwrap();
+    nonces.sort();
+    assert_eq!(nonces, vec![0, 1]);
+}
@@ -0,0 +1,116 @@
+mod common;
+
+use common::mock_rpc::MockRpc;
+use common::verify::test_recipients;
+use std::collections::HashSet;
+
+use distribution::addresses::Recipient;
+use distribution::allocation::allocate;
+use distribution::filters::{filter_recipients, retain_eligible, FilterReason};
+use distribution::prelude::*;
+
+const ETH: u128 = 1_000_000_000_000_000_000;
+
+#[tokio::test]
+async fn exclusion_files_drop_listed_addresses_without_chain_lookups() {
+    let mock = MockRpc::start().await;
+    let recipients = test_recipients(4);
+    let path = std::env::temp_dir().join(format!("{}-exclude.txt", std::process::id()));
+    std::fs::write(&path, format!("{}\n{}\n", recipients[1], recipients[3])).unwrap();
+    let path = path.to_str().unwrap().to_string();
+
+    let config = FilterConfig {
+        exclude_files: vec![path.clone()],
+        ..Default::default()
+    };
+    let filtered = filter_recipients(
+        &*mock.provider(),
+        &recipients,
+        &config,
+        &AddressesConfig::default(),
+    )
+    .await
+    .unwrap();
+    assert_eq!(filtered.len(), 2);
+    assert_eq!(
+        filtered[&recipients[1]],
+        FilterReason::Excluded(path.clone())
+    );
+    assert_eq!(filtered[&recipients[3]], FilterReason::Excluded(path));
+    assert_eq!(mock.requests(), 0);
+}
+
+#[tokio::test]
+async fn contracts_and_balances_outside_bounds_are_filtered() {
+    let mock = MockRpc::start().await;
+    let recipients = test_recipients(4);
+    mock.set_code(recipients[0], vec![0x60, 0x80].into());
+    mock.set_balance(recipients[1], U256::from(ETH / 100));
+    mock.set_balance(recipients[2], U256::from(ETH));
+    mock.set_balance(recipients[3], U256::from(20 * ETH));
+
+    let config = FilterConfig {
+        skip_contracts: true,
+        min_balance: Some(0.1),
+        max_balance: Some(10.0),
+        ..Default::default()
+    };
+    let filtered = filter_recipients(
+        &*mock.provider(),
+        &recipients,
+        &config,
+        &AddressesConfig::default(),
+    )
+    .await
+    .unwrap();
+    assert_eq!(filtered.len(), 3);
+    assert_eq!(filtered[&recipients[0]], FilterReason::HasCode);
+    assert_eq!(filtered[&recipients[1]], FilterReason::BelowMinBalance);
+    assert_eq!(filtered[&recipients[3]], FilterReason::AboveMaxBalance);
+}
+
+#[tokio::test]
+async fn budgets_are_split_between_the_recipients_left_after_filtering() {
+    let mock = MockRpc::start().await;
+    let addresses = test_recipients(4);
+    mock.set_code(addresses[1], vec![0x60, 0x80].into());
+    mock.set_code(addresses[2], vec![0x60, 0x80].into());
+    let recipients = addresses
+        .iter()
+        .map(|address| Recipient {
+            address: *address,
+            amount: None,
+            uri: None,
+            token_id: None,
+            label: None,
+            weight: None,
+        })
+        .collect::<Vec<_>>();
+
+    // The third recipient was paid by an earlier run, so it keeps its share unchecked
+    let config = FilterConfig {
+        skip_contracts: true,
+        ..Default::default()
+    };
+    let eligible = retain_eligible(
+        &*mock.provider(),
+        recipients,
+        &HashSet::from([addresses[2]]),
+        &config,
+        &AddressesConfig::default(),
+    )
+    .await
+    .unwrap();
+    assert_eq!(
+        eligible
+            .iter()
+            .map(|recipient| recipient.address)
+            .collect::<Vec<_>>(),
+        vec![addresses[0], addresses[2], addresses[3]]
+    );
+
+    let budget: AllocationConfig = serde_yaml::from_str("strategy: even\ntotal: 3").unwrap();
+    let amounts = allocate(&budget, &eligible).unwrap();
+    assert_eq!(amounts, vec![U256::from(ETH); 3]);
+    assert_eq!(amounts.iter().sum::<U256>(), U256::from(3 * ETH));
+}
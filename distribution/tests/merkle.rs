@@ -0,0 +1,199 @@
+mod common;
+
+use std::sync::Mutex;
+
+use alloy::primitives::B256;
+use alloy::sol_types::SolValue;
+use common::mock_rpc::MockRpc;
+use common::verify::{test_recipients, verify_transactions, Expected};
+use distribution::builders::merkle::{
+    MerkleClaimBuilder, MerkleDistributor, MerkleDistributorBuilder,
+};
+use distribution::merkle::{build_proofs, leaf, verify_proof};
+use distribution::prelude::*;
+
+const ETH: u128 = 1_000_000_000_000_000_000;
+
+fn builder(
+    signer: &PrivateKeySigner,
+    mock: &MockRpc,
+    distributor_address: Option<Address>,
+) -> MerkleDistributorBuilder {
+    MerkleDistributorBuilder {
+        signer: signer.clone(),
+        provider: mock.provider(),
+        distributor_address,
+        bytecode: Some(Bytes::from(vec![0x60, 0x80, 0x60, 0x40])),
+        root: B256::repeat_byte(0xab),
+        total: U256::from(3 * ETH),
+        fund: true,
+        distributor: Mutex::new(None),
+    }
+}
+
+#[test]
+fn every_proof_verifies_against_the_root() {
+    for count in [1, 2, 5, 8, 13] {
+        let accounts = test_recipients(count);
+        let amounts = (1..=count as u64)
+            .map(|i| U256::from(i * 1_000))
+            .collect::<Vec<_>>();
+        let proofs = build_proofs(&accounts, &amounts).unwrap();
+
+        assert_eq!(proofs.claims.len(), count);
+        assert_eq!(proofs.total, amounts.iter().sum::<U256>());
+        for claim in &proofs.claims {
+            let hash = leaf(claim.index, claim.account, claim.amount);
+            assert!(verify_proof(&claim.proof, proofs.root, hash));
+            // A different amount must not verify with the same proof
+            let forged = leaf(claim.index, claim.account, claim.amount + U256::from(1));
+            assert!(!verify_proof(&claim.proof, proofs.root, forged));
+        }
+    }
+    assert!(build_proofs(&[], &[]).is_err());
+}
+
+#[tokio::test]
+async fn merkle_builder_deploys_and_funds_a_new_distributor() {
+    let mock = MockRpc::start().await;
+    let signer = PrivateKeySigner::random();
+    mock.set_nonce(signer.address(), 7);
+    mock.set_gas_estimate(500_000);
+
+    let builder = builder(&signer, &mock, None);
+    let txs = builder.build_transactions(None).await.unwrap();
+
+    let expected = Expected::new(signer.address(), 7).total_value(U256::from(3 * ETH));
+    let decoded = verify_transactions(&txs, &expected);
+    assert_eq!(decoded.len(), 2);
+    assert_eq!(decoded[0].to, None);
+    assert!(decoded[0]
+        .input
+        .ends_with(B256::repeat_byte(0xab).as_slice()));
+    let distributor = signer.address().create(7);
+    assert_eq!(decoded[1].to, Some(distributor));
+    assert_eq!(decoded[1].value, U256::from(3 * ETH));
+    assert_eq!(*builder.distributor.lock().unwrap(), Some(distributor));
+}
+
+#[tokio::test]
+async fn merkle_builder_sets_root_on_existing_distributor() {
+    let mock = MockRpc::start().await;
+    let signer = PrivateKeySigner::random();
+    mock.set_gas_estimate(40_000);
+    let distributor = Address::with_last_byte(0xdd);
+
+    let builder = builder(&signer, &mock, Some(distributor));
+    let txs = builder.build_transactions(Some(3)).await.unwrap();
+
+    let expected = Expected::new(signer.address(), 3).total_value(U256::from(3 * ETH));
+    let decoded = verify_transactions(&txs, &expected);
+    assert_eq!(decoded[0].to, Some(distributor));
+    let call = MerkleDistributor::setMerkleRootCall::abi_decode(&decoded[0].input, true).unwrap();
+    assert_eq!(call.root, B256::repeat_byte(0xab));
+    assert_eq!(decoded[1].to, Some(distributor));
+
+    // The root has not been set on chain yet
+    mock.set_call_result::<MerkleDistributor::merkleRootCall>(distributor, B256::ZERO.abi_encode());
+    assert!(builder.verify().await.is_err());
+    mock.set_call_result::<MerkleDistributor::merkleRootCall>(
+        distributor,
+        B256::repeat_byte(0xab).abi_encode(),
+    );
+    builder.verify().await.unwrap();
+}
+
+#[tokio::test]
+async fn merkle_claim_builder_claims_unclaimed_on_behalf_of_recipients() {
+    let mock = MockRpc::start().await;
+    let signer = PrivateKeySigner::random();
+    let distributor = Address::with_last_byte(0xdd);
+    let accounts = test_recipients(5);
+    let amounts = vec![U256::from(ETH); 5];
+    let proofs = build_proofs(&accounts, &amounts).unwrap();
+    mock.set_call_result::<MerkleDistributor::merkleRootCall>(
+        distributor,
+        proofs.root.abi_encode(),
+    );
+    mock.set_call_result::<MerkleDistributor::isClaimedCall>(distributor, false.abi_encode());
+    let claimed = MerkleDistributor::isClaimedCall {
+        index: U256::from(1),
+    };
+    mock.set_exact_call_result(distributor, &claimed, true.abi_encode());
+
+    let builder = MerkleClaimBuilder::new(
+        signer.clone(),
+        mock.provider(),
+        distributor,
+        proofs.root,
+        proofs.claims.clone(),
+        true,
+    );
+    let txs = builder.build_transactions(None).await.unwrap();
+
+    let decoded = verify_transactions(&txs, &Expected::new(signer.address(), 0));
+    assert_eq!(decoded.len(), 4);
+    for (tx, signed) in decoded.iter().zip(&txs) {
+        assert_eq!(tx.to, Some(distributor));
+        let call = MerkleDistributor::claimCall::abi_decode(&tx.input, true).unwrap();
+        let claim = &proofs.claims[call.index.to::<usize>()];
+        assert_ne!(claim.index, 1);
+        assert_eq!(call.account, claim.account);
+        assert_eq!(call.amount, claim.amount);
+        assert_eq!(call.merkleProof, claim.proof);
+        // Journal entries must name the account being claimed for
+        assert_eq!(
+            builder.recipients()[signed.recipients.clone()],
+            [claim.account]
+        );
+    }
+
+    // Only claim 1 has been made so far
+    assert!(builder.verify().await.is_err());
+    mock.set_call_result::<MerkleDistributor::isClaimedCall>(distributor, true.abi_encode());
+    builder.verify().await.unwrap();
+}
+
+#[tokio::test]
+async fn merkle_claim_builder_rejects_proofs_for_another_root() {
+    let mock = MockRpc::start().await;
+    let signer = PrivateKeySigner::random();
+    let distributor = Address::with_last_byte(0xdd);
+    let proofs = build_proofs(&test_recipients(3), &[U256::from(ETH); 3]).unwrap();
+    mock.set_call_result::<MerkleDistributor::merkleRootCall>(
+        distributor,
+        B256::repeat_byte(0x01).abi_encode(),
+    );
+
+    let builder = MerkleClaimBuilder::new(
+        signer.clone(),
+        mock.provider(),
+        distributor,
+        proofs.root,
+        proofs.claims.clone(),
+        false,
+    );
+    assert!(builder.build_transactions(None).await.is_err());
+
+    // A tampered proof is caught before anything is sent
+    mock.set_call_result::<MerkleDistributor::merkleRootCall>(
+        distributor,
+        proofs.root.abi_encode(),
+    );
+    let mut claims = proofs.claims.clone();
+    claims[2].amount += U256::from(1);
+    let builder = MerkleClaimBuilder::new(
+        signer,
+        mock.provider(),
+        distributor,
+        proofs.root,
+        claims,
+        false,
+    );
+    let error = builder.build_transactions(None).await.unwrap_err();
+    assert!(
+        error.to_string().contains("Invalid proof for claim 2"),
+        "{}",
+        error
+    );
+}
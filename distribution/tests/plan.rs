@@ -0,0 +1,118 @@
+use distribution::distributors::continuous::ContinuousDistributor;
+use distribution::distributors::finite::{self, FiniteGroupDistributor};
+use distribution::plan::{Expectations, PlanSummary, TransactionPlan};
+use distribution::prelude::*;
+use serde_json::json;
+
+#[test]
+fn expectations_report_every_mismatch() {
+    let total = U256::from(3_000_000_000_000_000_000u128);
+    assert!(Expectations::default().mismatches(3, total).is_empty());
+
+    let matching = Expectations {
+        total: Some(total),
+        count: Some(3),
+    };
+    assert!(!matching.is_empty());
+    assert!(matching.mismatches(3, total).is_empty());
+
+    let mismatches = matching.mismatches(4, total + U256::from(1));
+    assert_eq!(mismatches.len(), 2);
+    assert!(mismatches[0].contains("expected 3 recipients, plan has 4"));
+    assert!(mismatches[1].contains("3000000000000000001"));
+}
+
+#[test]
+fn plan_summary_is_one_json_line_with_decimal_wei() {
+    let summary = PlanSummary {
+        distribution_type: "NativeBatch".to_string(),
+        signer: Address::with_last_byte(1),
+        recipients: 2,
+        total: U256::from(1_500_000_000_000_000_000u128).to_string(),
+        rpc_urls: vec!["https://testnet-rpc.monad.xyz".to_string()],
+        rpc_batch_size: 100,
+        target_tps: 10,
+        transactions: None,
+    };
+    let line = summary.to_json();
+    assert!(!line.contains('\n'));
+    let parsed: Value = serde_json::from_str(&line).unwrap();
+    assert_eq!(parsed["recipients"], json!(2));
+    assert!(parsed.get("transactions").is_none());
+    assert_eq!(parsed["total"], json!("1500000000000000000"));
+    assert_eq!(
+        parsed["signer"],
+        json!("0x0000000000000000000000000000000000000001")
+    );
+}
+
+fn signed_transfers(count: u64) -> Vec<SignedTransaction> {
+    let signer = PrivateKeySigner::random();
+    (0..count)
+        .map(|nonce| {
+            let tx = TxLegacy {
+                chain_id: Some(10143),
+                nonce,
+                gas_price: 2,
+                gas_limit: 21_000,
+                to: TxKind::Call(Address::with_last_byte(1)),
+                value: U256::from(10),
+                input: Bytes::new(),
+            };
+            SignedTransaction::sign_legacy(&signer, tx, 0..1).unwrap()
+        })
+        .collect()
+}
+
+fn options(rpc_batch_size: usize) -> DistributionOptions {
+    DistributionOptions {
+        rpc_batch_size,
+        ..Default::default()
+    }
+}
+
+#[test]
+fn transaction_plan_splits_into_rpc_batches_of_continuous_rounds() {
+    let txs = signed_transfers(5);
+    let distributor = ContinuousDistributor {
+        signer: PrivateKeySigner::random(),
+    };
+    let plan = TransactionPlan::new(&txs, &distributor.dispatch_groups(5, &options(2)));
+    assert_eq!(plan.count, 5);
+    assert_eq!(plan.gas_limit, 105_000);
+    assert_eq!(plan.value, "50");
+    assert_eq!(plan.max_fee_cost, "210000");
+    let batches = plan
+        .batches
+        .iter()
+        .map(|b| (b.transactions, b.first_nonce, b.last_nonce, b.gas_limit))
+        .collect::<Vec<_>>();
+    assert_eq!(
+        batches,
+        vec![(2, 0, 1, 42_000), (2, 2, 3, 42_000), (1, 4, 4, 21_000)]
+    );
+}
+
+#[test]
+fn transaction_plan_follows_the_groups_finite_runs_dispatch() {
+    let txs = signed_transfers(23);
+    let distributor = FiniteGroupDistributor {
+        signer: PrivateKeySigner::random(),
+    };
+    // The RPC batch size does not apply, as every transaction is its own request
+    let groups = distributor.dispatch_groups(23, &options(2));
+    assert_eq!(groups, vec![0..1, 1..11, 11..21, 21..23]);
+    assert_eq!(groups, finite::groups(23));
+
+    let plan = TransactionPlan::new(&txs, &groups);
+    let batches = plan
+        .batches
+        .iter()
+        .map(|b| (b.transactions, b.first_nonce, b.last_nonce))
+        .collect::<Vec<_>>();
+    assert_eq!(
+        batches,
+        vec![(1, 0, 0), (10, 1, 10), (10, 11, 20), (2, 21, 22)]
+    );
+    assert!(finite::groups(0).is_empty());
+}
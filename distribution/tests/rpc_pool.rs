@@ -0,0 +1,140 @@
+mod common;
+
+use std::time::Instant;
+
+use common::mock_rpc::MockRpc;
+use distribution::builders::native::NativeTransferBuilder;
+use distribution::distributors::finite::FiniteGroupDistributor;
+use distribution::prelude::*;
+
+fn endpoint(mock: &MockRpc, weight: f64, max_tps: Option<u64>) -> RpcEndpointConfig {
+    RpcEndpointConfig {
+        url: mock.url.clone(),
+        weight,
+        max_tps,
+    }
+}
+
+#[tokio::test]
+async fn pool_routes_by_weight() {
+    let a = MockRpc::start().await;
+    let b = MockRpc::start().await;
+    let pool = RpcPool::new(
+        &[a.url.clone(), b.url.clone()],
+        RpcPoolConfig {
+            endpoints: vec![endpoint(&a, 3.0, None)],
+            ..Default::default()
+        },
+    );
+    pool.check_health(&Client::new()).await;
+
+    let mut routed = (0, 0);
+    for _ in 0..8 {
+        if pool.select(1).await.unwrap() == a.url {
+            routed.0 += 1;
+        } else {
+            routed.1 += 1;
+        }
+    }
+    assert_eq!(routed, (6, 2));
+}
+
+#[tokio::test]
+async fn pool_excludes_lagging_and_unreachable_endpoints() {
+    let healthy = MockRpc::start().await;
+    let lagging = MockRpc::start().await;
+    healthy.set_block_number(100);
+    lagging.set_block_number(50);
+    let unreachable = "http://127.0.0.1:1".to_string();
+    let pool = RpcPool::new(
+        &[unreachable, lagging.url.clone(), healthy.url.clone()],
+        RpcPoolConfig::default(),
+    );
+    pool.check_health(&Client::new()).await;
+
+    let health = pool.health();
+    assert!(!health[0].healthy);
+    assert!(!health[1].healthy);
+    assert!(health[2].healthy);
+    assert_eq!(health[2].block_number, Some(100));
+    assert_eq!(pool.primary().unwrap(), healthy.url);
+    for _ in 0..5 {
+        assert_eq!(pool.select(1).await.unwrap(), healthy.url);
+    }
+
+    // Catching up makes the lagging endpoint eligible again
+    lagging.set_block_number(99);
+    pool.check_health(&Client::new()).await;
+    assert!(pool.health()[1].healthy);
+}
+
+#[tokio::test]
+async fn pool_holds_batches_over_an_endpoint_tps_cap() {
+    let mock = MockRpc::start().await;
+    let pool = RpcPool::new(
+        std::slice::from_ref(&mock.url),
+        RpcPoolConfig {
+            endpoints: vec![endpoint(&mock, 1.0, Some(4))],
+            ..Default::default()
+        },
+    );
+
+    let start = Instant::now();
+    pool.select(2).await.unwrap();
+    pool.select(2).await.unwrap();
+    assert!(start.elapsed() < Duration::from_millis(500));
+    pool.select(2).await.unwrap();
+    assert!(start.elapsed() >= Duration::from_millis(900));
+}
+
+#[tokio::test]
+async fn pool_fails_when_no_endpoint_can_be_picked() {
+    let empty = RpcPool::new(&[], RpcPoolConfig::default());
+    assert!(empty.primary().is_err());
+    assert!(empty.select(1).await.is_err());
+
+    let mock = MockRpc::start().await;
+    let unweighted = RpcPool::new(
+        std::slice::from_ref(&mock.url),
+        RpcPoolConfig {
+            endpoints: vec![endpoint(&mock, 0.0, None)],
+            ..Default::default()
+        },
+    );
+    assert_eq!(unweighted.primary().unwrap(), mock.url);
+    let error = tokio::time::timeout(Duration::from_secs(1), unweighted.select(1))
+        .await
+        .expect("select kept waiting")
+        .unwrap_err();
+    assert!(error.to_string().contains("weight above 0"), "{}", error);
+}
+
+#[tokio::test]
+async fn finite_distributor_only_sends_to_healthy_endpoints() {
+    let healthy = MockRpc::start().await;
+    let lagging = MockRpc::start().await;
+    healthy.set_block_number(100);
+    let signer = PrivateKeySigner::random();
+
+    let builder = NativeTransferBuilder {
+        signer: signer.clone(),
+        provider: healthy.provider(),
+        recipients: (0..6).map(|i| Address::with_last_byte(i + 1)).collect(),
+        amounts: vec![U256::from(1_000_000_000_000_000u128); 6],
+    };
+    let rpc_urls = vec![lagging.url.clone(), healthy.url.clone()];
+    let options = DistributionOptions {
+        rpc_urls: rpc_urls.clone(),
+        rpc_batch_size: 1,
+        target_tps: 10_000,
+        confirmation_timeout: 5,
+        ..Default::default()
+    };
+    FiniteGroupDistributor { signer }
+        .send_transactions(rpc_urls, Box::new(builder), options)
+        .await
+        .unwrap();
+
+    assert_eq!(healthy.sent().len(), 6);
+    assert!(lagging.sent().is_empty());
+}
@@ -0,0 +1,186 @@
+use distribution::validate::{load_config, ConfigError};
+
+fn write(name: &str, contents: &str) -> String {
+    let path = std::env::temp_dir().join(format!("validate-{}-{}", std::process::id(), name));
+    std::fs::write(&path, contents).unwrap();
+    path.to_str().unwrap().to_string()
+}
+
+fn find<'a>(errors: &'a [ConfigError], field: &str) -> &'a ConfigError {
+    errors
+        .iter()
+        .find(|e| e.field == field)
+        .unwrap_or_else(|| panic!("no error for {} in {:?}", field, errors))
+}
+
+#[test]
+fn reports_every_problem_with_its_line() {
+    let file = write(
+        "broken.yml",
+        "core:
+  rpc_urls:
+    - \"not a url\"
+  target_tps: 0
+  rpc_batch_size: 0
+  distribution_type: \"native-batch\"
+  addresses_file: \"/nonexistent/addresses.txt\"
+filters:
+  min_balance: 2.0
+  max_balance: 1.0
+",
+    );
+    let errors = load_config(&file, &[]).unwrap_err();
+
+    assert_eq!(find(&errors, "core.rpc_urls[0]").line, Some(2));
+    assert_eq!(find(&errors, "core.target_tps").line, Some(4));
+    assert_eq!(find(&errors, "core.rpc_batch_size").line, Some(5));
+    let addresses = find(&errors, "core.addresses_file");
+    assert_eq!(addresses.line, Some(7));
+    assert!(addresses.message.contains("does not exist"));
+    // A missing section points at nothing in the file
+    let token = find(&errors, "token");
+    assert_eq!(token.line, None);
+    assert!(token.message.contains("NativeBatch"));
+    assert_eq!(find(&errors, "filters.min_balance").line, Some(9));
+    assert!(errors[0].to_string().starts_with(&format!("{}:", file)));
+}
+
+#[test]
+fn reports_parse_errors_with_their_line() {
+    let file = write(
+        "unparseable.yml",
+        "core:
+  rpc_urls: [\"http://localhost:8545\"]
+  target_tps: lots
+",
+    );
+    let errors = load_config(&file, &[]).unwrap_err();
+    assert_eq!(errors.len(), 1);
+    assert_eq!(errors[0].line, Some(3));
+}
+
+#[test]
+fn checks_scenario_workloads() {
+    let scenario = write(
+        "scenario.yml",
+        "workloads:
+  - name: swaps
+    weight: 0
+    distribution_type: \"swapper\"
+    swapper:
+      swapper_address: \"0x0000000000000000000000000000000000000001\"
+      max_swaps: 10
+      amount:
+        distribution: uniform
+        low: 10
+        high: 1
+",
+    );
+    let file = write(
+        "scenario-main.yml",
+        &format!(
+            "core:
+  rpc_urls: [\"http://localhost:8545\"]
+  target_tps: 10
+  rpc_batch_size: 10
+  distribution_type: \"scenario\"
+  scenario_file: \"{}\"
+",
+            scenario
+        ),
+    );
+    let errors = load_config(&file, &[]).unwrap_err();
+    assert_eq!(errors.len(), 2);
+    assert_eq!(find(&errors, "workloads[0].weight").file, scenario);
+    assert_eq!(
+        find(&errors, "workloads[0].swapper.amount.low").line,
+        Some(10)
+    );
+
+    let valid = write(
+        "valid.yml",
+        "core:
+  rpc_urls: [\"http://localhost:8545\"]
+  target_tps: 10
+  rpc_batch_size: 10
+  distribution_type: \"native-direct\"
+  addresses_file: \"Cargo.toml\"
+",
+    );
+    assert!(load_config(&valid, &[]).is_ok());
+}
+
+#[test]
+fn rejects_signers_shared_between_workloads() {
+    let scenario = write(
+        "shared-signers.yml",
+        "workloads:
+  - name: first
+    weight: 1
+    distribution_type: \"strict-counter\"
+    strict_counter:
+      counter_address: \"0x0000000000000000000000000000000000000001\"
+      num_updates: 10
+  - name: second
+    weight: 1
+    distribution_type: \"strict-counter\"
+    signer_keys: [\"OTHER_KEY\", \"PRIVATE_KEY\"]
+    strict_counter:
+      counter_address: \"0x0000000000000000000000000000000000000002\"
+      num_updates: 10
+",
+    );
+    let file = write(
+        "shared-signers-main.yml",
+        &format!(
+            "core:
+  rpc_urls: [\"http://localhost:8545\"]
+  target_tps: 10
+  rpc_batch_size: 10
+  distribution_type: \"scenario\"
+  scenario_file: \"{}\"
+",
+            scenario
+        ),
+    );
+    let errors = load_config(&file, &[]).unwrap_err();
+    assert_eq!(errors.len(), 1);
+    // The first workload signs with PRIVATE_KEY by default
+    let shared = find(&errors, "workloads[1].signer_keys[1]");
+    assert_eq!(shared.line, Some(11));
+    assert!(shared.message.contains("first"));
+}
+
+#[test]
+fn rejects_token_sections_that_allocate_nothing() {
+    let addresses = write(
+        "unpaid-addresses.txt",
+        "0x0000000000000000000000000000000000000001\n0x0000000000000000000000000000000000000002\n",
+    );
+    let config = |addresses_file: &str| {
+        format!(
+            "core:
+  rpc_urls: [\"http://localhost:8545\"]
+  target_tps: 10
+  rpc_batch_size: 10
+  distribution_type: \"native-batch\"
+  addresses_file: \"{}\"
+token:
+  batch_sender_address: \"0x0000000000000000000000000000000000000003\"
+",
+            addresses_file
+        )
+    };
+    let file = write("unpaid.yml", &config(&addresses));
+    let errors = load_config(&file, &[]).unwrap_err();
+    assert_eq!(errors.len(), 1);
+    assert_eq!(find(&errors, "token.amount_per_address_high").line, Some(7));
+
+    // Amounts in the addresses file leave nothing to allocate
+    let paid = write(
+        "paid-addresses.csv",
+        "address,amount\n0x0000000000000000000000000000000000000001,1.5\n",
+    );
+    let file = write("paid.yml", &config(&paid));
+    assert!(load_config(&file, &[]).is_ok());
+}
@@ -0,0 +1,275 @@
+mod common;
+
+use alloy::json_abi::Function;
+use alloy::sol_types::SolEvent;
+use common::mock_rpc::{Fault, MockRpc};
+use common::verify::test_recipients;
+use distribution::builders::contract_call::ContractCallBuilder;
+use distribution::builders::native::{NativeBatchSenderBuilder, NativeTransferBuilder};
+use distribution::builders::nft::{SoulboundMinter, SoulboundNFTMintBuilder};
+use distribution::distributors::finite::FiniteGroupDistributor;
+use distribution::journal::JournalEntry;
+use distribution::prelude::*;
+use distribution::verify::{compare, mined_deliveries, Discrepancy, ExpectedAmount, Received};
+
+const ETH: u128 = 1_000_000_000_000_000_000;
+
+fn contract() -> Address {
+    Address::with_last_byte(0xcc)
+}
+
+/// Distribute through the finite distributor and return its journal
+async fn distribute(
+    mock: &MockRpc,
+    signer: &PrivateKeySigner,
+    builder: Box<dyn Builder + Send + Sync>,
+) -> Vec<JournalEntry> {
+    let path = std::env::temp_dir().join(format!("verify-{}.jsonl", signer.address()));
+    let path = path.to_str().unwrap().to_string();
+    let options = DistributionOptions {
+        rpc_urls: vec![mock.url.clone()],
+        rpc_batch_size: 1,
+        target_tps: 10_000,
+        confirmation_timeout: 5,
+        journal: Some(Arc::new(Journal::open(&path).unwrap())),
+        ..Default::default()
+    };
+    let distributor = FiniteGroupDistributor {
+        signer: signer.clone(),
+    };
+    distributor
+        .send_transactions(vec![mock.url.clone()], builder, options)
+        .await
+        .unwrap();
+    let entries = Journal::read(&path).unwrap();
+    std::fs::remove_file(&path).unwrap();
+    entries
+}
+
+#[tokio::test]
+async fn verify_reports_recipients_missed_by_a_run() {
+    let mock = MockRpc::start().await;
+    let signer = PrivateKeySigner::random();
+    mock.push_fault(Fault::Error {
+        code: -32000,
+        message: "nonce too low".to_string(),
+    });
+    let recipients = test_recipients(5);
+
+    let builder = NativeTransferBuilder {
+        signer: signer.clone(),
+        provider: mock.provider(),
+        recipients: recipients.clone(),
+        amounts: vec![U256::from(ETH / 2); 5],
+    };
+    let entries = distribute(&mock, &signer, Box::new(builder)).await;
+    let (deliveries, discrepancies) = mined_deliveries(&*mock.provider(), &entries).await.unwrap();
+    assert!(discrepancies.is_empty());
+    assert_eq!(deliveries.len(), 4);
+
+    let expected = recipients
+        .iter()
+        .map(|recipient| (*recipient, ExpectedAmount::Exact(U256::from(ETH / 2))))
+        .collect::<Vec<_>>();
+    let discrepancies = compare(&expected, &deliveries);
+    assert_eq!(discrepancies.len(), 1);
+    assert!(matches!(
+        discrepancies[0],
+        Discrepancy::Missing { recipient } if recipient == recipients[0]
+    ));
+}
+
+#[tokio::test]
+async fn verify_decodes_batch_sends_and_flags_wrong_amounts() {
+    let mock = MockRpc::start().await;
+    let signer = PrivateKeySigner::random();
+    let recipients = test_recipients(1_500);
+
+    let builder = NativeBatchSenderBuilder {
+        signer: signer.clone(),
+        provider: mock.provider(),
+        contract_address: contract(),
+        recipients: recipients.clone(),
+        amounts: vec![U256::from(ETH / 4); 1_500],
+        batch: BatchConfig::default(),
+        estimate_providers: vec![],
+    };
+    let entries = distribute(&mock, &signer, Box::new(builder)).await;
+    assert_eq!(entries.len(), 2);
+    mock.revert(entries[1].hash);
+
+    let (deliveries, discrepancies) = mined_deliveries(&*mock.provider(), &entries).await.unwrap();
+    assert_eq!(deliveries.len(), 1_000);
+    assert!(matches!(
+        discrepancies[..],
+        [Discrepancy::Reverted { hash }] if hash == entries[1].hash
+    ));
+
+    // The first recipient expected more, and the second is not in the addresses file
+    let mut expected = vec![(
+        recipients[0],
+        ExpectedAmount::Exact(U256::from(3 * ETH / 10)),
+    )];
+    expected.extend(recipients[2..1_000].iter().map(|recipient| {
+        (
+            *recipient,
+            ExpectedAmount::Range(U256::from(ETH / 5), U256::from(ETH / 4)),
+        )
+    }));
+    let discrepancies = compare(&expected, &deliveries);
+    assert_eq!(discrepancies.len(), 2);
+    assert!(matches!(
+        discrepancies[0],
+        Discrepancy::WrongAmount { recipient, received: Received::Native { amount }, .. }
+            if recipient == recipients[0] && amount == U256::from(ETH / 4)
+    ));
+    assert!(matches!(
+        discrepancies[1],
+        Discrepancy::Unexpected { recipient, .. } if recipient == recipients[1]
+    ));
+}
+
+#[tokio::test]
+async fn verify_matches_minted_tokens_from_transfer_logs() {
+    let mock = MockRpc::start().await;
+    let signer = PrivateKeySigner::random();
+    let recipients = test_recipients(3);
+
+    let builder = SoulboundNFTMintBuilder {
+        signer: signer.clone(),
+        provider: mock.provider(),
+        contract_address: contract(),
+        recipients: recipients.clone(),
+        image_url: "https://example.com/nft.json".to_string(),
+        batch: BatchConfig::default(),
+        estimate_providers: vec![],
+    };
+    let entries = distribute(&mock, &signer, Box::new(builder)).await;
+    assert_eq!(entries.len(), 1);
+
+    // The second recipient is minted twice and the third not at all
+    for (token_id, recipient) in [recipients[0], recipients[1], recipients[1]]
+        .into_iter()
+        .enumerate()
+    {
+        let transfer = SoulboundMinter::Transfer {
+            from: Address::ZERO,
+            to: recipient,
+            tokenId: U256::from(token_id),
+        };
+        mock.push_log(
+            entries[0].hash,
+            alloy::primitives::Log {
+                address: contract(),
+                data: transfer.encode_log_data(),
+            },
+        );
+    }
+
+    let (deliveries, _) = mined_deliveries(&*mock.provider(), &entries).await.unwrap();
+    assert_eq!(
+        deliveries[0].received,
+        Received::Token {
+            contract: contract(),
+            token_id: U256::ZERO
+        }
+    );
+
+    let expected = recipients
+        .iter()
+        .map(|recipient| (*recipient, ExpectedAmount::Token))
+        .collect::<Vec<_>>();
+    let discrepancies = compare(&expected, &deliveries);
+    assert_eq!(discrepancies.len(), 2);
+    assert!(matches!(
+        &discrepancies[0],
+        Discrepancy::Duplicate { recipient, hashes } if *recipient == recipients[1] && hashes.len() == 2
+    ));
+    assert!(matches!(
+        discrepancies[1],
+        Discrepancy::Missing { recipient } if recipient == recipients[2]
+    ));
+}
+
+#[tokio::test]
+async fn journal_records_the_row_each_contract_call_covers() {
+    let mock = MockRpc::start().await;
+    let signer = PrivateKeySigner::random();
+    let recipients = test_recipients(3);
+
+    let builder = ContractCallBuilder {
+        signer: signer.clone(),
+        provider: mock.provider(),
+        contract_address: contract(),
+        function: Function::parse("register(address account)").unwrap(),
+        args: vec![ArgSource::Column { index: 0 }],
+        rows: recipients
+            .iter()
+            .map(|address| vec![address.to_string()])
+            .collect(),
+        recipients: recipients.clone(),
+        max_calls: 0,
+        value: 0.0,
+    };
+    let entries = distribute(&mock, &signer, Box::new(builder)).await;
+
+    assert_eq!(entries.len(), 3);
+    for (entry, recipient) in entries.iter().zip(&recipients) {
+        assert_eq!(entry.recipients, vec![*recipient]);
+    }
+}
+
+#[tokio::test]
+async fn journal_records_the_recipients_a_run_kept_apart_from_its_entries() {
+    let mock = MockRpc::start().await;
+    let signer = PrivateKeySigner::random();
+    mock.push_fault(Fault::Error {
+        code: -32000,
+        message: "nonce too low".to_string(),
+    });
+    let recipients = test_recipients(3);
+    let path = std::env::temp_dir().join(format!("eligible-{}.jsonl", signer.address()));
+    let path = path.to_str().unwrap().to_string();
+    let journal = Arc::new(Journal::open(&path).unwrap());
+    journal.record_eligible(&recipients).unwrap();
+
+    let builder = NativeTransferBuilder {
+        signer: signer.clone(),
+        provider: mock.provider(),
+        recipients: recipients.clone(),
+        amounts: vec![U256::from(ETH); 3],
+    };
+    let options = DistributionOptions {
+        rpc_urls: vec![mock.url.clone()],
+        rpc_batch_size: 1,
+        target_tps: 10_000,
+        confirmation_timeout: 5,
+        journal: Some(journal),
+        ..Default::default()
+    };
+    FiniteGroupDistributor {
+        signer: signer.clone(),
+    }
+    .send_transactions(vec![mock.url.clone()], Box::new(builder), options)
+    .await
+    .unwrap();
+
+    // The rejected transfer leaves no entry, but its recipient is still expected
+    let entries = Journal::read(&path).unwrap();
+    assert_eq!(entries.len(), 2);
+    let eligible = Journal::read_eligible(&path).unwrap().unwrap();
+    std::fs::remove_file(&path).unwrap();
+    assert_eq!(eligible.len(), 3);
+
+    let (deliveries, _) = mined_deliveries(&*mock.provider(), &entries).await.unwrap();
+    let expected = recipients
+        .iter()
+        .filter(|recipient| eligible.contains(*recipient))
+        .map(|recipient| (*recipient, ExpectedAmount::Exact(U256::from(ETH))))
+        .collect::<Vec<_>>();
+    let discrepancies = compare(&expected, &deliveries);
+    assert!(matches!(
+        discrepancies[..],
+        [Discrepancy::Missing { recipient }] if recipient == recipients[0]
+    ));
+}
@@ -0,0 +1,128 @@
+mod common;
+
+use common::mock_rpc::MockRpc;
+use distribution::builders::native::NativeTransferBuilder;
+use distribution::distributors::finite::FiniteGroupDistributor;
+use distribution::prelude::*;
+use serde_json::json;
+
+fn request(id: usize, method: &str) -> JsonRpcRequest {
+    JsonRpcRequest {
+        jsonrpc: "2.0".to_string(),
+        id,
+        method: method.to_string(),
+        params: vec![],
+    }
+}
+
+#[tokio::test]
+async fn ws_client_pipelines_requests_keeping_their_ids() {
+    let mock = MockRpc::start().await;
+    mock.set_block_number(42);
+    let client = WsClient::connect(&mock.ws_url).await.unwrap();
+
+    let responses = client
+        .batch(&[
+            request(0, "eth_chainId"),
+            request(1, "eth_blockNumber"),
+            request(2, "eth_chainId"),
+        ])
+        .await
+        .unwrap();
+    assert_eq!(responses.len(), 3);
+    for (i, response) in responses.iter().enumerate() {
+        assert_eq!(response["id"], json!(i));
+    }
+    assert_eq!(responses[0]["result"], "0x279f");
+    assert_eq!(responses[1]["result"], "0x2a");
+
+    let block = client.request("eth_blockNumber", json!([])).await.unwrap();
+    assert_eq!(block, "0x2a");
+}
+
+#[tokio::test]
+async fn ws_subscriptions_deliver_new_heads_and_pending_transactions() {
+    let mock = MockRpc::start().await;
+    let signer = PrivateKeySigner::random();
+    let client = WsClient::connect(&mock.ws_url).await.unwrap();
+    let mut heads = client.subscribe_new_heads().await.unwrap();
+    let mut pending = client.subscribe_pending_transactions().await.unwrap();
+
+    mock.publish_new_head();
+    mock.publish_new_head();
+    assert_eq!(heads.recv().await.unwrap()["number"], "0x2");
+    assert_eq!(heads.recv().await.unwrap()["number"], "0x3");
+
+    let builder = NativeTransferBuilder {
+        signer: signer.clone(),
+        provider: mock.provider(),
+        recipients: vec![Address::with_last_byte(1)],
+        amounts: vec![U256::from(1)],
+    };
+    let tx = builder.build_transactions(None).await.unwrap().remove(0);
+    let mut send = request(0, "eth_sendRawTransaction");
+    send.params = vec![tx.to_hex()];
+    client.batch(&[send]).await.unwrap();
+    assert_eq!(pending.recv().await.unwrap(), json!(tx.hash));
+}
+
+#[tokio::test]
+async fn finite_distributor_sends_and_confirms_over_websocket() {
+    let mock = MockRpc::start().await;
+    mock.set_block_number(10);
+    let signer = PrivateKeySigner::random();
+    let builder = NativeTransferBuilder {
+        signer: signer.clone(),
+        provider: mock.provider(),
+        recipients: (0..12).map(|i| Address::with_last_byte(i + 1)).collect(),
+        amounts: vec![U256::from(1_000_000_000_000_000u128); 12],
+    };
+
+    let rpc_urls = vec![mock.ws_url.clone()];
+    let options = DistributionOptions {
+        rpc_urls: rpc_urls.clone(),
+        rpc_batch_size: 1,
+        target_tps: 10_000,
+        confirmation_timeout: 5,
+        ..Default::default()
+    };
+    FiniteGroupDistributor { signer }
+        .send_transactions(rpc_urls, Box::new(builder), options)
+        .await
+        .unwrap();
+
+    assert_eq!(mock.sent().len(), 12);
+    let block = wait_for_transaction(mock.ws_url.clone(), mock.sent()[11].hash, 1)
+        .await
+        .unwrap();
+    assert_eq!(block, Some(12));
+}
+
+#[tokio::test]
+async fn pool_connects_to_other_endpoints_while_one_stalls() {
+    let mock = MockRpc::start().await;
+    // Accepts connections but never answers the WebSocket handshake
+    let stalled = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let stalled_url = format!("ws://{}", stalled.local_addr().unwrap());
+    tokio::spawn(async move {
+        let mut streams = Vec::new();
+        while let Ok((stream, _)) = stalled.accept().await {
+            streams.push(stream);
+        }
+    });
+    let pool = Arc::new(RpcPool::new(
+        &[stalled_url.clone(), mock.ws_url.clone()],
+        RpcPoolConfig::default(),
+    ));
+
+    let start = std::time::Instant::now();
+    let stalled_pool = pool.clone();
+    let stalled_client =
+        tokio::spawn(async move { stalled_pool.ws_client(&stalled_url).await.map(|_| ()) });
+    sleep(Duration::from_millis(100)).await;
+    pool.ws_client(&mock.ws_url).await.unwrap();
+    assert!(start.elapsed() < Duration::from_secs(2));
+
+    let error = stalled_client.await.unwrap().unwrap_err();
+    assert!(error.to_string().contains("timed out"), "{}", error);
+}
@@ -0,0 +1,21 @@
+// SPDX-License-Identifier: UNLICENSED
+pragma solidity ^0.8.13;
+
+import {Script, console} from "forge-std/Script.sol";
+import {MerkleDistributor} from "../src/MerkleDistributor.sol";
+
+contract MerkleDistributorScript is Script {
+    MerkleDistributor public distributor;
+
+    function setUp() public {}
+
+    function run() public {
+        uint256 deployerPrivateKey = vm.envUint("PRIVATE_KEY");
+        bytes32 root = vm.envBytes32("MERKLE_ROOT");
+        vm.startBroadcast(deployerPrivateKey);
+
+        distributor = new MerkleDistributor(root);
+
+        vm.stopBroadcast();
+    }
+}
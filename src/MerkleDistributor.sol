@@ -0,0 +1,89 @@
+// SPDX-License-Identifier: MIT
+pragma solidity ^0.8.22;
+
+import {MerkleProof} from "@openzeppelin/contracts/utils/cryptography/MerkleProof.sol";
+
+/**
+ * @title MerkleDistributor
+ * @dev Holds native tokens that recipients, or anyone on their behalf, claim with a Merkle proof.
+ * Leaves are keccak256(abi.encodePacked(index, account, amount)) and pairs are hashed sorted.
+ */
+contract MerkleDistributor {
+    bytes32 public merkleRoot;
+
+    address public owner;
+
+    // Packed array of booleans per root, one bit per claim index, so a new root starts unclaimed
+    mapping(bytes32 => mapping(uint256 => uint256)) private claimedBitMap;
+
+    event Claimed(uint256 index, address account, uint256 amount);
+
+    /// @dev Only callable by the contract owner.
+    error OnlyOwner();
+
+    /// @dev The claim at this index was already paid out
+    error AlreadyClaimed();
+
+    /// @dev The proof does not match the Merkle root
+    error InvalidProof();
+
+    /// @dev Sending native tokens failed
+    error TransferFailed();
+
+    modifier onlyOwner() {
+        if (msg.sender != owner) revert OnlyOwner();
+        _;
+    }
+
+    constructor(bytes32 root) {
+        owner = msg.sender;
+        merkleRoot = root;
+    }
+
+    receive() external payable {}
+
+    /**
+     * @dev Replace the Merkle root. Claims are tracked per root, so every index of the new tree
+     * can be claimed once regardless of what was claimed under the previous one
+     * @param root The new Merkle root
+     */
+    function setMerkleRoot(bytes32 root) external onlyOwner {
+        merkleRoot = root;
+    }
+
+    /// @dev Whether the claim at this index of the current tree was paid out
+    function isClaimed(uint256 index) public view returns (bool) {
+        uint256 word = claimedBitMap[merkleRoot][index / 256];
+        uint256 mask = 1 << (index % 256);
+        return word & mask == mask;
+    }
+
+    /**
+     * @dev Pay out a claim to its account, callable by anyone
+     * @param index Index of the claim in the tree
+     * @param account The account receiving the tokens
+     * @param amount The amount of native tokens to pay
+     * @param merkleProof Sibling hashes from the leaf up to the root
+     */
+    function claim(uint256 index, address account, uint256 amount, bytes32[] calldata merkleProof) external {
+        if (isClaimed(index)) revert AlreadyClaimed();
+
+        bytes32 leaf = keccak256(abi.encodePacked(index, account, amount));
+        if (!MerkleProof.verify(merkleProof, merkleRoot, leaf)) revert InvalidProof();
+
+        claimedBitMap[merkleRoot][index / 256] |= 1 << (index % 256);
+        (bool success,) = account.call{value: amount}("");
+        if (!success) revert TransferFailed();
+
+        emit Claimed(index, account, amount);
+    }
+
+    /**
+     * @dev Recover unclaimed tokens
+     * @param to The address receiving the remaining balance
+     */
+    function withdraw(address to) external onlyOwner {
+        (bool success,) = to.call{value: address(this).balance}("");
+        if (!success) revert TransferFailed();
+    }
+}
@@ -0,0 +1,84 @@
+// SPDX-License-Identifier: MIT
+pragma solidity ^0.8.22;
+
+import {Test} from "forge-std/Test.sol";
+import {MerkleDistributor} from "../src/MerkleDistributor.sol";
+
+contract MerkleDistributorTest is Test {
+    MerkleDistributor public distributor;
+    address public alice;
+    address public bob;
+
+    function setUp() public {
+        alice = makeAddr("alice");
+        bob = makeAddr("bob");
+        distributor = new MerkleDistributor(bytes32(0));
+        vm.deal(address(distributor), 10 ether);
+    }
+
+    function leaf(uint256 index, address account, uint256 amount) internal pure returns (bytes32) {
+        return keccak256(abi.encodePacked(index, account, amount));
+    }
+
+    // Root of a two-leaf tree, whose proofs are each the other leaf
+    function pairRoot(bytes32 a, bytes32 b) internal pure returns (bytes32) {
+        return a < b ? keccak256(abi.encodePacked(a, b)) : keccak256(abi.encodePacked(b, a));
+    }
+
+    function proof(bytes32 sibling) internal pure returns (bytes32[] memory siblings) {
+        siblings = new bytes32[](1);
+        siblings[0] = sibling;
+    }
+
+    function testClaimPaysOnce() public {
+        bytes32 aliceLeaf = leaf(0, alice, 1 ether);
+        bytes32 bobLeaf = leaf(1, bob, 2 ether);
+        distributor.setMerkleRoot(pairRoot(aliceLeaf, bobLeaf));
+
+        distributor.claim(0, alice, 1 ether, proof(bobLeaf));
+        assertEq(alice.balance, 1 ether);
+        assertTrue(distributor.isClaimed(0));
+        assertFalse(distributor.isClaimed(1));
+
+        vm.expectRevert(MerkleDistributor.AlreadyClaimed.selector);
+        distributor.claim(0, alice, 1 ether, proof(bobLeaf));
+    }
+
+    function testClaimRejectsInvalidProof() public {
+        bytes32 aliceLeaf = leaf(0, alice, 1 ether);
+        bytes32 bobLeaf = leaf(1, bob, 2 ether);
+        distributor.setMerkleRoot(pairRoot(aliceLeaf, bobLeaf));
+
+        vm.expectRevert(MerkleDistributor.InvalidProof.selector);
+        distributor.claim(0, alice, 2 ether, proof(bobLeaf));
+    }
+
+    function testNewRootStartsUnclaimed() public {
+        bytes32 firstAlice = leaf(0, alice, 1 ether);
+        bytes32 firstBob = leaf(1, bob, 2 ether);
+        bytes32 first = pairRoot(firstAlice, firstBob);
+        distributor.setMerkleRoot(first);
+        distributor.claim(0, alice, 1 ether, proof(firstBob));
+
+        // Index 0 belongs to bob in the second tree and must not look claimed
+        bytes32 secondBob = leaf(0, bob, 3 ether);
+        bytes32 secondAlice = leaf(1, alice, 1 ether);
+        distributor.setMerkleRoot(pairRoot(secondBob, secondAlice));
+        assertFalse(distributor.isClaimed(0));
+        distributor.claim(0, bob, 3 ether, proof(secondAlice));
+        assertEq(bob.balance, 3 ether);
+        assertTrue(distributor.isClaimed(0));
+
+        // Going back to the first root keeps the claims made under it
+        distributor.setMerkleRoot(first);
+        assertTrue(distributor.isClaimed(0));
+        vm.expectRevert(MerkleDistributor.AlreadyClaimed.selector);
+        distributor.claim(0, alice, 1 ether, proof(firstBob));
+    }
+
+    function testOnlyOwnerSetsRoot() public {
+        vm.prank(alice);
+        vm.expectRevert(MerkleDistributor.OnlyOwner.selector);
+        distributor.setMerkleRoot(bytes32(uint256(1)));
+    }
+}